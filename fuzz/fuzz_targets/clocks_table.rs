@@ -0,0 +1,9 @@
+#![no_main]
+
+use amdgpu_sysfs::gpu_handle::overdrive::ClocksTableGen;
+use libfuzzer_sys::fuzz_target;
+use std::str::FromStr;
+
+fuzz_target!(|data: &str| {
+    let _ = ClocksTableGen::from_str(data);
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use amdgpu_sysfs::gpu_handle::overdrive::{ClocksTable, ClocksTableGen};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|tables: (ClocksTableGen, ClocksTableGen)| {
+    let (new_table, old_table) = tables;
+    let mut buf = Vec::new();
+    let _ = new_table.write_commands(&mut buf, &old_table);
+});
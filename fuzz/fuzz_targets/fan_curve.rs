@@ -0,0 +1,11 @@
+#![no_main]
+
+use amdgpu_sysfs::gpu_handle::fan_control::FanCurve;
+use libfuzzer_sys::fuzz_target;
+use std::str::FromStr;
+
+fuzz_target!(|data: &str| {
+    if let Ok(curve) = FanCurve::from_str(data) {
+        let _ = curve.validate();
+    }
+});
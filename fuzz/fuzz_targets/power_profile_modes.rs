@@ -0,0 +1,8 @@
+#![no_main]
+
+use amdgpu_sysfs::gpu_handle::power_profile_mode::PowerProfileModesTable;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let _ = PowerProfileModesTable::parse(data);
+});
@@ -0,0 +1,117 @@
+//! Demo GPUs backed by bundled SysFS data
+//!
+//! This allows developing and taking screenshots of applications on machines without AMD hardware.
+//! The data is copied into a temporary directory, so values written through the handle can be read back.
+//!
+//! ```
+//! use amdgpu_sysfs::demo::{DemoData, DemoGpu};
+//!
+//! let demo = DemoData::new(DemoGpu::Rx580).unwrap();
+//! let gpu_handle = demo.gpu_handle().unwrap();
+//! assert_eq!(gpu_handle.get_pci_id(), Some(("1002", "67DF")));
+//! ```
+use crate::{error::ErrorContext, gpu_handle::GpuHandle, sysfs::SysFS, Result};
+use rust_embed::RustEmbed;
+use std::{fs, path::Path};
+use tempfile::TempDir;
+
+#[derive(RustEmbed)]
+#[folder = "tests/data/rx580/"]
+struct Rx580;
+
+#[derive(RustEmbed)]
+#[folder = "tests/data/rx7800xt/"]
+struct Rx7800Xt;
+
+/// A GPU with bundled data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DemoGpu {
+    /// Radeon RX 580 (Polaris)
+    Rx580,
+    /// Radeon RX 7800 XT (RDNA 3)
+    Rx7800Xt,
+}
+
+impl DemoGpu {
+    /// All of the available demo GPUs.
+    pub const ALL: [DemoGpu; 2] = [DemoGpu::Rx580, DemoGpu::Rx7800Xt];
+
+    fn files(self) -> Vec<(String, Vec<u8>)> {
+        fn collect<A: RustEmbed>() -> Vec<(String, Vec<u8>)> {
+            A::iter()
+                .filter_map(|name| {
+                    let file = A::get(&name)?;
+                    Some((name.into_owned(), file.data.into_owned()))
+                })
+                .collect()
+        }
+
+        match self {
+            DemoGpu::Rx580 => collect::<Rx580>(),
+            DemoGpu::Rx7800Xt => collect::<Rx7800Xt>(),
+        }
+    }
+}
+
+/// The SysFS data of a demo GPU in a temporary directory. The directory is removed when this is dropped,
+/// so it needs to be kept around for as long as the handles created from it are used.
+#[derive(Debug)]
+pub struct DemoData {
+    gpu: DemoGpu,
+    temp_dir: TempDir,
+}
+
+impl DemoData {
+    /// Copies the data of the given GPU into a new temporary directory.
+    pub fn new(gpu: DemoGpu) -> Result<Self> {
+        let temp_dir = TempDir::new().context("Could not create temporary directory")?;
+
+        for (name, contents) in gpu.files() {
+            let path = temp_dir.path().join(name);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, contents)?;
+        }
+
+        Ok(Self { gpu, temp_dir })
+    }
+
+    /// Creates the data for all of the demo GPUs.
+    pub fn all() -> Result<Vec<Self>> {
+        DemoGpu::ALL.into_iter().map(Self::new).collect()
+    }
+
+    /// Gets the GPU whose data is used.
+    pub fn gpu(&self) -> DemoGpu {
+        self.gpu
+    }
+
+    /// Creates a handle for the demo GPU.
+    pub fn gpu_handle(&self) -> Result<GpuHandle> {
+        GpuHandle::new_from_path(self.temp_dir.path().to_path_buf())
+    }
+}
+
+impl SysFS for DemoData {
+    fn get_path(&self) -> &Path {
+        self.temp_dir.path()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DemoData, DemoGpu};
+
+    #[test]
+    fn all_demo_gpus() {
+        let demos = DemoData::all().unwrap();
+        assert_eq!(demos.len(), DemoGpu::ALL.len());
+
+        for demo in demos {
+            let gpu_handle = demo.gpu_handle().unwrap();
+            assert_eq!(gpu_handle.get_driver(), "amdgpu");
+            assert!(!gpu_handle.hw_monitors.is_empty(), "{:?}", demo.gpu());
+        }
+    }
+}
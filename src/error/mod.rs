@@ -18,6 +18,7 @@ pub struct Error {
 
 /// Possible types of errors
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ErrorKind {
     /// It is not allowed to perform the given action
     NotAllowed(String),
@@ -34,6 +35,13 @@ pub enum ErrorKind {
     },
     /// An IO error
     IoError(std::io::Error),
+    /// The kernel accepted a write, but the value was not applied
+    Rejected {
+        /// The value that was written
+        requested: String,
+        /// The value that was read back
+        actual: String,
+    },
 }
 
 impl Error {
@@ -71,6 +79,10 @@ impl Display for Error {
             ErrorKind::ParseError { msg, line } => write!(f, "parse error: {msg} at line {line}")?,
             ErrorKind::IoError(error) => write!(f, "io error: {error}")?,
             ErrorKind::Unsupported(err) => write!(f, "unsupported: {err}")?,
+            ErrorKind::Rejected { requested, actual } => write!(
+                f,
+                "rejected by the kernel: requested '{requested}', but the current value is '{actual}'"
+            )?,
         }
 
         if let Some(ctx) = &self.context {
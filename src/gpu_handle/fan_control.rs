@@ -2,38 +2,319 @@
 //! Only for Navi 3x (RDNA 3) and newer. Older GPUs have to use the HwMon interface.
 use crate::{
     error::{Error, ErrorKind},
+    gpu_handle::normalize_whitespace,
     Result,
 };
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::Write, ops::RangeInclusive};
+use std::{collections::HashMap, fmt::Write, ops::RangeInclusive, str::FromStr};
 
 /// Information about fan characteristics.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FanInfo {
     /// Current value
     pub current: u32,
-    /// Minimum and maximum allowed values.
+    /// Range of allowed values.
     /// This is empty if changes to the value are not supported.
-    pub allowed_range: Option<(u32, u32)>,
+    pub allowed_range: Option<RangeInclusive<u32>>,
+}
+
+/// Values of the PMFW fan control settings in `gpu_od/fan_ctrl`.
+/// Each value is `None` if the setting is not available on the GPU.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct PmfwSettings {
+    /// Acoustic limit in RPM
+    pub acoustic_limit: Option<u32>,
+    /// Acoustic target in RPM
+    pub acoustic_target: Option<u32>,
+    /// Target temperature in degrees
+    pub target_temperature: Option<u32>,
+    /// Minimum PWM as a percentage
+    pub minimum_pwm: Option<u32>,
+    /// Whether zero RPM mode is enabled
+    pub zero_rpm_enable: Option<bool>,
+    /// Zero RPM stop temperature in degrees
+    pub zero_rpm_stop_temperature: Option<u32>,
+    /// Fan curve
+    pub curve: Option<FanCurve>,
+}
+
+/// Point of a fan curve
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct CurvePoint {
+    /// Temperature in degrees
+    pub temperature: i32,
+    /// Fan speed as a percentage
+    pub speed: u8,
+}
+
+impl CurvePoint {
+    /// Creates a new curve point.
+    pub const fn new(temperature: i32, speed: u8) -> Self {
+        Self { temperature, speed }
+    }
+}
+
+impl From<(i32, u8)> for CurvePoint {
+    fn from((temperature, speed): (i32, u8)) -> Self {
+        Self::new(temperature, speed)
+    }
 }
 
 /// Custom fan curve
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct FanCurve {
-    /// Fan curve points in the (temperature, speed) format
+    /// Fan curve points.
     /// This is a boxed slice as the number of curve points cannot be modified, only their values can be.
-    pub points: Box<[(i32, u8)]>,
+    pub points: Box<[CurvePoint]>,
     /// Allowed value ranges.
     /// Empty when changes to the fan curve are not supported.
     pub allowed_ranges: Option<FanCurveRanges>,
 }
 
+impl FanCurve {
+    /// Creates a builder for a fan curve.
+    pub fn builder() -> FanCurveBuilder {
+        FanCurveBuilder::new()
+    }
+
+    /// Checks if the firmware is using its built-in fan control instead of a custom curve.
+    ///
+    /// In this case the kernel reports all of the points as 0 degrees and 0%, which is not real curve data.
+    pub fn is_default(&self) -> bool {
+        self.points
+            .iter()
+            .all(|point| *point == CurvePoint::new(0, 0))
+    }
+
+    /// Gets the number of points supported by the firmware.
+    /// This is defined by the GPU and cannot be changed, so it can be used to show the right number of editable points.
+    pub fn point_count(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Gets the range of values which the point at the given index can be set to,
+    /// so that the curve stays within the allowed ranges and non-decreasing (see [`FanCurve::validate`]).
+    ///
+    /// Returns `None` if the point does not exist or if changes to the curve are not supported.
+    pub fn point_ranges(&self, index: usize) -> Option<FanCurveRanges> {
+        let ranges = self.allowed_ranges.as_ref()?;
+        if index >= self.points.len() {
+            return None;
+        }
+
+        let mut min_temp = *ranges.temperature_range.start();
+        let mut max_temp = *ranges.temperature_range.end();
+        let mut min_speed = *ranges.speed_range.start();
+        let mut max_speed = *ranges.speed_range.end();
+
+        if let Some(prev) = index.checked_sub(1).map(|i| self.points[i]) {
+            min_temp = min_temp.max(prev.temperature);
+            min_speed = min_speed.max(prev.speed);
+        }
+        if let Some(next) = self.points.get(index + 1) {
+            max_temp = max_temp.min(next.temperature);
+            max_speed = max_speed.min(next.speed);
+        }
+
+        Some(FanCurveRanges {
+            temperature_range: min_temp..=max_temp,
+            speed_range: min_speed..=max_speed,
+        })
+    }
+
+    /// Gets the fan speed at the given temperature by linearly interpolating between the curve points.
+    /// Temperatures outside of the curve use the speed of the closest point.
+    ///
+    /// The points are expected to be sorted by temperature, see [`FanCurve::sorted`].
+    /// Returns `None` if the curve has no points.
+    pub fn interpolate(&self, temperature: i32) -> Option<u8> {
+        let first = self.points.first()?;
+        if temperature <= first.temperature {
+            return Some(first.speed);
+        }
+
+        for window in self.points.windows(2) {
+            let CurvePoint {
+                temperature: start_temp,
+                speed: start_speed,
+            } = window[0];
+            let CurvePoint {
+                temperature: end_temp,
+                speed: end_speed,
+            } = window[1];
+
+            if temperature <= end_temp {
+                if end_temp == start_temp {
+                    return Some(end_speed);
+                }
+                let ratio = f64::from(temperature - start_temp) / f64::from(end_temp - start_temp);
+                let speed = f64::from(start_speed)
+                    + (f64::from(end_speed) - f64::from(start_speed)) * ratio;
+                return Some(speed.round() as u8);
+            }
+        }
+
+        self.points.last().map(|point| point.speed)
+    }
+
+    /// Checks that the temperatures and speeds are non-decreasing between points,
+    /// and that all of the values are within the allowed ranges (if they are known).
+    pub fn validate(&self) -> Result<()> {
+        for (i, window) in self.points.windows(2).enumerate() {
+            let (prev, point) = (window[0], window[1]);
+
+            if point.temperature < prev.temperature {
+                return Err(Error::not_allowed(format!(
+                    "Temperature {} at point {} is lower than the previous point's {}",
+                    point.temperature,
+                    i + 1,
+                    prev.temperature
+                )));
+            }
+            if point.speed < prev.speed {
+                return Err(Error::not_allowed(format!(
+                    "Speed {} at point {} is lower than the previous point's {}",
+                    point.speed,
+                    i + 1,
+                    prev.speed
+                )));
+            }
+        }
+
+        if let Some(ranges) = &self.allowed_ranges {
+            for point in self.points.iter() {
+                ranges.check_point(point.temperature, point.speed)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets a copy of the curve with the points sorted by temperature.
+    pub fn sorted(&self) -> FanCurve {
+        let mut curve = self.clone();
+        curve.points.sort();
+        curve
+    }
+}
+
+/// Builder for a [`FanCurve`].
+///
+/// The points are validated with [`FanCurve::validate`] when calling [`FanCurveBuilder::build`].
+#[derive(Debug, Clone, Default)]
+pub struct FanCurveBuilder {
+    points: Vec<CurvePoint>,
+    allowed_ranges: Option<FanCurveRanges>,
+}
+
+impl FanCurveBuilder {
+    /// Creates a new builder with no points.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a curve point.
+    pub fn point(mut self, temperature: i32, speed: u8) -> Self {
+        self.points.push(CurvePoint::new(temperature, speed));
+        self
+    }
+
+    /// Appends multiple curve points, either as [`CurvePoint`]s or in the (temperature, speed) format.
+    pub fn points<P: Into<CurvePoint>>(mut self, points: impl IntoIterator<Item = P>) -> Self {
+        self.points.extend(points.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets the allowed ranges which the points will be validated against.
+    pub fn allowed_ranges(mut self, allowed_ranges: FanCurveRanges) -> Self {
+        self.allowed_ranges = Some(allowed_ranges);
+        self
+    }
+
+    /// Validates the points and builds the curve.
+    pub fn build(self) -> Result<FanCurve> {
+        let curve = FanCurve {
+            points: self.points.into_boxed_slice(),
+            allowed_ranges: self.allowed_ranges,
+        };
+        curve.validate()?;
+        Ok(curve)
+    }
+}
+
+impl FromStr for FanCurve {
+    type Err = Error;
+
+    /// Parses the contents of the `fan_curve` file.
+    /// GPU-specific quirks are not applied, see [`GpuHandle::get_fan_curve`](super::GpuHandle::get_fan_curve).
+    fn from_str(s: &str) -> Result<Self> {
+        let contents = FanCtrlContents::parse(s, "OD_FAN_CURVE")?;
+        let points = contents
+            .contents
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let mut split = line.split_whitespace();
+                split.next(); // Discard index
+
+                let raw_temp = split
+                    .next()
+                    .ok_or_else(|| Error::unexpected_eol("Temperature value", i))?;
+                let temp = raw_temp.trim_end_matches('C').parse()?;
+
+                let raw_speed = split
+                    .next()
+                    .ok_or_else(|| Error::unexpected_eol("Speed value", i))?;
+                let speed = raw_speed.trim_end_matches('%').parse()?;
+
+                Ok(CurvePoint::new(temp, speed))
+            })
+            .collect::<Result<_>>()?;
+
+        let temp_range = contents.od_range.get("FAN_CURVE(hotspot temp)");
+        let speed_range = contents.od_range.get("FAN_CURVE(fan speed)");
+
+        let allowed_ranges = if let Some(((min_temp, max_temp), (min_speed, max_speed))) =
+            (temp_range).zip(speed_range)
+        {
+            let min_temp: i32 = min_temp.trim_end_matches('C').parse()?;
+            let max_temp: i32 = max_temp.trim_end_matches('C').parse()?;
+
+            let min_speed: u8 = min_speed.trim_end_matches('%').parse()?;
+            let max_speed: u8 = max_speed.trim_end_matches('%').parse()?;
+
+            Some(FanCurveRanges {
+                temperature_range: min_temp..=max_temp,
+                speed_range: min_speed..=max_speed,
+            })
+        } else {
+            None
+        };
+
+        Ok(FanCurve {
+            points,
+            allowed_ranges,
+        })
+    }
+}
+
 /// Range of values allowed to be used within fan curve points
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct FanCurveRanges {
     /// Temperature range allowed in curve points
     pub temperature_range: RangeInclusive<i32>,
@@ -41,6 +322,25 @@ pub struct FanCurveRanges {
     pub speed_range: RangeInclusive<u8>,
 }
 
+impl FanCurveRanges {
+    /// Checks that a curve point is within the allowed ranges.
+    pub(crate) fn check_point(&self, temperature: i32, speed: u8) -> Result<()> {
+        if !self.temperature_range.contains(&temperature) {
+            return Err(Error::not_allowed(format!(
+                "Temperature value {temperature} is outside of the allowed range {:?}",
+                self.temperature_range
+            )));
+        }
+        if !self.speed_range.contains(&speed) {
+            return Err(Error::not_allowed(format!(
+                "Speed value {speed} is outside of the allowed range {:?}",
+                self.speed_range
+            )));
+        }
+        Ok(())
+    }
+}
+
 #[derive(PartialEq, Eq, Debug)]
 pub(crate) struct FanCtrlContents {
     pub contents: String,
@@ -49,7 +349,26 @@ pub(crate) struct FanCtrlContents {
 
 impl FanCtrlContents {
     pub(crate) fn parse(data: &str, expected_section_name: &str) -> Result<Self> {
-        let mut lines = data.lines().enumerate();
+        let (section_name, contents) = Self::parse_section(data)?;
+
+        if section_name != expected_section_name {
+            return Err(Error::basic_parse_error(format!(
+                "Found section {section_name}, expected {expected_section_name}"
+            )));
+        }
+
+        Ok(contents)
+    }
+
+    /// Parses the contents along with the name of the section they are in.
+    ///
+    /// Whitespace within the lines is normalized, so the contents and ranges are separated by single spaces.
+    pub(crate) fn parse_section(data: &str) -> Result<(String, Self)> {
+        let mut lines = data
+            .lines()
+            .map(normalize_whitespace)
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty());
         let (_, section_line) = lines
             .next()
             .ok_or_else(|| Error::unexpected_eol("Section name", 1))?;
@@ -58,12 +377,6 @@ impl FanCtrlContents {
             Error::basic_parse_error(format!("Section \"{section_line}\" should end with \":\""))
         })?;
 
-        if section_name != expected_section_name {
-            return Err(Error::basic_parse_error(format!(
-                "Found section {section_name}, expected {expected_section_name}"
-            )));
-        }
-
         let mut contents = String::new();
         for (_, line) in &mut lines {
             if line == "OD_RANGE:" {
@@ -92,15 +405,119 @@ impl FanCtrlContents {
             od_range.insert(name.to_owned(), (min.to_owned(), max.to_owned()));
         }
 
-        Ok(Self { contents, od_range })
+        Ok((section_name.to_owned(), Self { contents, od_range }))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::FanCtrlContents;
+    use super::{CurvePoint, FanCtrlContents, FanCurve, FanCurveRanges};
+    use crate::tests::whitespace_variants;
     use pretty_assertions::assert_eq;
 
+    fn test_curve() -> FanCurve {
+        FanCurve::builder()
+            .points([(40, 20), (50, 35), (60, 50), (70, 75), (80, 100)])
+            .allowed_ranges(FanCurveRanges {
+                temperature_range: 25..=100,
+                speed_range: 20..=100,
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn fan_curve_interpolate() {
+        let curve = test_curve();
+        assert_eq!(curve.interpolate(0), Some(20));
+        assert_eq!(curve.interpolate(40), Some(20));
+        assert_eq!(curve.interpolate(45), Some(28));
+        assert_eq!(curve.interpolate(65), Some(63));
+        assert_eq!(curve.interpolate(80), Some(100));
+        assert_eq!(curve.interpolate(95), Some(100));
+        assert_eq!(FanCurve::builder().build().unwrap().interpolate(50), None);
+    }
+
+    #[test]
+    fn fan_curve_point_ranges() {
+        let curve = test_curve();
+        assert_eq!(curve.point_count(), 5);
+        assert_eq!(
+            curve.point_ranges(0),
+            Some(FanCurveRanges {
+                temperature_range: 25..=50,
+                speed_range: 20..=35,
+            })
+        );
+        assert_eq!(
+            curve.point_ranges(2),
+            Some(FanCurveRanges {
+                temperature_range: 50..=70,
+                speed_range: 35..=75,
+            })
+        );
+        assert_eq!(
+            curve.point_ranges(4),
+            Some(FanCurveRanges {
+                temperature_range: 70..=100,
+                speed_range: 75..=100,
+            })
+        );
+        assert_eq!(curve.point_ranges(5), None);
+    }
+
+    #[test]
+    fn fan_curve_default() {
+        let curve = FanCurve {
+            points: vec![CurvePoint::default(); 5].into_boxed_slice(),
+            allowed_ranges: None,
+        };
+        assert!(curve.is_default());
+        assert!(!test_curve().is_default());
+    }
+
+    #[test]
+    fn fan_curve_validate() {
+        let ranges = FanCurveRanges {
+            temperature_range: 25..=100,
+            speed_range: 20..=100,
+        };
+
+        let decreasing_speed = FanCurve::builder().points([(40, 50), (50, 40)]).build();
+        assert!(decreasing_speed.is_err());
+
+        let decreasing_temp = FanCurve::builder().points([(50, 40), (40, 50)]).build();
+        assert!(decreasing_temp.is_err());
+
+        let out_of_range = FanCurve::builder()
+            .points([(20, 40), (50, 50)])
+            .allowed_ranges(ranges.clone())
+            .build();
+        assert!(out_of_range.is_err());
+
+        let speed_out_of_range = FanCurve::builder()
+            .point(30, 10)
+            .allowed_ranges(ranges)
+            .build();
+        assert!(speed_out_of_range.is_err());
+    }
+
+    #[test]
+    fn fan_curve_sorted() {
+        let curve = FanCurve {
+            points: [(60, 50), (40, 20), (50, 35)].map(CurvePoint::from).into(),
+            allowed_ranges: None,
+        };
+        assert!(curve.validate().is_err());
+
+        let sorted = curve.sorted();
+        assert_eq!(
+            &*sorted.points,
+            &[(40, 20), (50, 35), (60, 50)].map(CurvePoint::from)
+        );
+        assert!(sorted.validate().is_ok());
+    }
+
     #[test]
     fn parse_od_acoustic_limit() {
         let data = "\
@@ -157,4 +574,50 @@ FAN_CURVE(fan speed): 20% 100%";
         };
         assert_eq!(expected_contents, contents);
     }
+
+    #[test]
+    fn parse_irregular_whitespace() {
+        for data in [
+            include_test_data!("rx7900xt/gpu_od/fan_ctrl/fan_curve"),
+            include_test_data!("rx7800xt/gpu_od/fan_ctrl/fan_curve"),
+        ] {
+            let expected: FanCurve = data.parse().unwrap();
+            for variant in whitespace_variants(data) {
+                assert_eq!(expected, variant.parse().unwrap(), "{variant:?}");
+            }
+        }
+
+        let data = include_test_data!("rx7800xt/gpu_od/fan_ctrl/acoustic_limit_rpm_threshold");
+        let expected = FanCtrlContents::parse(data, "OD_ACOUSTIC_LIMIT").unwrap();
+        for variant in whitespace_variants(data) {
+            assert_eq!(
+                expected,
+                FanCtrlContents::parse(&variant, "OD_ACOUSTIC_LIMIT").unwrap(),
+                "{variant:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn fan_curve_from_str() {
+        let data = "\
+OD_FAN_CURVE:
+0: 40C 20%
+1: 50C 35%
+OD_RANGE:
+FAN_CURVE(hotspot temp): 25C 100C
+FAN_CURVE(fan speed): 20% 100%";
+        let curve: FanCurve = data.parse().unwrap();
+        assert_eq!(
+            FanCurve {
+                points: vec![CurvePoint::new(40, 20), CurvePoint::new(50, 35)].into(),
+                allowed_ranges: Some(FanCurveRanges {
+                    temperature_range: 25..=100,
+                    speed_range: 20..=100,
+                }),
+            },
+            curve
+        );
+        assert!("OD_ACOUSTIC_LIMIT:\n0".parse::<FanCurve>().is_err());
+    }
 }
@@ -0,0 +1,145 @@
+//! Binary `gpu_metrics` table exposed by the SMU
+use crate::{
+    error::{Error, ErrorKind},
+    Result,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Value used by the firmware for fields which are not available.
+const UNAVAILABLE: u16 = u16::MAX;
+
+/// Selected fields of the `gpu_metrics` table.
+///
+/// The table layout depends on the format and content revisions in its header.
+/// Only the fields which have a known location in the given revision are filled in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct GpuMetrics {
+    /// Format revision of the table
+    pub format_revision: u8,
+    /// Content revision of the table
+    pub content_revision: u8,
+    /// Average graphics engine activity, in percent
+    pub average_gfx_activity: Option<u16>,
+    /// Average multimedia (UVD/VCN) engine activity, in percent.
+    /// Not reported in the same way by datacenter GPUs (format 1.4 and newer).
+    pub average_mm_activity: Option<u16>,
+}
+
+impl GpuMetrics {
+    /// Parses the raw contents of the `gpu_metrics` file.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let header = data
+            .get(..4)
+            .ok_or_else(|| Error::basic_parse_error("gpu_metrics header is too short"))?;
+        let structure_size = u16::from_le_bytes([header[0], header[1]]);
+        let format_revision = header[2];
+        let content_revision = header[3];
+
+        if usize::from(structure_size) > data.len() {
+            return Err(Error::basic_parse_error(format!(
+                "gpu_metrics table is truncated: expected {structure_size} bytes, got {}",
+                data.len()
+            )));
+        }
+
+        // The multimedia activity follows the graphics (and on discrete GPUs, the memory controller) activity.
+        // Revisions 1.0 and 2.0 have an aligned 64-bit system clock counter before the temperatures.
+        let (gfx_activity_offset, mm_activity_offset) = match (format_revision, content_revision) {
+            // Discrete GPUs
+            (1, 0) => (28, Some(32)),
+            (1, 1..=3) => (16, Some(20)),
+            (1, _) => (12, None),
+            // APUs
+            (2, 0) => (40, Some(42)),
+            (2, _) => (28, Some(30)),
+            (3, _) => (42, Some(44)),
+            _ => {
+                return Err(ErrorKind::Unsupported(format!(
+                    "gpu_metrics format {format_revision}.{content_revision} is not supported"
+                ))
+                .into())
+            }
+        };
+
+        Ok(Self {
+            format_revision,
+            content_revision,
+            average_gfx_activity: read_u16(data, gfx_activity_offset),
+            average_mm_activity: mm_activity_offset.and_then(|offset| read_u16(data, offset)),
+        })
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    let value = u16::from_le_bytes([bytes[0], bytes[1]]);
+    (value != UNAVAILABLE).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GpuMetrics;
+    use crate::error::ErrorKind;
+
+    fn table(format_revision: u8, content_revision: u8, offset: usize, value: u16) -> Vec<u8> {
+        let mut data = vec![0; 64];
+        data[..2].copy_from_slice(&64u16.to_le_bytes());
+        data[2] = format_revision;
+        data[3] = content_revision;
+        data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn parse_gfx_activity() {
+        for (format, content, offset) in [
+            (1, 0, 28),
+            (1, 3, 16),
+            (1, 5, 12),
+            (2, 0, 40),
+            (2, 1, 28),
+            (3, 0, 42),
+        ] {
+            let metrics = GpuMetrics::parse(&table(format, content, offset, 57)).unwrap();
+            assert_eq!(
+                Some(57),
+                metrics.average_gfx_activity,
+                "v{format}.{content}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_mm_activity() {
+        for (format, content, offset) in
+            [(1, 0, 32), (1, 3, 20), (2, 0, 42), (2, 1, 30), (3, 0, 44)]
+        {
+            let metrics = GpuMetrics::parse(&table(format, content, offset, 12)).unwrap();
+            assert_eq!(Some(12), metrics.average_mm_activity, "v{format}.{content}");
+        }
+
+        let metrics = GpuMetrics::parse(&table(1, 5, 16, 12)).unwrap();
+        assert_eq!(None, metrics.average_mm_activity);
+    }
+
+    #[test]
+    fn parse_unavailable() {
+        let metrics = GpuMetrics::parse(&table(1, 3, 16, u16::MAX)).unwrap();
+        assert_eq!(None, metrics.average_gfx_activity);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(GpuMetrics::parse(&[0x40, 0]).is_err());
+
+        let mut truncated = table(1, 3, 16, 0);
+        truncated.truncate(32);
+        assert!(GpuMetrics::parse(&truncated).is_err());
+
+        let err = GpuMetrics::parse(&table(9, 0, 16, 0)).unwrap_err();
+        assert_eq!(ErrorKind::Unsupported(String::new()), err.kind);
+    }
+}
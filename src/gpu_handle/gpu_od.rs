@@ -0,0 +1,64 @@
+//! Generic access to the `gpu_od` overdrive interface
+use super::fan_control::FanCtrlContents;
+use crate::{error::ErrorContext, Result};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// An overdrive attribute file found under `gpu_od`, see [`GpuHandle::get_od_attributes`](super::GpuHandle::get_od_attributes).
+///
+/// This gives access to settings which don't have typed support in the library yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct OdAttribute {
+    /// Path of the file relative to `gpu_od`, e.g. `fan_ctrl/fan_curve`
+    pub path: String,
+    /// Name of the section reported in the file, e.g. `OD_FAN_CURVE`
+    pub section: String,
+    /// The current values, as reported by the kernel
+    pub contents: String,
+    /// Allowed ranges in the `(min, max)` format, indexed by the value name
+    pub od_range: BTreeMap<String, (String, String)>,
+}
+
+/// Reads all of the attribute files in the given `gpu_od` directory and its subdirectories.
+///
+/// Files which are not in the overdrive section format are skipped.
+pub(crate) fn read_od_attributes(gpu_od_path: &Path) -> Result<Vec<OdAttribute>> {
+    let mut attributes = Vec::new();
+    read_dir_attributes(gpu_od_path, gpu_od_path, &mut attributes)?;
+    attributes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(attributes)
+}
+
+fn read_dir_attributes(root: &Path, dir: &Path, attributes: &mut Vec<OdAttribute>) -> Result<()> {
+    let entries = fs::read_dir(dir).with_context(|| format!("Could not read directory {dir:?}"))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            read_dir_attributes(root, &path, attributes)?;
+            continue;
+        }
+
+        let Ok(data) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok((section, contents)) = FanCtrlContents::parse_section(data.trim()) else {
+            continue;
+        };
+        let Ok(relative_path) = path.strip_prefix(root) else {
+            continue;
+        };
+
+        attributes.push(OdAttribute {
+            path: relative_path.to_string_lossy().into_owned(),
+            section,
+            contents: contents.contents,
+            od_range: contents.od_range.into_iter().collect(),
+        });
+    }
+
+    Ok(())
+}
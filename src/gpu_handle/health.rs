@@ -0,0 +1,308 @@
+//! Types for checking the health of a GPU.
+use crate::{error::Error, gpu_handle::normalize_whitespace, Result};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Overall health verdict.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HealthStatus {
+    /// No problems were found
+    Healthy,
+    /// The GPU is working, but some signals indicate problems
+    Degraded,
+    /// The GPU appears to be stuck
+    Hung,
+}
+
+/// A problem found during a health check.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthIssue {
+    /// The GPU reports being fully busy while the core clock is stopped
+    BusyWithoutClock,
+    /// An attribute which exists could not be read
+    UnreadableAttribute {
+        /// Name of the attribute
+        attribute: String,
+        /// The read error
+        error: String,
+    },
+    /// New PCIe replays happened since the previous health check, indicating link errors
+    PcieReplays {
+        /// Number of new replays
+        count: u64,
+    },
+}
+
+impl HealthIssue {
+    /// The status implied by the issue.
+    pub fn status(&self) -> HealthStatus {
+        match self {
+            HealthIssue::BusyWithoutClock => HealthStatus::Hung,
+            HealthIssue::UnreadableAttribute { .. } | HealthIssue::PcieReplays { .. } => {
+                HealthStatus::Degraded
+            }
+        }
+    }
+}
+
+/// Fence state of a ring, as reported in `amdgpu_fence_info` in debugfs.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FenceInfo {
+    /// Ring index
+    pub index: u32,
+    /// Ring name
+    pub name: String,
+    /// Sequence number of the last signaled fence
+    pub last_signaled: u64,
+    /// Sequence number of the last emitted fence
+    pub last_emitted: u64,
+}
+
+impl FenceInfo {
+    /// Number of emitted fences which were not signaled yet.
+    pub fn pending(&self) -> u64 {
+        self.last_emitted.wrapping_sub(self.last_signaled) & u64::from(u32::MAX)
+    }
+}
+
+/// Error counters from one of the PCIe AER statistics files
+/// (`aer_dev_correctable`, `aer_dev_fatal` or `aer_dev_nonfatal`).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AerCounters {
+    /// Number of errors of each type, e.g. `BadTLP`
+    pub counters: BTreeMap<String, u64>,
+    /// Total number of errors
+    pub total: u64,
+}
+
+impl AerCounters {
+    /// Parses the contents of an AER statistics file.
+    ///
+    /// The total is taken from the `TOTAL_ERR_*` line, or summed up from the counters if it is missing.
+    pub fn parse(data: &str) -> Result<Self> {
+        let mut counters = BTreeMap::new();
+        let mut total = None;
+
+        for (i, line) in data.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (name, value) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| Error::unexpected_eol("AER counter value", i + 1))?;
+            let value: u64 = value.trim().parse()?;
+
+            if name.starts_with("TOTAL_ERR_") {
+                total = Some(value);
+            } else {
+                counters.insert(name.to_owned(), value);
+            }
+        }
+
+        let total = total.unwrap_or_else(|| counters.values().sum());
+        Ok(Self { counters, total })
+    }
+}
+
+/// PCIe Advanced Error Reporting statistics of the device.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AerStats {
+    /// Errors which were corrected by the hardware
+    pub correctable: AerCounters,
+    /// Uncorrectable errors which made the link unreliable
+    pub fatal: AerCounters,
+    /// Uncorrectable errors which only affected a single transaction
+    pub nonfatal: AerCounters,
+}
+
+impl AerStats {
+    /// Total number of errors of all kinds.
+    pub fn total(&self) -> u64 {
+        self.correctable.total + self.fatal.total + self.nonfatal.total
+    }
+}
+
+/// Result of a health check.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthReport {
+    /// The overall verdict, which is the most severe status out of all the issues
+    pub status: HealthStatus,
+    /// Problems that were found
+    pub issues: Vec<HealthIssue>,
+    /// Fence state of each ring. This is empty if debugfs is not accessible.
+    pub fences: Vec<FenceInfo>,
+}
+
+impl HealthReport {
+    pub(crate) fn new(issues: Vec<HealthIssue>, fences: Vec<FenceInfo>) -> Self {
+        let status = issues
+            .iter()
+            .map(HealthIssue::status)
+            .max()
+            .unwrap_or(HealthStatus::Healthy);
+        Self {
+            status,
+            issues,
+            fences,
+        }
+    }
+}
+
+/// Parses the contents of `amdgpu_fence_info`.
+pub fn parse_fence_info(data: &str) -> Result<Vec<FenceInfo>> {
+    let mut fences: Vec<FenceInfo> = Vec::new();
+
+    for (i, line) in data.lines().enumerate() {
+        let line = normalize_whitespace(line);
+
+        if let Some(header) = line
+            .strip_prefix("--- ring ")
+            .and_then(|header| header.strip_suffix(" ---"))
+        {
+            let (index, name) = header
+                .split_once(' ')
+                .ok_or_else(|| Error::unexpected_eol("ring name", i + 1))?;
+            let name = name.trim_start_matches('(').trim_end_matches(')');
+
+            fences.push(FenceInfo {
+                index: index.parse()?,
+                name: name.to_owned(),
+                last_signaled: 0,
+                last_emitted: 0,
+            });
+        } else if let Some(fence) = fences.last_mut() {
+            // Only the first signaled/emitted pair refers to the regular fences
+            if let Some(value) = line.strip_prefix("Last signaled fence") {
+                fence.last_signaled = parse_hex(value, i)?;
+            } else if let Some(value) = line.strip_prefix("Last emitted") {
+                if fence.last_emitted == 0 {
+                    fence.last_emitted = parse_hex(value, i)?;
+                }
+            }
+        }
+    }
+
+    Ok(fences)
+}
+
+fn parse_hex(value: &str, line: usize) -> Result<u64> {
+    let value = value.trim();
+    let hex = value
+        .strip_prefix("0x")
+        .ok_or_else(|| Error::unexpected_eol("hex value", line + 1))?;
+    Ok(u64::from_str_radix(hex, 16)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_fence_info, AerCounters, FenceInfo, HealthIssue, HealthReport, HealthStatus,
+    };
+    use crate::tests::whitespace_variants;
+    use pretty_assertions::assert_eq;
+
+    const FENCE_INFO: &str = "--- ring 0 (gfx_0.0.0) ---
+Last signaled fence          0x0000a6c3
+Last emitted                 0x0000a6c5
+Last signaled trailing fence 0x00000000
+Last emitted                 0x00000000
+Last preempted               0x00000000
+Last reset                   0x00000000
+Last both                    0x00000000
+--- ring 1 (comp_1.0.0) ---
+Last signaled fence          0x00000010
+Last emitted                 0x00000010
+";
+
+    #[test]
+    fn parse_fences() {
+        let fences = parse_fence_info(FENCE_INFO).unwrap();
+        assert_eq!(
+            vec![
+                FenceInfo {
+                    index: 0,
+                    name: "gfx_0.0.0".to_owned(),
+                    last_signaled: 0xa6c3,
+                    last_emitted: 0xa6c5,
+                },
+                FenceInfo {
+                    index: 1,
+                    name: "comp_1.0.0".to_owned(),
+                    last_signaled: 0x10,
+                    last_emitted: 0x10,
+                },
+            ],
+            fences
+        );
+        assert_eq!(2, fences[0].pending());
+        assert_eq!(0, fences[1].pending());
+    }
+
+    #[test]
+    fn parse_aer_counters() {
+        let data = "RxErr 2\nBadTLP 0\nBadDLLP 5\nTOTAL_ERR_COR 7\n";
+        let counters = AerCounters::parse(data).unwrap();
+        assert_eq!(7, counters.total);
+        assert_eq!(Some(&5), counters.counters.get("BadDLLP"));
+        assert_eq!(3, counters.counters.len());
+
+        let without_total = AerCounters::parse("RxErr 2\nBadTLP 1").unwrap();
+        assert_eq!(3, without_total.total);
+
+        assert!(AerCounters::parse("RxErr").is_err());
+    }
+
+    #[test]
+    fn parse_irregular_whitespace() {
+        let fences = parse_fence_info(FENCE_INFO).unwrap();
+        // The file is padded with null bytes, which are stripped when reading it
+        let aer_data =
+            include_test_data!("rx6900xt/aer_dev_correctable").replace(char::from(0), "");
+        let counters = AerCounters::parse(&aer_data).unwrap();
+
+        for variant in whitespace_variants(FENCE_INFO) {
+            assert_eq!(fences, parse_fence_info(&variant).unwrap(), "{variant:?}");
+        }
+        for variant in whitespace_variants(&aer_data) {
+            assert_eq!(
+                counters,
+                AerCounters::parse(&variant).unwrap(),
+                "{variant:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn report_status() {
+        assert_eq!(
+            HealthStatus::Healthy,
+            HealthReport::new(vec![], vec![]).status
+        );
+        assert_eq!(
+            HealthStatus::Hung,
+            HealthReport::new(
+                vec![
+                    HealthIssue::PcieReplays { count: 1 },
+                    HealthIssue::BusyWithoutClock
+                ],
+                vec![]
+            )
+            .status
+        );
+    }
+}
@@ -4,42 +4,72 @@ pub mod overdrive;
 #[macro_use]
 mod power_levels;
 pub mod fan_control;
+pub mod gpu_metrics;
+pub mod gpu_od;
+pub mod health;
+pub mod pm_policy;
 pub mod power_profile_mode;
+pub mod quirks;
+pub mod xcp;
 
-pub use power_levels::{PowerLevelKind, PowerLevels};
+pub use power_levels::{PcieLevel, PowerLevel, PowerLevelKind, PowerLevels};
 
-use self::fan_control::{FanCurve, FanCurveRanges, FanInfo};
+use self::fan_control::{FanCurve, FanInfo, PmfwSettings};
+use self::gpu_metrics::GpuMetrics;
+use self::gpu_od::OdAttribute;
+use self::health::{AerCounters, AerStats, FenceInfo, HealthIssue, HealthReport};
+#[cfg(feature = "overdrive")]
+use self::overdrive::{ClocksTable, ClocksTableGen};
+use self::pm_policy::{PmPolicy, PmPolicyLevels};
+use self::quirks::Quirk;
+use self::xcp::XcpHandle;
 use crate::{
     error::{Error, ErrorContext, ErrorKind},
     gpu_handle::fan_control::FanCtrlContents,
     hw_mon::HwMon,
-    sysfs::SysFS,
+    monitoring::{self, Metric, Sample},
+    sysfs::{self, SysFS},
     Result,
 };
-use power_profile_mode::PowerProfileModesTable;
+use power_profile_mode::{
+    CustomHeuristics, NamedHeuristics, PowerProfileModesTable, StandardProfile,
+};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
-    fmt::{self, Display, Write as _},
+    fmt::{self, Display},
     fs,
-    io::Write,
-    path::PathBuf,
+    hash::{Hash, Hasher},
+    io::{self, BufRead, BufReader, Read, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
     str::FromStr,
-};
-#[cfg(feature = "overdrive")]
-use {
-    self::overdrive::{ClocksTable, ClocksTableGen},
-    std::fs::File,
+    sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock},
+    time::{Duration, Instant},
 };
 
 /// A `GpuHandle` represents a handle over a single GPU device, as exposed in the Linux SysFS.
+///
+/// Handles are compared and hashed by the PCI slot of the device (or the canonical sysfs path when the slot is not known),
+/// so handles created through different symlinks to the same GPU are equal.
 #[derive(Clone, Debug)]
 pub struct GpuHandle {
     sysfs_path: PathBuf,
     /// A collection of all [HwMon](../hw_mon/struct.HwMon.html)s bound to this GPU. They are used to expose real-time data.
     pub hw_monitors: Vec<HwMon>,
+    /// Monitor found by [`GpuHandle::with_hw_mon`] after the previous one went stale
+    rescanned_hw_mon: Arc<RwLock<Option<HwMon>>>,
     uevent: HashMap<String, String>,
+    #[cfg(feature = "overdrive")]
+    default_clocks_table: Arc<Mutex<Option<ClocksTableGen>>>,
+    power_profile_modes: Arc<Mutex<Option<PowerProfileModesTable>>>,
+    default_fan_settings: Arc<Mutex<Option<PmfwSettings>>>,
+    /// Allowed ranges of the fan settings, indexed by the file name
+    fan_ranges: Arc<Mutex<HashMap<String, Option<RangeInclusive<u32>>>>>,
+    pcie_replay_count: Arc<Mutex<Option<u64>>>,
+    pcie_port_path: Arc<Mutex<Option<PathBuf>>>,
+    quirks: Vec<Quirk>,
 }
 
 impl GpuHandle {
@@ -47,18 +77,17 @@ impl GpuHandle {
     ///
     /// Normally, the path should look akin to `/sys/class/drm/card0/device`,
     /// and it needs to at least contain a `uevent` file.
+    /// Paths of DRM cards (`/sys/class/drm/card0`) and hwmon directories are also accepted,
+    /// in which case the `device` directory they link to is used.
     pub fn new_from_path(sysfs_path: PathBuf) -> Result<Self> {
-        let mut hw_monitors = Vec::new();
+        let (sysfs_path, uevent_raw) = resolve_device_path(sysfs_path);
+        let sysfs_path = fs::canonicalize(&sysfs_path).unwrap_or(sysfs_path);
 
-        if let Ok(hw_mons_iter) = fs::read_dir(sysfs_path.join("hwmon")) {
-            for hw_mon_dir in hw_mons_iter.flatten() {
-                if let Ok(hw_mon) = HwMon::new_from_path(hw_mon_dir.path()) {
-                    hw_monitors.push(hw_mon);
-                }
-            }
+        let uevent_raw = match uevent_raw {
+            Some(uevent_raw) => uevent_raw,
+            None => fs::read_to_string(sysfs_path.join("uevent"))?,
         }
-
-        let uevent_raw = fs::read_to_string(sysfs_path.join("uevent"))?.replace(char::from(0), "");
+        .replace(char::from(0), "");
 
         let mut uevent = HashMap::new();
 
@@ -70,16 +99,174 @@ impl GpuHandle {
             uevent.insert(key.to_owned(), value.to_owned());
         }
 
+        // Some virtualized setups don't include the slot name in uevent, but the device directory is named after it
+        if !uevent.contains_key("PCI_SLOT_NAME") {
+            let slot_name = sysfs_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .filter(|name| is_pci_slot_name(name))
+                .map(str::to_owned);
+            if let Some(slot_name) = slot_name {
+                uevent.insert("PCI_SLOT_NAME".to_owned(), slot_name);
+            }
+        }
+
+        let quirks = uevent
+            .get("PCI_ID")
+            .and_then(|pci_id| pci_id.split_once(':'))
+            .map(|(vendor_id, device_id)| quirks::builtin_quirks(vendor_id, device_id))
+            .unwrap_or_default();
+
         match uevent.get("DRIVER") {
             Some(_) => Ok(Self {
+                hw_monitors: scan_hw_monitors(&sysfs_path),
                 sysfs_path,
-                hw_monitors,
+                rescanned_hw_mon: Arc::default(),
                 uevent,
+                #[cfg(feature = "overdrive")]
+                default_clocks_table: Arc::default(),
+                power_profile_modes: Arc::default(),
+                default_fan_settings: Arc::default(),
+                fan_ranges: Arc::default(),
+                pcie_replay_count: Arc::default(),
+                pcie_port_path: Arc::default(),
+                quirks,
             }),
             None => Err(ErrorKind::InvalidSysFS.into()),
         }
     }
 
+    /// Finds all GPUs in `/sys/class/drm`, see [`GpuHandle::enumerate_from`].
+    pub fn enumerate() -> Result<Vec<Self>> {
+        Self::enumerate_from(Path::new("/sys/class/drm"))
+    }
+
+    /// Finds all GPUs in the given DRM class directory, sorted by the card index.
+    ///
+    /// The handles are initialized in parallel, as probing every device can be slow on systems with many GPUs.
+    /// Cards which can't be initialized (e.g. virtual display devices) are skipped.
+    ///
+    /// Initializing a handle only reads the device's `uevent` file and lists its hardware monitors.
+    /// Everything else (like the default clocks table or the power profile modes) is read on first use.
+    pub fn enumerate_from(drm_path: &Path) -> Result<Vec<Self>> {
+        let mut cards: Vec<(u32, PathBuf)> = fs::read_dir(drm_path)
+            .with_context(|| format!("Could not read directory {drm_path:?}"))?
+            .flatten()
+            .filter_map(|entry| {
+                let index = entry
+                    .file_name()
+                    .to_str()?
+                    .strip_prefix("card")?
+                    .parse()
+                    .ok()?;
+                Some((index, entry.path()))
+            })
+            .collect();
+        cards.sort_unstable_by_key(|(index, _)| *index);
+
+        let handles = std::thread::scope(|scope| {
+            let threads: Vec<_> = cards
+                .into_iter()
+                .map(|(_, path)| scope.spawn(move || Self::new_from_path(path)))
+                .collect();
+
+            threads
+                .into_iter()
+                .filter_map(|thread| thread.join().ok()?.ok())
+                .collect()
+        });
+        Ok(handles)
+    }
+
+    /// Scans the `hwmon` directory again, replacing [`GpuHandle::hw_monitors`].
+    ///
+    /// The hardware monitor directory can be recreated under a different name when the driver is rebound
+    /// or the device comes back from runtime suspend, leaving the previously found monitors stale.
+    pub fn rescan_hw_monitors(&mut self) {
+        self.hw_monitors = scan_hw_monitors(&self.sysfs_path);
+        *self
+            .rescanned_hw_mon
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    /// Runs the function with the first hardware monitor.
+    ///
+    /// If the function fails because the monitor directory no longer exists, the `hwmon` directory is scanned again
+    /// and the function is retried with the newly found monitor.
+    /// Other errors (like a missing optional attribute) are returned as is.
+    /// The new monitor is remembered (also by clones of the handle) and used by later calls,
+    /// but this does not update [`GpuHandle::hw_monitors`], see [`GpuHandle::rescan_hw_monitors`].
+    pub fn with_hw_mon<T, F>(&self, f: F) -> Result<T>
+    where
+        F: Fn(&HwMon) -> Result<T>,
+    {
+        let err = {
+            let rescanned = self
+                .rescanned_hw_mon
+                .read()
+                .unwrap_or_else(PoisonError::into_inner);
+            let hw_mon = rescanned
+                .as_ref()
+                .or(self.hw_monitors.first())
+                .ok_or_else(|| ErrorKind::Unsupported("No hardware monitor found".to_owned()))?;
+            match f(hw_mon) {
+                Err(err) if err.is_not_found() && !hw_mon.exists() => err,
+                result => return result,
+            }
+        };
+
+        let mut rescanned = self
+            .rescanned_hw_mon
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        // Another thread may have found the new monitor while waiting for the lock
+        if let Some(hw_mon) = rescanned.as_ref().filter(|hw_mon| hw_mon.exists()) {
+            return f(hw_mon);
+        }
+        match scan_hw_monitors(&self.sysfs_path).into_iter().next() {
+            Some(hw_mon) => f(rescanned.insert(hw_mon)),
+            None => Err(err),
+        }
+    }
+
+    /// Waits until all of the given interfaces are available, polling the device until the timeout expires.
+    ///
+    /// Some attributes (like `pp_od_clk_voltage` and the hardware monitor) appear slightly after the card is registered,
+    /// so services which apply settings at boot should wait for the interfaces they need.
+    /// The hardware monitors are rescanned while waiting for [`Interface::HwMon`].
+    ///
+    /// Returns an error of the [`std::io::ErrorKind::TimedOut`] kind with the missing interfaces if the timeout expires.
+    pub fn wait_until_ready(&mut self, timeout: Duration, required: &[Interface]) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            // The monitors can be stale after the driver re-probes the device, not only missing
+            if required.contains(&Interface::HwMon) && !Interface::HwMon.is_available(self) {
+                self.rescan_hw_monitors();
+            }
+
+            let missing: Vec<Interface> = required
+                .iter()
+                .copied()
+                .filter(|interface| !interface.is_available(self))
+                .collect();
+            if missing.is_empty() {
+                return Ok(());
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("Timed out waiting for {missing:?}"),
+                )
+                .into());
+            }
+            std::thread::sleep(READY_POLL_INTERVAL.min(deadline - now));
+        }
+    }
+
     /// Gets the kernel driver used.
     pub fn get_driver(&self) -> &str {
         self.uevent.get("DRIVER").unwrap()
@@ -102,15 +289,128 @@ impl GpuHandle {
     }
 
     /// Gets the pci slot name of the card.
+    ///
+    /// When it's not reported in `uevent`, the name of the device directory is used instead.
     pub fn get_pci_slot_name(&self) -> Option<&str> {
         self.uevent.get("PCI_SLOT_NAME").map(|s| s.as_str())
     }
 
+    /// Finds the audio controller of the card (the HDMI/DP audio function in the same PCI slot, e.g. `0000:03:00.1`).
+    ///
+    /// Runtime power management of the GPU and its audio function should be handled together,
+    /// as the GPU cannot enter a low power state while the audio function is active.
+    pub fn get_audio_function(&self) -> Result<PciFunction> {
+        let slot_name = self
+            .get_pci_slot_name()
+            .ok_or_else(|| ErrorKind::Unsupported("The PCI slot name is not known".to_owned()))?;
+        let (device, _) = slot_name.rsplit_once('.').ok_or_else(|| {
+            Error::basic_parse_error(format!("Invalid PCI slot name {slot_name}"))
+        })?;
+        let audio_slot_name = format!("{device}.1");
+
+        let path = self
+            .sysfs_path
+            .parent()
+            .map(|parent| parent.join(&audio_slot_name))
+            .filter(|path| path.is_dir())
+            .ok_or_else(|| {
+                ErrorKind::Unsupported(format!("No PCI function found at {audio_slot_name}"))
+            })?;
+
+        let read_id = |file: &str| -> Result<String> {
+            let contents = fs::read_to_string(path.join(file))
+                .with_context(|| format!("Could not read {file} of {audio_slot_name}"))?;
+            let id = trim_sysfs_line(&contents);
+            Ok(id.strip_prefix("0x").unwrap_or(id).to_ascii_uppercase())
+        };
+
+        // Multimedia audio controllers have the 0x0403xx class
+        let class = read_id("class")?;
+        if !class.starts_with("0403") {
+            return Err(ErrorKind::Unsupported(format!(
+                "The PCI function at {audio_slot_name} is not an audio controller (class {class})"
+            ))
+            .into());
+        }
+
+        Ok(PciFunction {
+            vendor_id: read_id("vendor")?,
+            device_id: read_id("device")?,
+            slot_name: audio_slot_name,
+            path,
+        })
+    }
+
+    /// Gets the name of the DRM card of this GPU, e.g. `card0`.
+    pub fn get_card_name(&self) -> Option<String> {
+        fs::read_dir(self.sysfs_path.join("drm"))
+            .ok()?
+            .flatten()
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .find(|name| {
+                name.strip_prefix("card")
+                    .is_some_and(|index| index.parse::<u32>().is_ok())
+            })
+    }
+
+    /// Gets a short human-readable description of the GPU, see the [`Display`] implementation.
+    pub fn summary(&self) -> String {
+        self.to_string()
+    }
+
+    /// Gets the identifying information of the GPU, which can be persisted and matched against later.
+    pub fn get_identity(&self) -> GpuIdentity {
+        let join_id = |(vendor, device): (&str, &str)| format!("{vendor}:{device}");
+
+        GpuIdentity {
+            driver: self.get_driver().to_owned(),
+            pci_id: self.get_pci_id().map(join_id),
+            pci_subsys_id: self.get_pci_subsys_id().map(join_id),
+            pci_slot_name: self.get_pci_slot_name().map(str::to_owned),
+            unique_id: self.read_file("unique_id").ok(),
+            vbios_version: self.get_vbios_version().ok(),
+        }
+    }
+
+    /// Key used for comparing and hashing handles.
+    fn identity_key(&self) -> (Option<&str>, &Path) {
+        match self.get_pci_slot_name() {
+            Some(slot_name) => (Some(slot_name), Path::new("")),
+            None => (None, &self.sysfs_path),
+        }
+    }
+
+    /// Gets the quirks applied to this GPU, see [`quirks`].
+    pub fn quirks(&self) -> &[Quirk] {
+        &self.quirks
+    }
+
+    /// Adds a quirk to be applied to this GPU, in addition to the built-in ones.
+    pub fn add_quirk(&mut self, quirk: Quirk) {
+        if !self.quirks.contains(&quirk) {
+            self.quirks.push(quirk);
+        }
+    }
+
     fn get_link(&self, file_name: &str) -> Result<String> {
+        let port_path = self.pcie_port_path()?;
+        self.read_file(port_path.join(file_name))
+    }
+
+    /// Resolves the path which reports the PCIe link of the GPU, caching it for later use.
+    fn pcie_port_path(&self) -> Result<PathBuf> {
         // Despite being labled NAVI10, newer generations use the same port device ids
         const NAVI10_UPSTREAM_PORT: &str = "0x1478\n";
         const NAVI10_DOWNSTREAM_PORT: &str = "0x1479\n";
 
+        let mut cached_path = self
+            .pcie_port_path
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(path) = cached_path.as_ref() {
+            return Ok(path.clone());
+        }
+
         let mut sysfs_path = std::fs::canonicalize(self.get_path())?.join("../"); // pcie port
 
         for _ in 0..2 {
@@ -127,12 +427,8 @@ impl GpuHandle {
 
         sysfs_path.pop();
 
-        Self {
-            sysfs_path,
-            hw_monitors: Vec::new(),
-            uevent: HashMap::new(),
-        }
-        .read_file(file_name)
+        *cached_path = Some(sysfs_path.clone());
+        Ok(sysfs_path)
     }
 
     /// Gets the current PCIe link speed.
@@ -155,6 +451,124 @@ impl GpuHandle {
         self.get_link("max_link_width")
     }
 
+    /// Gets the current and maximum PCIe link speed and width together.
+    pub fn get_link_status(&self) -> Result<LinkStatus> {
+        Ok(LinkStatus {
+            current_speed: self.get_current_link_speed()?,
+            current_width: self.get_current_link_width()?,
+            max_speed: self.get_max_link_speed()?,
+            max_width: self.get_max_link_width()?,
+        })
+    }
+
+    /// Gets the available and active levels of a power management policy from the `pm_policy` directory,
+    /// e.g. `get_pm_policy::<SocPstatePolicy>()`. Only available on some datacenter GPUs.
+    pub fn get_pm_policy<T: PmPolicy<Err = Error>>(&self) -> Result<PmPolicyLevels<T>> {
+        let policy = self.read_pm_policy::<T>()?;
+        Ok(PmPolicyLevels {
+            levels: policy.levels.into_iter().map(|(_, level)| level).collect(),
+            active: policy.active.map(|(_, level)| level),
+        })
+    }
+
+    /// Sets a power management policy. Returns a `NotAllowed` error if the GPU does not support the given policy.
+    pub fn set_pm_policy<T: PmPolicy<Err = Error>>(&self, policy: T) -> Result<()> {
+        let (number, _) = self
+            .read_pm_policy::<T>()?
+            .levels
+            .into_iter()
+            .find(|(_, level)| *level == policy)
+            .ok_or_else(|| Error::not_allowed(format!("Policy {policy} is not available")))?;
+
+        self.write_file(
+            &format!("pm_policy/{}", T::FILE_NAME),
+            format!("{number}\n"),
+        )
+    }
+
+    fn read_pm_policy<T: PmPolicy<Err = Error>>(&self) -> Result<PmPolicyLevels<(u32, T)>> {
+        let file = format!("pm_policy/{}", T::FILE_NAME);
+        let data = self.read_file(&file)?;
+        pm_policy::parse_levels(&data).with_context(|| format!("Could not parse {file}"))
+    }
+
+    /// Gets the current compute partitioning mode, e.g. `SPX` or `CPX`. Only available on datacenter GPUs.
+    pub fn get_compute_partition(&self) -> Result<String> {
+        self.read_file("current_compute_partition")
+    }
+
+    /// Gets the compute partitioning modes supported by the GPU.
+    pub fn get_available_compute_partitions(&self) -> Result<Vec<String>> {
+        let data = self.read_file("available_compute_partition")?;
+        Ok(data
+            .split(',')
+            .map(str::trim)
+            .filter(|mode| !mode.is_empty())
+            .map(str::to_owned)
+            .collect())
+    }
+
+    /// Finds the compute partitions (XCPs) of the GPU, see [`GpuHandle::get_xcp_partitions_from`].
+    pub fn get_xcp_partitions(&self) -> Result<Vec<XcpHandle>> {
+        self.get_xcp_partitions_from(Path::new(xcp::KFD_TOPOLOGY_PATH))
+    }
+
+    /// Finds the compute partitions of the GPU in the given KFD topology directory (normally `/sys/class/kfd/kfd/topology/nodes`).
+    ///
+    /// Each partition has its own topology node which reports the PCI location of this GPU.
+    /// When the GPU is not partitioned, the single returned partition represents the whole GPU.
+    pub fn get_xcp_partitions_from(&self, topology_path: &Path) -> Result<Vec<XcpHandle>> {
+        xcp::find_partitions(self, topology_path)
+    }
+
+    /// Gets the PCIe AER (Advanced Error Reporting) error counters of the device.
+    ///
+    /// Complements the replay counter checked in [`GpuHandle::health_check`] when diagnosing unstable PCIe links.
+    pub fn get_aer_stats(&self) -> Result<AerStats> {
+        let read_counters = |file: &str| {
+            self.read_file(file).and_then(|data| {
+                AerCounters::parse(&data).with_context(|| format!("Could not parse {file}"))
+            })
+        };
+
+        Ok(AerStats {
+            correctable: read_counters("aer_dev_correctable")?,
+            fatal: read_counters("aer_dev_fatal")?,
+            nonfatal: read_counters("aer_dev_nonfatal")?,
+        })
+    }
+
+    /// Gets the version of the graphics (GC) IP block from the IP discovery table, e.g. `(11, 0, 0)` on Navi 31.
+    ///
+    /// This identifies the ASIC family more reliably than the PCI id. Only reported on Navi 1x and newer.
+    pub fn get_gc_version(&self) -> Result<(u32, u32, u32)> {
+        Ok((
+            self.read_file_parsed("ip_discovery/die/0/GC/0/major")?,
+            self.read_file_parsed("ip_discovery/die/0/GC/0/minor")?,
+            self.read_file_parsed("ip_discovery/die/0/GC/0/revision")?,
+        ))
+    }
+
+    /// Reads all of the attributes which do not change at runtime.
+    ///
+    /// Attributes which are not available on the GPU are left empty.
+    pub fn get_static_info(&self) -> StaticInfo {
+        StaticInfo {
+            identity: self.get_identity(),
+            total_vram: self.get_total_vram().ok(),
+            max_link_speed: self.get_max_link_speed().ok(),
+            max_link_width: self.get_max_link_width().ok(),
+            gc_version: self.get_gc_version().ok(),
+        }
+    }
+
+    /// Reads all of the values which change during runtime (usage, VRAM, temperatures, power, clocks and fan).
+    ///
+    /// Values which are not available on the GPU are left empty, with the reason recorded in [`Sample::errors`].
+    pub fn get_dynamic_info(&self) -> Sample {
+        monitoring::read_sample(self, Metric::ALL, Duration::ZERO)
+    }
+
     fn read_vram_file(&self, file: &str) -> Result<u64> {
         let raw_vram = self.read_file(file)?;
         Ok(raw_vram.parse()?)
@@ -170,12 +584,86 @@ impl GpuHandle {
         self.read_vram_file("mem_info_vram_used")
     }
 
+    /// Gets the percentage of VRAM that is currently used.
+    ///
+    /// Returns an [`ErrorKind::Unsupported`] error on devices which don't report VRAM usage, such as integrated GPUs.
+    pub fn get_vram_usage_percent(&self) -> Result<f64> {
+        let read_vram = |file: &str| match self.read_vram_file(file) {
+            Err(err) if err.is_not_found() => Err(Error::from(ErrorKind::Unsupported(
+                "VRAM usage is not reported by this device".to_owned(),
+            ))),
+            result => result,
+        };
+
+        let used = read_vram("mem_info_vram_used")?;
+        let total = read_vram("mem_info_vram_total")?;
+        if total == 0 {
+            return Err(ErrorKind::Unsupported(
+                "The device does not have dedicated VRAM".to_owned(),
+            )
+            .into());
+        }
+
+        Ok(used as f64 / total as f64 * 100.0)
+    }
+
     /// Returns the GPU busy percentage.
     pub fn get_busy_percent(&self) -> Result<u8> {
-        let raw_busy = self.read_file("gpu_busy_percent")?;
+        let raw_busy = optional_attribute(
+            self.read_file("gpu_busy_percent"),
+            "gpu_busy_percent requires Linux 4.19 or newer",
+        )?;
         Ok(raw_busy.parse()?)
     }
 
+    /// Lists the attributes which are currently available under `gpu_od`, along with their allowed ranges.
+    ///
+    /// This includes attributes that don't have dedicated functions yet, e.g. sections added in newer kernels.
+    pub fn get_od_attributes(&self) -> Result<Vec<OdAttribute>> {
+        gpu_od::read_od_attributes(&self.sysfs_path.join("gpu_od"))
+    }
+
+    /// Reads and parses the binary `gpu_metrics` table.
+    pub fn get_gpu_metrics(&self) -> Result<GpuMetrics> {
+        let data = fs::read(self.sysfs_path.join("gpu_metrics"))
+            .context("Could not read file \"gpu_metrics\"")?;
+        GpuMetrics::parse(&data)
+    }
+
+    /// Gets the activity of the multimedia (UVD/VCN) engine used for video encoding and decoding, in percent.
+    ///
+    /// This is read from `gpu_metrics`, so it is only available on GPUs which report it there.
+    pub fn get_media_activity(&self) -> Result<u16> {
+        self.get_gpu_metrics()?.average_mm_activity.ok_or_else(|| {
+            ErrorKind::Unsupported("Media engine activity is not reported".to_owned()).into()
+        })
+    }
+
+    /// Returns the GPU activity percentage.
+    ///
+    /// Uses `gpu_busy_percent` when it is available, and falls back to the graphics activity from `gpu_metrics` otherwise.
+    pub fn get_activity(&self) -> Result<Activity> {
+        match self.get_busy_percent() {
+            Ok(percent) => Ok(Activity {
+                percent,
+                source: ActivitySource::BusyPercent,
+            }),
+            Err(busy_err) => {
+                let gfx_activity = self
+                    .get_gpu_metrics()
+                    .ok()
+                    .and_then(|metrics| metrics.average_gfx_activity);
+                match gfx_activity {
+                    Some(activity) => Ok(Activity {
+                        percent: activity.min(100) as u8,
+                        source: ActivitySource::GpuMetrics,
+                    }),
+                    None => Err(busy_err),
+                }
+            }
+        }
+    }
+
     /// Returns the GPU VBIOS version.
     pub fn get_vbios_version(&self) -> Result<String> {
         self.read_file("vbios_version")
@@ -192,21 +680,77 @@ impl GpuHandle {
         self.write_file("power_dpm_force_performance_level", level.to_string())
     }
 
+    /// Forces a given performance level and reads it back to check that it was applied.
+    ///
+    /// Some kernels silently ignore certain levels, in which case an [`ErrorKind::Rejected`] error is returned.
+    pub fn set_power_force_performance_level_verified(
+        &self,
+        level: PerformanceLevel,
+    ) -> Result<()> {
+        self.set_power_force_performance_level(level.clone())?;
+
+        let actual = self.get_power_force_performance_level()?;
+        if actual == level {
+            Ok(())
+        } else {
+            Err(ErrorKind::Rejected {
+                requested: level.to_string(),
+                actual: actual.to_string(),
+            }
+            .into())
+        }
+    }
+
     /// Retuns the list of power levels and index of the currently active level for a given kind of power state.
     /// `T` is the type that values should be deserialized into.
     pub fn get_clock_levels<T>(&self, kind: PowerLevelKind) -> Result<PowerLevels<T>>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        self.parse_clock_levels(kind, |value, _| value)
+    }
+
+    /// Same as [`GpuHandle::get_clock_levels`], but also keeps the original line of every level,
+    /// so that it can be displayed exactly as reported by the kernel.
+    pub fn get_clock_levels_with_raw<T>(
+        &self,
+        kind: PowerLevelKind,
+    ) -> Result<PowerLevels<PowerLevel<T>>>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        self.parse_clock_levels(kind, |value, raw_line| PowerLevel {
+            value,
+            raw: raw_line.to_owned(),
+        })
+    }
+
+    /// Parses a DPM file, building each level from its parsed value and the original line.
+    fn parse_clock_levels<T, L>(
+        &self,
+        kind: PowerLevelKind,
+        make_level: impl Fn(T, &str) -> L,
+    ) -> Result<PowerLevels<L>>
     where
         T: FromStr,
         <T as FromStr>::Err: Display,
     {
         self.read_file(kind.filename()).and_then(|content| {
-            let mut levels = Vec::new();
+            let lines: Vec<&str> = content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect();
+            let mut levels = Vec::with_capacity(lines.len());
             let mut active = None;
             let mut invalid_active = false;
 
-            for mut line in content.trim().split('\n') {
+            for raw_line in lines {
+                let mut line = raw_line;
                 if let Some(stripped) = line.strip_suffix('*') {
-                    line = stripped;
+                    line = stripped.trim_end();
 
                     if let Some(identifier) = stripped.split(':').next() {
                         if !invalid_active {
@@ -223,26 +767,25 @@ impl GpuHandle {
                         }
                     }
                 }
-                if let Some(s) = line.split(':').last() {
-                    let parse_result = if let Some(suffix) = kind.value_suffix() {
-                        let raw_value = s.trim().to_lowercase();
-                        let value = raw_value.strip_suffix(suffix).ok_or_else(|| {
-                            ErrorKind::ParseError {
-                                msg: format!("Level did not have the expected suffix {suffix}"),
-                                line: levels.len() + 1,
-                            }
-                        })?;
-                        T::from_str(value)
-                    } else {
-                        let value = s.trim();
-                        T::from_str(value)
-                    };
+                if let Some(s) = line.split(':').next_back() {
+                    let parse_result =
+                        if let Some(suffix) = kind.value_suffix() {
+                            let value = strip_suffix_ignore_ascii_case(s.trim(), suffix)
+                                .ok_or_else(|| ErrorKind::ParseError {
+                                    msg: format!("Level did not have the expected suffix {suffix}"),
+                                    line: levels.len() + 1,
+                                })?;
+                            T::from_str(value)
+                        } else {
+                            let value = s.trim();
+                            T::from_str(value)
+                        };
 
                     let parsed_value = parse_result.map_err(|err| ErrorKind::ParseError {
                         msg: format!("Could not deserialize power level value: {err}"),
                         line: levels.len() + 1,
                     })?;
-                    levels.push(parsed_value);
+                    levels.push(make_level(parsed_value, raw_line));
                 }
             }
 
@@ -250,74 +793,421 @@ impl GpuHandle {
         })
     }
 
+    /// Gets the levels from a DPM file as text, without parsing the values.
+    ///
+    /// Unlike [`GpuHandle::get_clock_levels`], this does not fail on unexpected formats,
+    /// so it can be used to at least display the levels on newer kernels.
+    pub fn get_clock_levels_raw(&self, kind: PowerLevelKind) -> Result<PowerLevels<String>> {
+        self.read_file(kind.filename())
+            .map(|content| power_levels::parse_raw_levels(&content))
+    }
+
     impl_get_clocks_levels!(get_core_clock_levels, PowerLevelKind::CoreClock, u64);
     impl_get_clocks_levels!(get_memory_clock_levels, PowerLevelKind::MemoryClock, u64);
     impl_get_clocks_levels!(get_pcie_clock_levels, PowerLevelKind::PcieSpeed, String);
+    impl_get_clocks_levels!(get_soc_clock_levels, PowerLevelKind::SOCClock, u64);
+    impl_get_clocks_levels!(get_fabric_clock_levels, PowerLevelKind::FabricClock, u64);
+    impl_get_clocks_levels!(get_dcef_clock_levels, PowerLevelKind::DCEFClock, u64);
+
+    /// Gets PCIe levels parsed into the link speed and width.
+    pub fn get_pcie_levels(&self) -> Result<PowerLevels<PcieLevel>> {
+        self.get_clock_levels(PowerLevelKind::PcieSpeed)
+    }
+
+    /// Locks the PCIe link to the levels matching the given generation and optional width, e.g. `Gen4 x16` or `Gen3`.
+    ///
+    /// Can only be used if `power_force_performance_level` is set to `manual`.
+    pub fn lock_pcie_link(&self, link: &str) -> Result<()> {
+        let (generation, width) = power_levels::parse_pcie_link(link)?;
+        let levels = self.get_pcie_levels()?;
+
+        let matching_levels: Vec<u8> = levels
+            .levels
+            .iter()
+            .enumerate()
+            .filter(|(_, level)| {
+                level.generation() == Some(generation)
+                    && width.is_none_or(|width| level.width == width)
+            })
+            .map(|(i, _)| i as u8)
+            .collect();
+
+        if matching_levels.is_empty() {
+            let available = levels
+                .levels
+                .iter()
+                .map(|level| level.raw.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(ErrorKind::NotAllowed(format!(
+                "No PCIe levels match {link}, available levels: {available}"
+            ))
+            .into());
+        }
+
+        self.set_enabled_power_levels(PowerLevelKind::PcieSpeed, &matching_levels)
+    }
 
     /// Sets the enabled power levels for a power state kind to a given list of levels. This means that only the given power levels will be allowed.
     ///
     /// Can only be used if `power_force_performance_level` is set to `manual`.
     pub fn set_enabled_power_levels(&self, kind: PowerLevelKind, levels: &[u8]) -> Result<()> {
-        match self.get_power_force_performance_level()? {
-            PerformanceLevel::Manual => {
-                let mut s = String::new();
+        self.check_manual_performance_level("adjust power levels")?;
 
-                for l in levels {
-                    s.push(char::from_digit((*l).into(), 10).unwrap());
-                    s.push(' ');
-                }
+        let s = levels
+            .iter()
+            .map(u8::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
 
-                Ok(self.write_file(kind.filename(), s)?)
-            }
-            _ => Err(ErrorKind::NotAllowed(
-                "power_force_performance level needs to be set to 'manual' to adjust power levels"
-                    .to_string(),
-            )
+        self.write_file(kind.filename(), s)
+    }
+
+    /// Enables only the power levels in the given range, checking that they exist in the DPM file.
+    ///
+    /// Can only be used if `power_force_performance_level` is set to `manual`.
+    pub fn set_enabled_power_levels_range(
+        &self,
+        kind: PowerLevelKind,
+        range: RangeInclusive<u8>,
+    ) -> Result<()> {
+        let count = self.get_power_level_count(&kind)?;
+        if range.is_empty() || usize::from(*range.end()) >= count {
+            return Err(ErrorKind::NotAllowed(format!(
+                "Invalid power level range {}..={}, {} has {count} levels",
+                range.start(),
+                range.end(),
+                kind.filename()
+            ))
+            .into());
+        }
+
+        let levels: Vec<u8> = range.collect();
+        self.set_enabled_power_levels(kind, &levels)
+    }
+
+    /// Enables only the highest power level.
+    ///
+    /// Can only be used if `power_force_performance_level` is set to `manual`.
+    pub fn enable_only_highest_power_level(&self, kind: PowerLevelKind) -> Result<()> {
+        let highest = self.get_power_level_count(&kind)? - 1;
+        self.set_enabled_power_levels_range(kind, highest as u8..=highest as u8)
+    }
+
+    /// Enables all of the available power levels.
+    ///
+    /// Can only be used if `power_force_performance_level` is set to `manual`.
+    pub fn enable_all_power_levels(&self, kind: PowerLevelKind) -> Result<()> {
+        let highest = self.get_power_level_count(&kind)? - 1;
+        self.set_enabled_power_levels_range(kind, 0..=highest as u8)
+    }
+
+    /// Sets the performance level to manual and allows only the highest core and memory clock levels.
+    pub fn force_highest_power_levels(&self) -> Result<()> {
+        self.set_power_force_performance_level(PerformanceLevel::Manual)?;
+        for kind in self.forced_power_level_kinds() {
+            self.enable_only_highest_power_level(kind)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the performance level to manual and allows only the lowest core and memory clock levels.
+    pub fn force_lowest_power_levels(&self) -> Result<()> {
+        self.set_power_force_performance_level(PerformanceLevel::Manual)?;
+        for kind in self.forced_power_level_kinds() {
+            self.set_enabled_power_levels_range(kind, 0..=0)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the performance level back to automatic, which also re-enables all of the power levels.
+    pub fn restore_auto_power_levels(&self) -> Result<()> {
+        self.set_power_force_performance_level(PerformanceLevel::Auto)
+    }
+
+    /// Core and memory clock kinds which exist on this GPU (integrated GPUs may not have `pp_dpm_mclk`)
+    fn forced_power_level_kinds(&self) -> impl Iterator<Item = PowerLevelKind> + '_ {
+        [PowerLevelKind::CoreClock, PowerLevelKind::MemoryClock]
+            .into_iter()
+            .filter(|kind| self.get_path().join(kind.filename()).exists())
+    }
+
+    /// Gets the number of levels in a DPM file. Returns an error if there are none.
+    pub(crate) fn get_power_level_count(&self, kind: &PowerLevelKind) -> Result<usize> {
+        let levels = self.get_clock_levels::<String>(kind.clone())?.levels;
+        match levels.len() {
+            0 => Err(ErrorKind::Unsupported(format!(
+                "No power levels found in {}",
+                kind.filename()
+            ))
+            .into()),
+            count => Ok(count.min(usize::from(u8::MAX) + 1)),
+        }
+    }
+
+    fn check_manual_performance_level(&self, action: &str) -> Result<()> {
+        match self.get_power_force_performance_level()? {
+            PerformanceLevel::Manual => Ok(()),
+            _ => Err(ErrorKind::NotAllowed(format!(
+                "power_force_performance level needs to be set to 'manual' to {action}, \
+                 use `set_power_force_performance_level(PerformanceLevel::Manual)` first"
+            ))
             .into()),
         }
     }
 
     /// Reads the clocks table from `pp_od_clk_voltage`.
+    ///
+    /// The first table which is read successfully is remembered as the default table, see [`GpuHandle::get_default_clocks_table`].
     #[cfg(feature = "overdrive")]
     pub fn get_clocks_table(&self) -> Result<ClocksTableGen> {
-        self.read_file_parsed("pp_od_clk_voltage")
+        let table: ClocksTableGen = optional_attribute(
+            self.read_file_parsed("pp_od_clk_voltage"),
+            "The clocks table is only available when overdrive is enabled with the amdgpu.ppfeaturemask kernel parameter",
+        )?;
+
+        let mut default_table = self.lock_default_clocks_table();
+        if default_table.is_none() {
+            *default_table = Some(table.clone());
+        }
+
+        Ok(table)
+    }
+
+    #[cfg(feature = "overdrive")]
+    fn lock_default_clocks_table(&self) -> MutexGuard<'_, Option<ClocksTableGen>> {
+        self.default_clocks_table
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Gets the default clocks table, which can be used to show relative adjustments or to restore the defaults.
+    ///
+    /// This is the table captured on the first read or after the last [`GpuHandle::reset_clocks_table`].
+    /// Note that if the table was modified before the first read (e.g. by another application), it will not contain factory values.
+    #[cfg(feature = "overdrive")]
+    pub fn get_default_clocks_table(&self) -> Result<ClocksTableGen> {
+        if let Some(table) = self.lock_default_clocks_table().as_ref() {
+            return Ok(table.clone());
+        }
+        self.get_clocks_table()
+    }
+
+    /// Re-reads the current clocks table and remembers it as the default one.
+    #[cfg(feature = "overdrive")]
+    pub fn capture_default_clocks_table(&self) -> Result<ClocksTableGen> {
+        let table: ClocksTableGen = self.read_file_parsed("pp_od_clk_voltage")?;
+        *self.lock_default_clocks_table() = Some(table.clone());
+        Ok(table)
+    }
+
+    /// Writes the default clocks table (see [`GpuHandle::get_default_clocks_table`]) back to `pp_od_clk_voltage`.
+    #[cfg(feature = "overdrive")]
+    pub fn restore_default_clocks_table(&self) -> Result<CommitHandle> {
+        let default_table = self.get_default_clocks_table()?;
+        self.set_clocks_table(&default_table)
     }
 
     /// Writes and commits the given clocks table to `pp_od_clk_voltage`.
     #[cfg(feature = "overdrive")]
     pub fn set_clocks_table(&self, new_table: &ClocksTableGen) -> Result<CommitHandle> {
         let old_table = self.get_clocks_table()?;
+        let commands = self.clocks_commands(new_table, &old_table)?;
+
+        let path = self.sysfs_path.join("pp_od_clk_voltage");
+        let mut file = sysfs::create(&path)?;
+
+        for command in commands {
+            file.write_all(format!("{command}\n").as_bytes())
+                .with_context(|| format!("Error when writing clocks table command `{command}`"))?;
+        }
+
+        Ok(CommitHandle::new(path))
+    }
+
+    /// Writes and commits the given clocks table to `pp_od_clk_voltage`, concatenating all commands into a single write.
+    ///
+    /// This reduces the window where the table is in a half-applied state, but is only accepted by kernels
+    /// which parse multiple commands per write. Prefer [`GpuHandle::set_clocks_table`] otherwise.
+    #[cfg(feature = "overdrive")]
+    pub fn set_clocks_table_batched(&self, new_table: &ClocksTableGen) -> Result<CommitHandle> {
+        let old_table = self.get_clocks_table()?;
+
+        let mut buf = String::new();
+        for command in self.clocks_commands(new_table, &old_table)? {
+            buf.push_str(&command);
+            buf.push('\n');
+        }
+
+        let path = self.sysfs_path.join("pp_od_clk_voltage");
+        sysfs::write(&path, buf).context("Could not write batched clocks table commands")?;
+
+        Ok(CommitHandle::new(path))
+    }
+
+    /// Gets the list of commands that would be written to `pp_od_clk_voltage` by [`GpuHandle::set_clocks_table`],
+    /// without writing anything. Useful for logging, confirmation dialogs and dry runs.
+    #[cfg(feature = "overdrive")]
+    pub fn preview_clocks_commands(&self, new_table: &ClocksTableGen) -> Result<Vec<String>> {
+        let old_table = self.get_clocks_table()?;
+        self.clocks_commands(new_table, &old_table)
+    }
+
+    /// Generates the commands for applying a clocks table, taking the GPU quirks into account.
+    #[cfg(feature = "overdrive")]
+    fn clocks_commands(
+        &self,
+        new_table: &ClocksTableGen,
+        old_table: &ClocksTableGen,
+    ) -> Result<Vec<String>> {
+        if let ClocksTableGen::Vega20(table) = new_table {
+            self.check_voltage_offset_quirk(table.voltage_offset)?;
+        }
+
+        let mut commands = new_table.get_commands(old_table)?;
+        if self.quirks.contains(&Quirk::MclkBeforeSclk) {
+            quirks::order_mclk_first(&mut commands);
+        }
+        Ok(commands)
+    }
+
+    /// Checks the voltage offset against the [`Quirk::MaxVoltageOffset`] limit, if the GPU has one.
+    #[cfg(feature = "overdrive")]
+    pub(crate) fn check_voltage_offset_quirk(&self, offset: Option<i32>) -> Result<()> {
+        let Some(offset) = offset else {
+            return Ok(());
+        };
+        for quirk in &self.quirks {
+            if let Quirk::MaxVoltageOffset(limit) = quirk {
+                if offset.abs() > *limit {
+                    return Err(Error::not_allowed(format!(
+                        "Voltage offset {offset} exceeds the limit of {limit}mV for this GPU"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes only the changed voltage curve points and voltage offset of the given table to `pp_od_clk_voltage`,
+    /// without any clockspeed commands. See [`vega20::Table::write_voltage_commands`](overdrive::vega20::Table::write_voltage_commands).
+    ///
+    /// Only supported on the Vega20 (and newer) table format.
+    #[cfg(feature = "overdrive")]
+    pub fn set_clocks_table_voltages(
+        &self,
+        new_table: &overdrive::vega20::Table,
+    ) -> Result<CommitHandle> {
+        let ClocksTableGen::Vega20(old_table) = self.get_clocks_table()? else {
+            return Err(ErrorKind::Unsupported(
+                "Voltage-only adjustments are only supported on Vega20 and newer".to_owned(),
+            )
+            .into());
+        };
+
+        self.check_voltage_offset_quirk(new_table.voltage_offset)?;
 
         let path = self.sysfs_path.join("pp_od_clk_voltage");
-        let mut file = File::create(&path)?;
+        let mut file = sysfs::create(&path)?;
 
-        new_table.write_commands(&mut file, &old_table)?;
+        new_table.write_voltage_commands(&mut file, &old_table)?;
 
         Ok(CommitHandle::new(path))
     }
 
     /// Resets the clocks table to the default configuration.
+    ///
+    /// The table after the reset is remembered as the default one, see [`GpuHandle::get_default_clocks_table`].
     #[cfg(feature = "overdrive")]
     pub fn reset_clocks_table(&self) -> Result<()> {
         let path = self.sysfs_path.join("pp_od_clk_voltage");
-        let mut file = File::create(path)?;
+        let mut file = sysfs::create(&path)?;
         file.write_all(b"r\n")?;
 
+        if self.capture_default_clocks_table().is_err() {
+            *self.lock_default_clocks_table() = None;
+        }
+
         Ok(())
     }
 
     /// Reads the list of predefined power profiles and the relevant heuristics settings for them from `pp_power_profile_mode`
     ///
+    /// The table is also cached, see [`GpuHandle::get_cached_power_profile_modes`].
+    ///
     /// https://kernel.org/doc/html/latest/gpu/amdgpu/thermal.html#pp-power-profile-mode
     pub fn get_power_profile_modes(&self) -> Result<PowerProfileModesTable> {
         let contents = self.read_file("pp_power_profile_mode")?;
-        PowerProfileModesTable::parse(&contents)
+        let table = PowerProfileModesTable::parse(&contents)?;
+        *self.lock_power_profile_modes() = Some(table.clone());
+        Ok(table)
+    }
+
+    /// Gets the power profile modes table, only reading it from `pp_power_profile_mode` if it is not cached yet.
+    ///
+    /// The cached table is updated when changing the profile or heuristics through this handle.
+    /// If the file may have been changed externally, [`GpuHandle::invalidate_power_profile_modes`] should be called.
+    pub fn get_cached_power_profile_modes(&self) -> Result<PowerProfileModesTable> {
+        if let Some(table) = self.lock_power_profile_modes().as_ref() {
+            return Ok(table.clone());
+        }
+        self.get_power_profile_modes()
+    }
+
+    /// Clears the cached power profile modes table, so that it gets read again on the next use.
+    pub fn invalidate_power_profile_modes(&self) {
+        *self.lock_power_profile_modes() = None;
+    }
+
+    fn lock_power_profile_modes(&self) -> MutexGuard<'_, Option<PowerProfileModesTable>> {
+        self.power_profile_modes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
     }
 
     /// Sets the current power profile mode. You can get the available modes with [`get_power_profile_modes`].
     /// Requires the performance level to be set to "manual" first using [`set_power_force_performance_level`]
+    ///
+    /// Returns a `NotAllowed` error when a different performance level is set, as the kernel may silently ignore the new mode otherwise.
     pub fn set_active_power_profile_mode(&self, i: u16) -> Result<()> {
-        self.write_file("pp_power_profile_mode", format!("{i}\n"))
+        // Without the performance level file there is no way to change it, so the mode is written as is
+        if let Err(err) = self.check_manual_performance_level("change the power profile mode") {
+            if !err.is_not_found() {
+                return Err(err);
+            }
+        }
+
+        let result = self.write_file("pp_power_profile_mode", format!("{i}\n"));
+
+        let mut cached_table = self.lock_power_profile_modes();
+        match (&result, cached_table.as_mut()) {
+            (Ok(()), Some(table)) => table.active = i,
+            (Err(_), _) => *cached_table = None,
+            (Ok(()), None) => (),
+        }
+
+        result
+    }
+
+    /// Sets the current power profile mode by its type, looking up the index from [`get_power_profile_modes`].
+    /// The indices of the modes differ between GPU generations, so this should be preferred over [`set_active_power_profile_mode`].
+    /// Requires the performance level to be set to "manual" first using [`set_power_force_performance_level`]
+    pub fn set_active_power_profile(&self, profile: StandardProfile) -> Result<()> {
+        let table = self.get_cached_power_profile_modes()?;
+        let (index, _) = table.find_profile(&profile).ok_or_else(|| {
+            ErrorKind::Unsupported(format!("Power profile {profile} is not available"))
+        })?;
+        self.set_active_power_profile_mode(index)
+    }
+
+    /// Sets the current power profile mode by its name as reported in [`get_power_profile_modes`] (case-insensitive).
+    /// Requires the performance level to be set to "manual" first using [`set_power_force_performance_level`]
+    pub fn set_active_power_profile_by_name(&self, name: &str) -> Result<()> {
+        let table = self.get_cached_power_profile_modes()?;
+        let (index, _) = table.find_profile_by_name(name).ok_or_else(|| {
+            ErrorKind::Unsupported(format!("Power profile {name} is not available"))
+        })?;
+        self.set_active_power_profile_mode(index)
     }
 
     /// Sets a custom power profile mode. You can get the available modes, and the list of heuristic names with [`get_power_profile_modes`].
@@ -326,56 +1216,60 @@ impl GpuHandle {
         &self,
         components: &[Vec<Option<i32>>],
     ) -> Result<()> {
-        let table = self.get_power_profile_modes()?;
-        let (index, current_custom_profile) = table
-            .modes
-            .iter()
-            .find(|(_, profile)| profile.is_custom())
-            .ok_or_else(|| {
-                ErrorKind::NotAllowed("Could not find a custom power profile".to_owned())
-            })?;
+        let table = self.get_cached_power_profile_modes()?;
+        let updates = table.custom_heuristics_updates(components)?;
+        self.write_custom_heuristics_updates(&table, updates)
+    }
 
-        if current_custom_profile.components.len() != components.len() {
-            return Err(ErrorKind::NotAllowed(format!(
-                "Expected {} power profile components, got {}",
-                current_custom_profile.components.len(),
-                components.len()
-            ))
-            .into());
-        }
+    /// Sets the heuristics of the custom power profile mode by name.
+    /// See [`PowerProfileModesTable::custom_heuristics_commands_by_name`] for how the values are validated.
+    /// Requires the performance level to be set to "manual" first using [`set_power_force_performance_level`]
+    pub fn set_custom_power_profile_mode_heuristics_by_name(
+        &self,
+        heuristics: &[NamedHeuristics],
+    ) -> Result<()> {
+        let table = self.get_cached_power_profile_modes()?;
+        let updates = table.named_heuristics_updates(heuristics)?;
+        self.write_custom_heuristics_updates(&table, updates)
+    }
 
-        if current_custom_profile.components.len() == 1 {
-            let mut values_command = format!("{index}");
-            for heuristic in &components[0] {
-                match heuristic {
-                    Some(value) => write!(values_command, " {value}").unwrap(),
-                    None => write!(values_command, " -").unwrap(),
-                }
+    fn write_custom_heuristics_updates(
+        &self,
+        table: &PowerProfileModesTable,
+        updates: Vec<(usize, Vec<Option<i32>>)>,
+    ) -> Result<()> {
+        let commands = table.custom_heuristics_update_commands(&updates)?;
+        for command in commands {
+            if let Err(err) = self.write_file("pp_power_profile_mode", command) {
+                self.invalidate_power_profile_modes();
+                return Err(err);
             }
+        }
 
-            values_command.push('\n');
-            self.write_file("pp_power_profile_mode", values_command)
-        } else {
-            for (component_index, heuristics) in components.iter().enumerate() {
-                let mut values_command = format!("{index} {component_index}");
-                for heuristic in heuristics {
-                    match heuristic {
-                        Some(value) => write!(values_command, " {value}").unwrap(),
-                        None => write!(values_command, " -").unwrap(),
-                    }
-                }
-                values_command.push('\n');
+        if let Some(cached_table) = self.lock_power_profile_modes().as_mut() {
+            cached_table.apply_custom_heuristics_updates(updates);
+        }
+        Ok(())
+    }
 
-                self.write_file("pp_power_profile_mode", values_command)?;
-            }
+    /// Captures the current heuristics of the custom power profile mode, so that they can be restored later
+    /// (e.g. after a GPU reset or reboot) with [`GpuHandle::restore_custom_power_profile_mode_heuristics`].
+    pub fn capture_custom_power_profile_mode_heuristics(&self) -> Result<CustomHeuristics> {
+        self.get_power_profile_modes()?.custom_heuristics()
+    }
 
-            Ok(())
-        }
+    /// Applies previously captured heuristics of the custom power profile mode.
+    /// Requires the performance level to be set to "manual" first using [`set_power_force_performance_level`]
+    pub fn restore_custom_power_profile_mode_heuristics(
+        &self,
+        heuristics: &CustomHeuristics,
+    ) -> Result<()> {
+        self.set_custom_power_profile_mode_heuristics_by_name(&heuristics.components)
     }
 
     fn read_fan_info(&self, file: &str, section_name: &str, range_name: &str) -> Result<FanInfo> {
         let file_path = self.get_path().join("gpu_od/fan_ctrl").join(file);
-        let data = self.read_file(file_path)?;
+        let data = optional_attribute(self.read_file(file_path), FAN_CTRL_REQUIREMENT)?;
         let contents = FanCtrlContents::parse(&data, section_name)?;
 
         let current = contents.contents.parse()?;
@@ -384,10 +1278,12 @@ impl GpuHandle {
             Some((raw_min, raw_max)) => {
                 let min = raw_min.parse()?;
                 let max = raw_max.parse()?;
-                Some((min, max))
+                Some(min..=max)
             }
             None => None,
         };
+        self.lock_fan_ranges()
+            .insert(file.to_owned(), allowed_range.clone());
 
         Ok(FanInfo {
             current,
@@ -395,6 +1291,26 @@ impl GpuHandle {
         })
     }
 
+    /// Gets the allowed range of a fan setting. The ranges don't change at runtime, so they are only read once.
+    fn get_fan_range(
+        &self,
+        file: &str,
+        section_name: &str,
+        range_name: &str,
+    ) -> Result<Option<RangeInclusive<u32>>> {
+        if let Some(range) = self.lock_fan_ranges().get(file) {
+            return Ok(range.clone());
+        }
+        self.read_fan_info(file, section_name, range_name)
+            .map(|info| info.allowed_range)
+    }
+
+    fn lock_fan_ranges(&self) -> MutexGuard<'_, HashMap<String, Option<RangeInclusive<u32>>>> {
+        self.fan_ranges
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
     /// Gets the fan acoustic limit. Values are in RPM.
     ///
     /// Only available on Navi3x (RDNA 3) or newer.
@@ -462,26 +1378,37 @@ impl GpuHandle {
         )
     }
 
-    fn set_fan_value(
+    fn set_fan_value(
+        &self,
+        file: &str,
+        value: u32,
+        section_name: &str,
+        range_name: &str,
+    ) -> Result<CommitHandle> {
+        let file_path = self.check_fan_value(file, value, section_name, range_name)?;
+        sysfs::write(&file_path, format!("{value}\n"))?;
+        Ok(CommitHandle::new(file_path))
+    }
+
+    /// Checks that a fan setting can be changed to the given value, and returns the path of its file.
+    fn check_fan_value(
         &self,
         file: &str,
         value: u32,
         section_name: &str,
         range_name: &str,
-    ) -> Result<CommitHandle> {
-        let info = self.read_fan_info(file, section_name, range_name)?;
-        match info.allowed_range {
-            Some((min, max)) => {
-                if !(min..=max).contains(&value) {
+    ) -> Result<PathBuf> {
+        match self.get_fan_range(file, section_name, range_name)? {
+            Some(range) => {
+                if !range.contains(&value) {
                     return Err(Error::not_allowed(format!(
-                        "Value {value} is out of range, should be between {min} and {max}"
+                        "Value {value} is out of range, should be between {} and {}",
+                        range.start(),
+                        range.end()
                     )));
                 }
 
-                let file_path = self.sysfs_path.join("gpu_od/fan_ctrl").join(file);
-                std::fs::write(&file_path, format!("{value}\n"))?;
-
-                Ok(CommitHandle::new(file_path))
+                Ok(self.sysfs_path.join("gpu_od/fan_ctrl").join(file))
             }
             None => Err(Error::not_allowed(format!(
                 "Changes to {range_name} are not allowed"
@@ -561,7 +1488,7 @@ impl GpuHandle {
 
     fn reset_fan_value(&self, file: &str) -> Result<()> {
         let file_path = self.sysfs_path.join("gpu_od/fan_ctrl").join(file);
-        let mut file = File::create(file_path)?;
+        let mut file = sysfs::create(&file_path)?;
         writeln!(file, "r")?;
         Ok(())
     }
@@ -599,91 +1526,241 @@ impl GpuHandle {
     }
 
     /// Gets the PMFW (power management firmware) fan curve.
-    /// Note: if no custom curve is used, all of the curve points may be set to 0.
+    /// Note: if no custom curve is used, all of the curve points may be set to 0, see [`FanCurve::is_default`].
     ///
     /// Only available on Navi3x (RDNA 3) or newer.
     /// Older GPUs do not have a configurable fan curve in firmware, they need custom logic.
     pub fn get_fan_curve(&self) -> Result<FanCurve> {
-        let data = self.read_file("gpu_od/fan_ctrl/fan_curve")?;
-        let contents = FanCtrlContents::parse(&data, "OD_FAN_CURVE")?;
-        let points = contents
-            .contents
-            .lines()
-            .enumerate()
-            .map(|(i, line)| {
-                let mut split = line.split(' ');
-                split.next(); // Discard index
-
-                let raw_temp = split
-                    .next()
-                    .ok_or_else(|| Error::unexpected_eol("Temperature value", i))?;
-                let temp = raw_temp.trim_end_matches('C').parse()?;
+        let data = optional_attribute(
+            self.read_file("gpu_od/fan_ctrl/fan_curve"),
+            FAN_CTRL_REQUIREMENT,
+        )?;
+        let mut curve = FanCurve::from_str(&data)?;
+
+        if self.quirks.contains(&Quirk::FanCurvePointsOutOfRange) && !curve.is_default() {
+            if let Some(ranges) = &curve.allowed_ranges {
+                for point in curve.points.iter_mut() {
+                    point.temperature = point.temperature.clamp(
+                        *ranges.temperature_range.start(),
+                        *ranges.temperature_range.end(),
+                    );
+                    point.speed = point
+                        .speed
+                        .clamp(*ranges.speed_range.start(), *ranges.speed_range.end());
+                }
+            }
+        }
 
-                let raw_speed = split
-                    .next()
-                    .ok_or_else(|| Error::unexpected_eol("Speed value", i))?;
-                let speed = raw_speed.trim_end_matches('%').parse()?;
+        Ok(curve)
+    }
 
-                Ok((temp, speed))
-            })
-            .collect::<Result<_>>()?;
+    /// Sets and applies the PMFW fan curve.
+    ///
+    /// All of the points are validated before anything is written, so an invalid point does not leave the curve partially applied.
+    /// The kernel only accepts a single point per write, so each point is still written separately.
+    ///
+    /// If the curve has fewer points than supported by the firmware, the last point is repeated for the remaining ones,
+    /// so that no stale points from the previous curve are left over.
+    ///
+    /// Only available on Navi3x (RDNA 3) or newer.
+    /// <https://kernel.org/doc/html/latest/gpu/amdgpu/thermal.html#fan-curve>
+    pub fn set_fan_curve(&self, new_curve: &FanCurve) -> Result<CommitHandle> {
+        let (file_path, curve) = self.prepare_fan_curve(new_curve)?;
+        write_fan_curve(&file_path, &curve)?;
+        Ok(CommitHandle::new(file_path))
+    }
 
-        let temp_range = contents.od_range.get("FAN_CURVE(hotspot temp)");
-        let speed_range = contents.od_range.get("FAN_CURVE(fan speed)");
+    /// Checks that the fan curve can be applied, and returns the path of its file with the curve padded to the supported point count.
+    fn prepare_fan_curve(&self, new_curve: &FanCurve) -> Result<(PathBuf, FanCurve)> {
+        let current_curve = self.get_fan_curve()?;
+        let allowed_ranges = current_curve.allowed_ranges.ok_or_else(|| {
+            Error::not_allowed("Changes to the fan curve are not supported".to_owned())
+        })?;
 
-        let allowed_ranges = if let Some(((min_temp, max_temp), (min_speed, max_speed))) =
-            (temp_range).zip(speed_range)
-        {
-            let min_temp: i32 = min_temp.trim_end_matches('C').parse()?;
-            let max_temp: i32 = max_temp.trim_end_matches('C').parse()?;
+        let point_count = current_curve.points.len();
+        let Some(last_point) = new_curve.points.last() else {
+            return Err(Error::not_allowed(format!(
+                "The fan curve requires {point_count} points, got none"
+            )));
+        };
+        if new_curve.points.len() > point_count {
+            return Err(Error::not_allowed(format!(
+                "The fan curve supports {point_count} points, got {}",
+                new_curve.points.len()
+            )));
+        }
 
-            let min_speed: u8 = min_speed.trim_end_matches('%').parse()?;
-            let max_speed: u8 = max_speed.trim_end_matches('%').parse()?;
+        for point in new_curve.points.iter() {
+            allowed_ranges.check_point(point.temperature, point.speed)?;
+        }
 
-            Some(FanCurveRanges {
-                temperature_range: min_temp..=max_temp,
-                speed_range: min_speed..=max_speed,
-            })
-        } else {
-            None
+        let mut points = new_curve.points.to_vec();
+        points.resize(point_count, *last_point);
+        let curve = FanCurve {
+            points: points.into_boxed_slice(),
+            allowed_ranges: Some(allowed_ranges),
         };
 
-        Ok(FanCurve {
-            points,
-            allowed_ranges,
-        })
+        Ok((self.sysfs_path.join("gpu_od/fan_ctrl/fan_curve"), curve))
     }
 
-    /// Sets and applies the PMFW fan curve.
+    /// Sets a single point of the PMFW fan curve, leaving the other points unchanged.
     ///
     /// Only available on Navi3x (RDNA 3) or newer.
     /// <https://kernel.org/doc/html/latest/gpu/amdgpu/thermal.html#fan-curve>
-    pub fn set_fan_curve(&self, new_curve: &FanCurve) -> Result<CommitHandle> {
+    pub fn set_fan_curve_point(
+        &self,
+        index: usize,
+        temperature: i32,
+        speed: u8,
+    ) -> Result<CommitHandle> {
         let current_curve = self.get_fan_curve()?;
         let allowed_ranges = current_curve.allowed_ranges.ok_or_else(|| {
             Error::not_allowed("Changes to the fan curve are not supported".to_owned())
         })?;
 
+        if index >= current_curve.points.len() {
+            return Err(Error::not_allowed(format!(
+                "The fan curve has {} points, point {index} does not exist",
+                current_curve.points.len()
+            )));
+        }
+        allowed_ranges.check_point(temperature, speed)?;
+
         let file_path = self.sysfs_path.join("gpu_od/fan_ctrl/fan_curve");
+        sysfs::write(&file_path, format!("{index} {temperature} {speed}\n"))?;
 
-        for (i, (temperature, speed)) in new_curve.points.iter().enumerate() {
-            if !allowed_ranges.temperature_range.contains(temperature) {
-                Err(Error::not_allowed(format!(
-                    "Temperature value {temperature} is outside of the allowed range {:?}",
-                    allowed_ranges.temperature_range
-                )))?;
-            }
-            if !allowed_ranges.speed_range.contains(speed) {
-                Err(Error::not_allowed(format!(
-                    "Speed value {speed} is outside of the allowed range {:?}",
-                    allowed_ranges.speed_range
-                )))?;
-            }
+        Ok(CommitHandle::new(file_path))
+    }
+
+    /// Reads all of the PMFW fan settings. Settings which are not available are left empty.
+    ///
+    /// Only available on Navi3x (RDNA 3) or newer.
+    pub fn get_pmfw_settings(&self) -> Result<PmfwSettings> {
+        Ok(PmfwSettings {
+            acoustic_limit: optional_fan_setting(self.get_fan_acoustic_limit())?
+                .map(|info| info.current),
+            acoustic_target: optional_fan_setting(self.get_fan_acoustic_target())?
+                .map(|info| info.current),
+            target_temperature: optional_fan_setting(self.get_fan_target_temperature())?
+                .map(|info| info.current),
+            minimum_pwm: optional_fan_setting(self.get_fan_minimum_pwm())?.map(|info| info.current),
+            zero_rpm_enable: optional_fan_setting(self.get_fan_zero_rpm_enable())?,
+            zero_rpm_stop_temperature: optional_fan_setting(
+                self.get_fan_zero_rpm_stop_temperature(),
+            )?
+            .map(|info| info.current),
+            curve: optional_fan_setting(self.get_fan_curve())?,
+        })
+    }
+
+    /// Applies all of the given PMFW fan settings. Settings which are empty are left unchanged.
+    ///
+    /// All of the values are validated before anything is written. The changes are applied together with a single commit.
+    ///
+    /// Only available on Navi3x (RDNA 3) or newer.
+    pub fn apply_pmfw_settings(&self, settings: &PmfwSettings) -> Result<CommitHandle> {
+        let (writes, prepared_curve) = self.prepare_pmfw_settings(settings)?;
 
-            std::fs::write(&file_path, format!("{i} {temperature} {speed}\n"))?;
+        let mut last_path = None;
+        for (file_path, value) in writes {
+            sysfs::write(&file_path, format!("{value}\n"))?;
+            last_path = Some(file_path);
+        }
+        if let Some((curve_path, curve)) = prepared_curve {
+            write_fan_curve(&curve_path, &curve)?;
+            last_path = Some(curve_path);
         }
 
-        Ok(CommitHandle::new(file_path))
+        // Committing any of the files applies all of the staged fan settings
+        last_path
+            .map(CommitHandle::new)
+            .ok_or_else(|| Error::not_allowed("No fan settings to apply".to_owned()))
+    }
+
+    /// Validates the given PMFW settings, returning the values to be written to each file and the padded fan curve.
+    pub(crate) fn prepare_pmfw_settings(
+        &self,
+        settings: &PmfwSettings,
+    ) -> Result<PreparedPmfwSettings> {
+        let values = [
+            (
+                settings.acoustic_limit,
+                "acoustic_limit_rpm_threshold",
+                "OD_ACOUSTIC_LIMIT",
+                "ACOUSTIC_LIMIT",
+            ),
+            (
+                settings.acoustic_target,
+                "acoustic_target_rpm_threshold",
+                "OD_ACOUSTIC_TARGET",
+                "ACOUSTIC_TARGET",
+            ),
+            (
+                settings.target_temperature,
+                "fan_target_temperature",
+                "FAN_TARGET_TEMPERATURE",
+                "TARGET_TEMPERATURE",
+            ),
+            (
+                settings.minimum_pwm,
+                "fan_minimum_pwm",
+                "FAN_MINIMUM_PWM",
+                "MINIMUM_PWM",
+            ),
+            (
+                settings.zero_rpm_enable.map(u32::from),
+                "fan_zero_rpm_enable",
+                "FAN_ZERO_RPM_ENABLE",
+                "ZERO_RPM_ENABLE",
+            ),
+            (
+                settings.zero_rpm_stop_temperature,
+                "fan_zero_rpm_stop_temperature",
+                "FAN_ZERO_RPM_STOP_TEMPERATURE",
+                "ZERO_RPM_STOP_TEMPERATURE",
+            ),
+        ];
+
+        let mut writes = Vec::new();
+        for (value, file, section_name, range_name) in values {
+            if let Some(value) = value {
+                let file_path = self.check_fan_value(file, value, section_name, range_name)?;
+                writes.push((file_path, value));
+            }
+        }
+        let prepared_curve = match &settings.curve {
+            Some(curve) => Some(self.prepare_fan_curve(curve)?),
+            None => None,
+        };
+
+        Ok((writes, prepared_curve))
+    }
+
+    fn lock_default_fan_settings(&self) -> MutexGuard<'_, Option<PmfwSettings>> {
+        self.default_fan_settings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Reads the current PMFW fan settings and remembers them as the defaults.
+    ///
+    /// This should be called when the settings are known to be stock, e.g. on startup or after resetting them.
+    pub fn capture_default_fan_settings(&self) -> Result<PmfwSettings> {
+        let settings = self.get_pmfw_settings()?;
+        *self.lock_default_fan_settings() = Some(settings.clone());
+        Ok(settings)
+    }
+
+    /// Gets the default PMFW fan settings, which can be used to show the stock values next to the current ones.
+    ///
+    /// These are the settings captured with [`GpuHandle::capture_default_fan_settings`].
+    /// If they were not captured yet, the current settings are captured.
+    pub fn get_default_fan_settings(&self) -> Result<PmfwSettings> {
+        if let Some(settings) = self.lock_default_fan_settings().as_ref() {
+            return Ok(settings.clone());
+        }
+        self.capture_default_fan_settings()
     }
 
     /// Resets the PMFW fan curve.
@@ -693,6 +1770,181 @@ impl GpuHandle {
     pub fn reset_fan_curve(&self) -> Result<()> {
         self.reset_fan_value("fan_curve")
     }
+
+    /// Restores the default settings across all subsystems: enabled power levels, the clocks table,
+    /// PMFW fan settings, the power cap and the performance level (to [`PerformanceLevel::Auto`]).
+    ///
+    /// Every step is attempted even if a previous one fails. Steps which are not supported on the GPU are skipped.
+    pub fn restore_defaults(&self) -> RestoreReport {
+        let mut results = Vec::new();
+
+        if self.get_power_force_performance_level().ok() == Some(PerformanceLevel::Manual) {
+            let result = [
+                PowerLevelKind::CoreClock,
+                PowerLevelKind::MemoryClock,
+                PowerLevelKind::SOCClock,
+                PowerLevelKind::FabricClock,
+                PowerLevelKind::DCEFClock,
+                PowerLevelKind::PcieSpeed,
+            ]
+            .into_iter()
+            .filter(|kind| self.get_path().join(kind.filename()).exists())
+            .try_for_each(|kind| self.enable_all_power_levels(kind));
+            results.push((RestoreStep::PowerLevels, result));
+        }
+
+        #[cfg(feature = "overdrive")]
+        if self.get_path().join("pp_od_clk_voltage").exists() {
+            results.push((RestoreStep::ClocksTable, self.reset_clocks_table()));
+        }
+
+        if self.get_path().join("gpu_od/fan_ctrl").exists() {
+            results.push((RestoreStep::FanSettings, self.reset_fan_settings()));
+        }
+
+        let power_cap_result = self.with_hw_mon(|hw_mon| {
+            // Makes a stale monitor fail with a not found error, so that it gets rescanned
+            hw_mon.read_file("name")?;
+            if !hw_mon.get_path().join("power1_cap").exists() {
+                return Ok(None);
+            }
+            hw_mon
+                .get_power_cap_default()
+                .and_then(|cap| hw_mon.set_power_cap(cap))
+                .map(Some)
+        });
+        match power_cap_result {
+            Ok(None) => (),
+            Err(err) if matches!(err.kind, ErrorKind::Unsupported(_)) => (),
+            result => results.push((RestoreStep::PowerCap, result.map(|_| ()))),
+        }
+
+        if self
+            .get_path()
+            .join("power_dpm_force_performance_level")
+            .exists()
+        {
+            let result = self.set_power_force_performance_level(PerformanceLevel::Auto);
+            results.push((RestoreStep::PerformanceLevel, result));
+        }
+
+        RestoreReport { results }
+    }
+
+    /// Resets and commits all of the available PMFW fan settings.
+    fn reset_fan_settings(&self) -> Result<()> {
+        let mut last_path = None;
+        for file in [
+            "fan_curve",
+            "acoustic_limit_rpm_threshold",
+            "acoustic_target_rpm_threshold",
+            "fan_target_temperature",
+            "fan_minimum_pwm",
+            "fan_zero_rpm_enable",
+            "fan_zero_rpm_stop_temperature",
+        ] {
+            let path = self.sysfs_path.join("gpu_od/fan_ctrl").join(file);
+            if path.exists() {
+                self.reset_fan_value(file)?;
+                last_path = Some(path);
+            }
+        }
+
+        match last_path {
+            Some(path) => CommitHandle::new(path).commit(),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks the GPU for signs of problems, such as being hung or having unreadable attributes.
+    ///
+    /// The PCIe replay counter is compared against the value from the previous check on this handle,
+    /// so periodic checks will report any new link errors.
+    /// Ring fence information is included if debugfs is accessible.
+    pub fn health_check(&self) -> HealthReport {
+        self.health_check_with_debugfs(Path::new("/sys/kernel/debug/dri"))
+    }
+
+    fn health_check_with_debugfs(&self, debugfs_dri_path: &Path) -> HealthReport {
+        let mut issues = Vec::new();
+
+        let mut check = |attribute: &str, err: &Error| {
+            // Attributes which don't exist are simply unsupported on the GPU
+            if !err.is_not_found() && !matches!(err.kind, ErrorKind::Unsupported(_)) {
+                issues.push(HealthIssue::UnreadableAttribute {
+                    attribute: attribute.to_owned(),
+                    error: err.to_string(),
+                });
+            }
+        };
+
+        let busy_percent = self.get_busy_percent();
+        if let Err(err) = &busy_percent {
+            check("gpu_busy_percent", err);
+        }
+        if let Err(err) = self.get_used_vram() {
+            check("mem_info_vram_used", &err);
+        }
+        if let Err(err) = self.get_power_force_performance_level() {
+            check("power_dpm_force_performance_level", &err);
+        }
+
+        let gpu_clockspeed = match self.with_hw_mon(HwMon::get_gpu_clockspeed) {
+            Ok(clockspeed) => Some(clockspeed),
+            Err(err) if matches!(err.kind, ErrorKind::Unsupported(_)) => None,
+            Err(err) => {
+                check("freq1_input", &err);
+                None
+            }
+        };
+
+        match self.read_file_parsed::<u64, _>("pcie_replay_count") {
+            Ok(count) => {
+                let mut last_count = self
+                    .pcie_replay_count
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                if let Some(last_count) = *last_count {
+                    if count > last_count {
+                        issues.push(HealthIssue::PcieReplays {
+                            count: count - last_count,
+                        });
+                    }
+                }
+                *last_count = Some(count);
+            }
+            Err(err) => check("pcie_replay_count", &err),
+        }
+
+        if busy_percent.ok() == Some(100) && gpu_clockspeed == Some(0) {
+            issues.push(HealthIssue::BusyWithoutClock);
+        }
+
+        HealthReport::new(issues, self.read_fence_info(debugfs_dri_path))
+    }
+
+    fn read_fence_info(&self, debugfs_dri_path: &Path) -> Vec<FenceInfo> {
+        let mut dirs = Vec::new();
+        if let Some(slot_name) = self.get_pci_slot_name() {
+            dirs.push(debugfs_dri_path.join(slot_name));
+        }
+        if let Ok(entries) = fs::read_dir(self.sysfs_path.join("drm")) {
+            for entry in entries.flatten() {
+                if let Some(minor) = entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|name| name.strip_prefix("card"))
+                {
+                    dirs.push(debugfs_dri_path.join(minor));
+                }
+            }
+        }
+
+        dirs.into_iter()
+            .find_map(|dir| fs::read_to_string(dir.join("amdgpu_fence_info")).ok())
+            .and_then(|data| health::parse_fence_info(&data).ok())
+            .unwrap_or_default()
+    }
 }
 
 impl SysFS for GpuHandle {
@@ -701,12 +1953,56 @@ impl SysFS for GpuHandle {
     }
 }
 
+/// Formats the card name, PCI slot, PCI ids, driver and VBIOS version, e.g.
+/// `card0 (0000:09:00.0), PCI ID 1002:67DF, subsystem 1DA2:E387, driver amdgpu, VBIOS 113-1E3871U-O4C`.
+///
+/// Values which are not available are left out.
+impl Display for GpuHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get_card_name() {
+            Some(card_name) => write!(f, "{card_name}")?,
+            None => write!(f, "GPU")?,
+        }
+        if let Some(slot_name) = self.get_pci_slot_name() {
+            write!(f, " ({slot_name})")?;
+        }
+        if let Some((vendor_id, device_id)) = self.get_pci_id() {
+            write!(f, ", PCI ID {vendor_id}:{device_id}")?;
+        }
+        if let Some((vendor_id, device_id)) = self.get_pci_subsys_id() {
+            write!(f, ", subsystem {vendor_id}:{device_id}")?;
+        }
+        write!(f, ", driver {}", self.get_driver())?;
+        if let Ok(vbios_version) = self.get_vbios_version() {
+            write!(f, ", VBIOS {vbios_version}")?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for GpuHandle {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity_key() == other.identity_key()
+    }
+}
+
+impl Eq for GpuHandle {}
+
+impl Hash for GpuHandle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.identity_key().hash(state);
+    }
+}
+
 /// Performance level to be used by the GPU.
 ///
 /// <https://kernel.org/doc/html/latest/gpu/amdgpu/thermal.html#pp-od-clk-voltage>
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[non_exhaustive]
 pub enum PerformanceLevel {
     /// When auto is selected, the driver will attempt to dynamically select the optimal power profile for current conditions in the driver.
     #[default]
@@ -717,6 +2013,11 @@ pub enum PerformanceLevel {
     High,
     /// When manual is selected, power states can be manually adjusted via `pp_dpm_*` files ([`GpuHandle::set_enabled_power_levels`]) and `pp_od_clk_voltage` ([`GpuHandle::set_clocks_table`]).
     Manual,
+    /// A level which is not known to this library, such as the `profile_*` levels, given by its name.
+    ///
+    /// It is written back unchanged when setting it.
+    #[cfg_attr(feature = "serde", serde(untagged))]
+    Unknown(String),
 }
 
 impl FromStr for PerformanceLevel {
@@ -728,11 +2029,12 @@ impl FromStr for PerformanceLevel {
             "high" | "Highest Clocks" => Ok(PerformanceLevel::High),
             "low" | "Lowest Clocks" => Ok(PerformanceLevel::Low),
             "manual" | "Manual" => Ok(PerformanceLevel::Manual),
-            _ => Err(ErrorKind::ParseError {
-                msg: "unrecognized GPU power profile".to_string(),
+            "" => Err(ErrorKind::ParseError {
+                msg: "empty GPU performance level".to_string(),
                 line: 1,
             }
             .into()),
+            other => Ok(PerformanceLevel::Unknown(other.to_owned())),
         }
     }
 }
@@ -747,16 +2049,358 @@ impl fmt::Display for PerformanceLevel {
                 PerformanceLevel::High => "high",
                 PerformanceLevel::Low => "low",
                 PerformanceLevel::Manual => "manual",
+                PerformanceLevel::Unknown(name) => name,
             }
         )
     }
 }
 
+/// Validated PMFW setting values with their files, and the padded fan curve with its file
+type PreparedPmfwSettings = (Vec<(PathBuf, u32)>, Option<(PathBuf, FanCurve)>);
+
+fn write_fan_curve(file_path: &Path, curve: &FanCurve) -> Result<()> {
+    for (i, point) in curve.points.iter().enumerate() {
+        sysfs::write(
+            file_path,
+            format!("{i} {} {}\n", point.temperature, point.speed),
+        )?;
+    }
+    Ok(())
+}
+
+/// Treats a missing fan control file as the setting not being available
+fn optional_fan_setting<T>(result: Result<T>) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if err.is_not_found() || matches!(err.kind, ErrorKind::Unsupported(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+const FAN_CTRL_REQUIREMENT: &str =
+    "PMFW fan control requires an RDNA 3 or newer GPU and Linux 6.7 or newer (6.13 for the zero RPM settings)";
+
+/// Reports a missing optional attribute as [`ErrorKind::Unsupported`], with an explanation of what it requires
+fn optional_attribute<T>(result: Result<T>, requirement: &str) -> Result<T> {
+    result.map_err(|err| {
+        if err.is_not_found() {
+            ErrorKind::Unsupported(requirement.to_owned()).into()
+        } else {
+            err
+        }
+    })
+}
+
+/// Strips the suffix from the value, ignoring ASCII case (e.g. `Mhz` and `MHz` both match `mhz`).
+fn strip_suffix_ignore_ascii_case<'a>(value: &'a str, suffix: &str) -> Option<&'a str> {
+    let split_at = value.len().checked_sub(suffix.len())?;
+    let (stripped, value_suffix) = (value.get(..split_at)?, value.get(split_at..)?);
+    value_suffix
+        .eq_ignore_ascii_case(suffix)
+        .then_some(stripped)
+}
+
+fn scan_hw_monitors(sysfs_path: &Path) -> Vec<HwMon> {
+    let mut hw_monitors = Vec::new();
+
+    if let Ok(hw_mons_iter) = fs::read_dir(sysfs_path.join("hwmon")) {
+        for hw_mon_dir in hw_mons_iter.flatten() {
+            if let Ok(hw_mon) = HwMon::new_from_path(hw_mon_dir.path()) {
+                hw_monitors.push(hw_mon);
+            }
+        }
+    }
+
+    hw_monitors
+}
+
+/// Resolves the path of the underlying device when given a path like `/sys/class/drm/card0` or `/sys/class/hwmon/hwmon0`.
+///
+/// The contents of the `uevent` file are returned as well when the given path is the device itself, so it's not read twice.
+fn resolve_device_path(path: PathBuf) -> (PathBuf, Option<String>) {
+    let uevent = fs::read_to_string(path.join("uevent")).ok();
+    let has_driver = uevent
+        .as_deref()
+        .is_some_and(|uevent| uevent.lines().any(|line| line.starts_with("DRIVER=")));
+
+    let device_path = path.join("device");
+    if !has_driver && device_path.join("uevent").exists() {
+        (device_path, None)
+    } else {
+        (path, uevent)
+    }
+}
+
+/// Checks if the name is a PCI address in the `domain:bus:device.function` format, e.g. `0000:03:00.0`.
+fn is_pci_slot_name(name: &str) -> bool {
+    let is_hex =
+        |part: &str, len: usize| part.len() == len && part.chars().all(|c| c.is_ascii_hexdigit());
+
+    let mut parts = name.split(':');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(domain), Some(bus), Some(device_function), None) => {
+            match device_function.split_once('.') {
+                Some((device, function)) => {
+                    is_hex(domain, 4) && is_hex(bus, 2) && is_hex(device, 2) && is_hex(function, 1)
+                }
+                None => false,
+            }
+        }
+        _ => false,
+    }
+}
+
 /// For some reason files sometimes have random null bytes around lines
 fn trim_sysfs_line(line: &str) -> &str {
     line.trim_matches(char::from(0)).trim()
 }
 
+/// Trims a line and collapses runs of whitespace (such as tabs, `\r` or repeated spaces) into single spaces.
+///
+/// Dumps collected on other systems or by some kernels are not formatted exactly like the usual SysFS output.
+pub(crate) fn normalize_whitespace(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Reads the lines of a SysFS file dump one at a time, with null bytes removed.
+///
+/// Iteration stops at the first I/O error, which is stored in `error`.
+pub(crate) fn read_sysfs_lines<'a, R: Read + 'a>(
+    reader: R,
+    error: &'a mut Option<io::Error>,
+) -> impl Iterator<Item = String> + 'a {
+    BufReader::new(reader)
+        .lines()
+        .map_while(move |line| match line {
+            Ok(mut line) => {
+                line.retain(|c| c != char::from(0));
+                Some(line)
+            }
+            Err(err) => {
+                *error = Some(err);
+                None
+            }
+        })
+}
+
+/// Interval between the checks in [`GpuHandle::wait_until_ready`].
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// An interface of the GPU which may not be available right away, see [`GpuHandle::wait_until_ready`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Interface {
+    /// Hardware monitor (`hwmon`)
+    HwMon,
+    /// Performance level (`power_dpm_force_performance_level`)
+    PerformanceLevel,
+    /// Clocks and voltage table (`pp_od_clk_voltage`)
+    Overdrive,
+    /// Power profile modes (`pp_power_profile_mode`)
+    PowerProfileModes,
+    /// PMFW fan control (`gpu_od/fan_ctrl`)
+    FanControl,
+    /// Binary metrics table (`gpu_metrics`)
+    GpuMetrics,
+}
+
+impl Interface {
+    /// Checks if the interface is currently available on the GPU.
+    pub fn is_available(&self, gpu_handle: &GpuHandle) -> bool {
+        let path = match self {
+            Interface::HwMon => return gpu_handle.hw_monitors.iter().any(HwMon::exists),
+            Interface::PerformanceLevel => "power_dpm_force_performance_level",
+            Interface::Overdrive => "pp_od_clk_voltage",
+            Interface::PowerProfileModes => "pp_power_profile_mode",
+            Interface::FanControl => "gpu_od/fan_ctrl",
+            Interface::GpuMetrics => "gpu_metrics",
+        };
+        gpu_handle.get_path().join(path).exists()
+    }
+}
+
+/// A step of [`GpuHandle::restore_defaults`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RestoreStep {
+    /// Enabling all power levels
+    PowerLevels,
+    /// Resetting the clocks table
+    ClocksTable,
+    /// Resetting the PMFW fan settings
+    FanSettings,
+    /// Setting the power cap to the default value
+    PowerCap,
+    /// Setting the performance level to auto
+    PerformanceLevel,
+}
+
+/// Results of [`GpuHandle::restore_defaults`].
+#[derive(Debug)]
+pub struct RestoreReport {
+    /// The result of every step that was attempted, in order
+    pub results: Vec<(RestoreStep, Result<()>)>,
+}
+
+impl RestoreReport {
+    /// Checks if all of the steps succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.results.iter().all(|(_, result)| result.is_ok())
+    }
+
+    /// Gets the steps which failed along with their errors.
+    pub fn errors(&self) -> impl Iterator<Item = (RestoreStep, &Error)> {
+        self.results
+            .iter()
+            .filter_map(|(step, result)| result.as_ref().err().map(|err| (*step, err)))
+    }
+}
+
+/// Attributes of a GPU which do not change at runtime, see [`GpuHandle::get_static_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct StaticInfo {
+    /// Driver, PCI ids and VBIOS version
+    pub identity: GpuIdentity,
+    /// Total VRAM size in bytes
+    pub total_vram: Option<u64>,
+    /// Maximum PCIe link speed
+    pub max_link_speed: Option<String>,
+    /// Maximum PCIe link width
+    pub max_link_width: Option<String>,
+    /// Version of the graphics IP block, which identifies the ASIC family
+    pub gc_version: Option<(u32, u32, u32)>,
+}
+
+/// Current and maximum PCIe link of a GPU, see [`GpuHandle::get_link_status`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct LinkStatus {
+    /// Current link speed, e.g. `8.0 GT/s PCIe`
+    pub current_speed: String,
+    /// Current link width, e.g. `16`
+    pub current_width: String,
+    /// Maximum link speed
+    pub max_speed: String,
+    /// Maximum link width
+    pub max_width: String,
+}
+
+impl LinkStatus {
+    /// Checks if the link is running below its maximum speed or width.
+    ///
+    /// Note that some GPUs lower the link speed when idle to save power,
+    /// so a degraded link is only a problem if it stays that way under load.
+    /// Values which cannot be parsed (such as `Unknown`) are not considered degraded.
+    pub fn is_degraded(&self) -> bool {
+        let is_lower = |current: Option<f64>, max: Option<f64>| match (current, max) {
+            (Some(current), Some(max)) => current < max,
+            _ => false,
+        };
+
+        is_lower(
+            parse_link_value(&self.current_speed),
+            parse_link_value(&self.max_speed),
+        ) || is_lower(
+            parse_link_value(&self.current_width),
+            parse_link_value(&self.max_width),
+        )
+    }
+}
+
+impl Display for LinkStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} x{}", self.current_speed, self.current_width)?;
+        if self.is_degraded() {
+            write!(f, " (max {} x{})", self.max_speed, self.max_width)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the numeric part of a link speed (`8.0 GT/s PCIe`) or width (`16`).
+fn parse_link_value(value: &str) -> Option<f64> {
+    value.split_whitespace().next()?.parse().ok()
+}
+
+/// Identifying information of a GPU, see [`GpuHandle::get_identity`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct GpuIdentity {
+    /// Kernel driver
+    pub driver: String,
+    /// PCI vendor and device ID of the GPU chip, in the `1002:73BF` format
+    pub pci_id: Option<String>,
+    /// PCI subsystem vendor and device ID of the card model
+    pub pci_subsys_id: Option<String>,
+    /// PCI slot name, e.g. `0000:0c:00.0`
+    pub pci_slot_name: Option<String>,
+    /// Unique serial ID of the GPU. Only reported on some generations.
+    pub unique_id: Option<String>,
+    /// VBIOS version
+    pub vbios_version: Option<String>,
+}
+
+impl GpuIdentity {
+    /// Checks if the identity refers to the same GPU.
+    ///
+    /// The unique ID is compared when both identities have it, otherwise the PCI IDs and slot have to match.
+    /// The VBIOS version is ignored, as it changes when the card is flashed.
+    pub fn matches(&self, other: &GpuIdentity) -> bool {
+        match (&self.unique_id, &other.unique_id) {
+            (Some(unique_id), Some(other_unique_id)) => unique_id == other_unique_id,
+            _ => {
+                self.pci_id == other.pci_id
+                    && self.pci_subsys_id == other.pci_subsys_id
+                    && self.pci_slot_name == other.pci_slot_name
+            }
+        }
+    }
+}
+
+/// A PCI function of the same device as the GPU, see [`GpuHandle::get_audio_function`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PciFunction {
+    /// SysFS path of the function
+    pub path: PathBuf,
+    /// PCI slot name, e.g. `0000:0c:00.1`
+    pub slot_name: String,
+    /// PCI vendor ID, in the same format as in [`GpuHandle::get_pci_id`]
+    pub vendor_id: String,
+    /// PCI device ID
+    pub device_id: String,
+}
+
+/// GPU activity along with where it was read from, see [`GpuHandle::get_activity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Activity {
+    /// Activity in percent
+    pub percent: u8,
+    /// Source of the value
+    pub source: ActivitySource,
+}
+
+/// Source of the GPU activity value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ActivitySource {
+    /// The `gpu_busy_percent` file
+    BusyPercent,
+    /// The graphics activity field of the `gpu_metrics` table
+    GpuMetrics,
+}
+
 /// Handle for committing values which were previusly written
 #[must_use]
 #[derive(Debug)]
@@ -771,7 +2415,7 @@ impl CommitHandle {
 
     /// Commit the previously written values
     pub fn commit(self) -> Result<()> {
-        std::fs::write(&self.file_path, "c\n").with_context(|| {
+        sysfs::write(&self.file_path, "c\n").with_context(|| {
             format!(
                 "Could not commit values to {:?}",
                 self.file_path.file_name().unwrap()
@@ -779,3 +2423,63 @@ impl CommitHandle {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{is_pci_slot_name, strip_suffix_ignore_ascii_case, LinkStatus, PerformanceLevel};
+    use std::str::FromStr;
+
+    #[test]
+    fn pci_slot_name() {
+        assert!(is_pci_slot_name("0000:03:00.0"));
+        assert!(is_pci_slot_name("0000:c1:00.1"));
+        assert!(!is_pci_slot_name("card0"));
+        assert!(!is_pci_slot_name("0000:03:00"));
+        assert!(!is_pci_slot_name("0000:03:00.0:1"));
+        assert!(!is_pci_slot_name("tmp.AbCdEf"));
+    }
+
+    #[test]
+    fn link_degradation() {
+        let link = |current_speed: &str, current_width: &str| LinkStatus {
+            current_speed: current_speed.to_owned(),
+            current_width: current_width.to_owned(),
+            max_speed: "16.0 GT/s PCIe".to_owned(),
+            max_width: "16".to_owned(),
+        };
+
+        assert!(!link("16.0 GT/s PCIe", "16").is_degraded());
+        assert!(link("2.5 GT/s PCIe", "16").is_degraded());
+        assert!(link("16.0 GT/s PCIe", "8").is_degraded());
+        assert!(!link("Unknown", "16").is_degraded());
+        assert_eq!(
+            "16.0 GT/s PCIe x16",
+            link("16.0 GT/s PCIe", "16").to_string()
+        );
+        assert_eq!(
+            "2.5 GT/s PCIe x8 (max 16.0 GT/s PCIe x16)",
+            link("2.5 GT/s PCIe", "8").to_string()
+        );
+    }
+
+    #[test]
+    fn strip_suffix_ignoring_case() {
+        assert_eq!(Some("800"), strip_suffix_ignore_ascii_case("800Mhz", "mhz"));
+        assert_eq!(Some("800"), strip_suffix_ignore_ascii_case("800MHz", "mhz"));
+        assert_eq!(None, strip_suffix_ignore_ascii_case("800", "mhz"));
+        assert_eq!(None, strip_suffix_ignore_ascii_case("hz", "mhz"));
+        assert_eq!(None, strip_suffix_ignore_ascii_case("80°", "z"));
+    }
+
+    #[test]
+    fn unknown_performance_level() {
+        let level = PerformanceLevel::from_str("profile_peak").unwrap();
+        assert_eq!(PerformanceLevel::Unknown("profile_peak".to_owned()), level);
+        assert_eq!("profile_peak", level.to_string());
+        assert_eq!(
+            PerformanceLevel::Manual,
+            PerformanceLevel::from_str("manual").unwrap()
+        );
+        assert!(PerformanceLevel::from_str("").is_err());
+    }
+}
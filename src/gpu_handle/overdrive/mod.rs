@@ -1,19 +1,25 @@
 //! GPU overdrive (overclocking)
 //!
 //! <https://kernel.org/doc/html/latest/gpu/amdgpu/thermal.html#pp-od-clk-voltage>
+mod normalized;
 pub mod vega10;
 pub mod vega20;
 
+pub use normalized::{NormalizedRanges, NormalizedTable};
+
 use crate::{
-    error::{Error, ErrorKind},
+    error::{Error, ErrorContext, ErrorKind},
+    gpu_handle::{read_sysfs_lines, trim_sysfs_line},
     Result,
 };
 use enum_dispatch::enum_dispatch;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::{
+    cmp,
     convert::TryFrom,
-    io::Write,
+    io::{Read, Write},
+    ops::{Add, Sub},
     str::{FromStr, SplitWhitespace},
 };
 
@@ -97,6 +103,7 @@ pub trait ClocksTable: FromStr {
 
     /// Sets the maximum memory clock.
     fn set_max_mclk(&mut self, clockspeed: i32) -> Result<()> {
+        check_mclk_supported(self)?;
         let range = self.get_max_mclk_range();
         check_clockspeed_in_range(range, clockspeed)?;
         self.set_max_mclk_unchecked(clockspeed)
@@ -107,6 +114,7 @@ pub trait ClocksTable: FromStr {
 
     /// Sets the minimum memory clock.
     fn set_min_mclk(&mut self, clockspeed: i32) -> Result<()> {
+        check_mclk_supported(self)?;
         let range = self.get_min_mclk_range();
         check_clockspeed_in_range(range, clockspeed)?;
         self.set_min_mclk_unchecked(clockspeed)
@@ -137,11 +145,61 @@ pub trait ClocksTable: FromStr {
 
     /// Gets the current maximum voltage (used on maximum clockspeed).
     fn get_max_sclk_voltage(&self) -> Option<i32>;
+
+    /// Applies a relative voltage change (in mV) uniformly, clamping the values to the allowed ranges.
+    /// A negative delta means undervolting.
+    ///
+    /// On Vega10 and older this adjusts all clock levels, while on newer GPUs
+    /// it adjusts all VDDC curve points, or the voltage offset if there is no curve.
+    /// The default implementation returns an error, as there is no generic way to adjust the voltages.
+    fn apply_voltage_delta(&mut self, delta: i32) -> Result<()> {
+        Err(ErrorKind::Unsupported(format!(
+            "Applying a voltage delta of {delta} mV is not supported by this table format"
+        ))
+        .into())
+    }
+
+    /// Clamps all of the current values into the allowed OD ranges reported by the GPU.
+    /// This is useful for sanitizing a table before writing it, as some GPUs have default values outside of the allowed range.
+    ///
+    /// The default implementation only clamps the current clockspeed ranges.
+    fn normalize(&mut self) {
+        let sclk = self.get_current_sclk_range();
+        if let (Some(min), Some(range)) = (sclk.min, self.get_min_sclk_range()) {
+            let _ = self.set_min_sclk_unchecked(range.clamp(min));
+        }
+        if let (Some(max), Some(range)) = (sclk.max, self.get_max_sclk_range()) {
+            let _ = self.set_max_sclk_unchecked(range.clamp(max));
+        }
+
+        let mclk = self.get_current_mclk_range();
+        if let (Some(min), Some(range)) = (mclk.min, self.get_min_mclk_range()) {
+            let _ = self.set_min_mclk_unchecked(range.clamp(min));
+        }
+        if let (Some(max), Some(range)) = (mclk.max, self.get_max_mclk_range()) {
+            let _ = self.set_max_mclk_unchecked(range.clamp(max));
+        }
+    }
+}
+
+/// Integrated GPUs do not report any memory clock information, as they use system memory.
+fn check_mclk_supported<T: ClocksTable>(table: &T) -> Result<()> {
+    if table.get_max_mclk_range().is_none()
+        && table.get_min_mclk_range().is_none()
+        && table.get_current_mclk_range() == Range::empty()
+    {
+        Err(ErrorKind::Unsupported(
+            "Memory overclocking is not available on this device".to_owned(),
+        )
+        .into())
+    } else {
+        Ok(())
+    }
 }
 
 fn check_clockspeed_in_range(range: Option<Range>, clockspeed: i32) -> Result<()> {
-    if let (Some(min), Some(max)) = range.map_or((None, None), |range| (range.min, range.max)) {
-        if (min..=max).contains(&clockspeed) {
+    if let Some((min, max)) = range.and_then(Range::into_full) {
+        if Range::full(min, max).contains(clockspeed) {
             Ok(())
         } else {
             Err(Error::not_allowed(format!(
@@ -155,17 +213,36 @@ fn check_clockspeed_in_range(range: Option<Range>, clockspeed: i32) -> Result<()
     }
 }
 
+/// Checks a value against the bounds of a range, ignoring bounds which are not present.
+pub(crate) fn check_value_in_range(range: Range, value: i32, name: &str) -> Result<()> {
+    if range.contains(value) {
+        Ok(())
+    } else {
+        let min = range
+            .min
+            .map_or_else(|| "-".to_owned(), |min| min.to_string());
+        let max = range
+            .max
+            .map_or_else(|| "-".to_owned(), |max| max.to_string());
+        Err(Error::not_allowed(format!(
+            "Given {name} {value} is out of the allowed OD range {min} to {max}"
+        )))
+    }
+}
+
 /// Representation of clocks and voltage table (`pp_od_clk_voltage`).
 ///
 /// NOTE: despite the names, the tables here are not exclusive to Vega10 and 20!
 /// Vega10 covers everything Vega10 and older (including Polaris), while Vega20 includes all newer gpus as well (like Navi)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[cfg_attr(
     feature = "serde",
     serde(tag = "kind", content = "data", rename_all = "snake_case")
 )]
 #[enum_dispatch(ClocksTable)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum ClocksTableGen {
     /// Vega10 (and older) format
     Vega10(vega10::Table),
@@ -177,17 +254,67 @@ impl FromStr for ClocksTableGen {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        if s.contains("VDDC_CURVE") || s.contains("OD_VDDGFX_OFFSET") || {
-            let mut lines = s.lines();
-            lines.next() == Some("OD_SCLK:")
-                && lines.next().is_some_and(|sclk_line| {
-                    let sclk_line = sclk_line.to_ascii_lowercase();
-                    sclk_line.contains("mhz") && !sclk_line.contains("mv")
-                })
-        } {
-            vega20::Table::from_str(s).map(Self::Vega20)
+        Self::parse_lines(s.lines())
+    }
+}
+
+impl ClocksTableGen {
+    /// Parses the table from a reader, such as a file with a captured `pp_od_clk_voltage` dump.
+    ///
+    /// The input is read line by line without buffering all of it first.
+    pub fn parse_from_reader<R: Read>(reader: R) -> Result<Self> {
+        let mut error = None;
+        let table = Self::parse_lines(read_sysfs_lines(reader, &mut error));
+        error
+            .map_or(Ok(()), Err)
+            .context("Could not read clocks table")?;
+        table
+    }
+
+    fn parse_lines<I, S>(lines: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut lines = lines.into_iter();
+        // Lines read while detecting the format, which still have to be parsed
+        let mut detection_lines = Vec::new();
+        let mut previous_line_is_sclk = false;
+
+        let is_vega20 = loop {
+            let Some(line) = lines.next() else {
+                break false;
+            };
+            let trimmed = trim_sysfs_line(line.as_ref());
+
+            let detected = if trimmed.contains("VDDC_CURVE")
+                || trimmed.contains("OD_VDDGFX_OFFSET")
+                || trimmed.contains("OD_SCLK_OFFSET")
+            {
+                Some(true)
+            } else if previous_line_is_sclk && !trimmed.is_empty() {
+                // Vega10 sclk levels also include the voltage
+                let sclk_line = trimmed.to_ascii_lowercase();
+                sclk_line.contains("mhz").then(|| !sclk_line.contains("mv"))
+            } else {
+                None
+            };
+
+            if !trimmed.is_empty() {
+                previous_line_is_sclk = trimmed == "OD_SCLK:";
+            }
+            detection_lines.push(line);
+
+            if let Some(is_vega20) = detected {
+                break is_vega20;
+            }
+        };
+
+        let lines = detection_lines.into_iter().chain(lines);
+        if is_vega20 {
+            vega20::Table::parse_lines(lines).map(Self::Vega20)
         } else {
-            vega10::Table::from_str(s).map(Self::Vega10)
+            vega10::Table::parse_lines(lines).map(Self::Vega10)
         }
     }
 }
@@ -238,8 +365,10 @@ where
 }
 
 /// A range.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Range {
     /// The lower value of a range.
     pub min: Option<i32>,
@@ -284,6 +413,70 @@ impl Range {
     pub fn into_full(self) -> Option<(i32, i32)> {
         self.min.zip(self.max)
     }
+
+    /// Checks if the range has neither a minimum nor a maximum value.
+    pub fn is_empty(&self) -> bool {
+        self.min.is_none() && self.max.is_none()
+    }
+
+    /// Checks if a value is within the range. Missing bounds are treated as unlimited.
+    pub fn contains(&self, value: i32) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+
+    /// Clamps a value into the range. Missing bounds are treated as unlimited.
+    pub fn clamp(&self, mut value: i32) -> i32 {
+        if let Some(min) = self.min {
+            value = cmp::max(min, value);
+        }
+        if let Some(max) = self.max {
+            value = cmp::min(max, value);
+        }
+        value
+    }
+
+    /// Gets the range of values allowed by both ranges.
+    /// The result may have a minimum which is higher than the maximum if the ranges do not overlap.
+    pub fn intersect(self, other: Range) -> Self {
+        let min = match (self.min, other.min) {
+            (Some(a), Some(b)) => Some(cmp::max(a, b)),
+            (a, b) => a.or(b),
+        };
+        let max = match (self.max, other.max) {
+            (Some(a), Some(b)) => Some(cmp::min(a, b)),
+            (a, b) => a.or(b),
+        };
+        Self { min, max }
+    }
+
+    /// Gets the distance between the minimum and maximum values, if both are present.
+    pub fn width(&self) -> Option<i32> {
+        self.into_full().map(|(min, max)| max - min)
+    }
+}
+
+/// Shifts both bounds of the range by the given value.
+impl Add<i32> for Range {
+    type Output = Range;
+
+    fn add(self, rhs: i32) -> Self::Output {
+        Self {
+            min: self.min.map(|min| min.saturating_add(rhs)),
+            max: self.max.map(|max| max.saturating_add(rhs)),
+        }
+    }
+}
+
+/// Shifts both bounds of the range by the given value.
+impl Sub<i32> for Range {
+    type Output = Range;
+
+    fn sub(self, rhs: i32) -> Self::Output {
+        Self {
+            min: self.min.map(|min| min.saturating_sub(rhs)),
+            max: self.max.map(|max| max.saturating_sub(rhs)),
+        }
+    }
 }
 
 impl TryFrom<Range> for (i32, i32) {
@@ -299,8 +492,10 @@ impl TryFrom<Range> for (i32, i32) {
 }
 
 /// Represents a combination of a clockspeed and voltage. May be used in different context based on the table format.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct ClocksLevel {
     /// Clockspeed (in MHz)
     pub clockspeed: i32,
@@ -356,10 +551,15 @@ mod tests {
 
     use insta::assert_yaml_snapshot;
 
-    use crate::gpu_handle::overdrive::ClocksTableGen;
+    use crate::{
+        error::ErrorKind,
+        gpu_handle::overdrive::{ClocksTable, ClocksTableGen},
+    };
 
     use super::{check_clockspeed_in_range, parse_level_line, parse_range_line, Range};
+    use crate::tests::whitespace_variants;
 
+    /// Includes the `pp_od_clk_voltage` test data of a given GPU.
     #[macro_export]
     macro_rules! include_table {
         ($name:literal) => {
@@ -370,6 +570,51 @@ mod tests {
     pub const TABLE_PHOENIX: &str = include_table!("internal-7840u");
     pub const TABLE_VEGA56: &str = include_table!("vega56");
 
+    #[test]
+    fn parse_irregular_whitespace() {
+        for data in [
+            TABLE_PHOENIX,
+            TABLE_VEGA56,
+            include_table!("rx580"),
+            include_table!("rx6900xt"),
+            include_table!("rx7900xt"),
+        ] {
+            let expected = ClocksTableGen::from_str(data).unwrap();
+            for variant in whitespace_variants(data) {
+                assert_eq!(
+                    expected,
+                    ClocksTableGen::from_str(&variant).unwrap(),
+                    "{variant:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parse_from_reader() {
+        for data in [
+            TABLE_PHOENIX,
+            TABLE_VEGA56,
+            include_table!("rx580"),
+            include_table!("rx7900xt"),
+            include_table!("rx9070xt"),
+        ] {
+            let table = ClocksTableGen::parse_from_reader(data.as_bytes()).unwrap();
+            assert_eq!(ClocksTableGen::from_str(data).unwrap(), table);
+        }
+
+        let padded = format!("{TABLE_VEGA56}\0\0");
+        let table = ClocksTableGen::parse_from_reader(padded.as_bytes()).unwrap();
+        assert_eq!(ClocksTableGen::from_str(TABLE_VEGA56).unwrap(), table);
+    }
+
+    #[test]
+    fn parse_from_reader_io_error() {
+        let data = [TABLE_VEGA56.as_bytes(), &[0xff, b'\n']].concat();
+        let err = ClocksTableGen::parse_from_reader(data.as_slice()).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::IoError(_)), "{err:?}");
+    }
+
     #[test]
     fn parse_range_line_sclk() {
         let line = "SCLK:     300MHz       2000MHz";
@@ -398,6 +643,35 @@ mod tests {
         check_clockspeed_in_range(range, 250).unwrap_err();
     }
 
+    #[test]
+    fn range_helpers() {
+        let range = Range::full(300, 1000);
+        assert!(range.contains(300));
+        assert!(range.contains(1000));
+        assert!(!range.contains(1001));
+        assert!(Range::min(300).contains(5000));
+        assert!(Range::empty().contains(-5000));
+        assert!(Range::empty().is_empty());
+        assert!(!Range::max(0).is_empty());
+
+        assert_eq!(range.clamp(200), 300);
+        assert_eq!(range.clamp(500), 500);
+        assert_eq!(range.clamp(1500), 1000);
+        assert_eq!(Range::max(0).clamp(-50), -50);
+
+        assert_eq!(
+            range.intersect(Range::full(500, 2000)),
+            Range::full(500, 1000)
+        );
+        assert_eq!(range.intersect(Range::max(800)), Range::full(300, 800));
+        assert_eq!(Range::empty().intersect(Range::min(10)), Range::min(10));
+
+        assert_eq!(range.width(), Some(700));
+        assert_eq!(Range::min(10).width(), None);
+        assert_eq!(range + 50, Range::full(350, 1050));
+        assert_eq!(Range::max(0) - 50, Range::max(-50));
+    }
+
     #[test]
     fn parse_range_line_voltage_point() {
         let line = "VDDC_CURVE_SCLK[2]:     800Mhz       2150Mhz";
@@ -406,6 +680,15 @@ mod tests {
         assert_eq!(name, "VDDC_CURVE_SCLK[2]");
     }
 
+    #[test]
+    fn set_mclk_igpu_unsupported() {
+        let mut table = ClocksTableGen::from_str(TABLE_PHOENIX).unwrap();
+        let err = table.set_max_mclk(1000).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Unsupported(String::new()));
+        let err = table.set_min_mclk(500).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Unsupported(String::new()));
+    }
+
     #[test]
     fn detect_type_phoenix() {
         let table = ClocksTableGen::from_str(TABLE_PHOENIX).unwrap();
@@ -0,0 +1,148 @@
+use super::{ClocksLevel, ClocksTable, ClocksTableGen, Range};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A format-independent view of a clocks table.
+///
+/// This allows reading the commonly used values without matching on the [`ClocksTableGen`] format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct NormalizedTable {
+    /// The current core clock range (in MHz).
+    pub sclk: Range,
+    /// The current memory clock range (in MHz). Empty on iGPUs.
+    pub mclk: Range,
+    /// The clockspeed and voltage points.
+    /// This is the VDDC curve on Vega20 and newer, and the list of core clock levels on Vega10 and older.
+    /// `None` if the GPU does not report any voltage points.
+    pub curve: Option<Vec<ClocksLevel>>,
+    /// The voltage offset (in mV). Only present on GPUs which use offset-based voltage control.
+    pub voltage_offset: Option<i32>,
+    /// The allowed ranges for the values.
+    pub ranges: NormalizedRanges,
+}
+
+/// Allowed ranges of a [`NormalizedTable`]. Each range is `None` if the GPU does not report it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct NormalizedRanges {
+    /// Allowed range for the minimum core clock.
+    pub min_sclk: Option<Range>,
+    /// Allowed range for the maximum core clock.
+    pub max_sclk: Option<Range>,
+    /// Allowed range for the minimum memory clock.
+    pub min_mclk: Option<Range>,
+    /// Allowed range for the maximum memory clock.
+    pub max_mclk: Option<Range>,
+    /// Allowed range for the voltage at the lowest power level.
+    pub min_voltage: Option<Range>,
+    /// Allowed range for the voltage at the highest power level.
+    pub max_voltage: Option<Range>,
+    /// Allowed range for the voltage offset.
+    pub voltage_offset: Option<Range>,
+}
+
+impl From<&ClocksTableGen> for NormalizedTable {
+    fn from(table: &ClocksTableGen) -> Self {
+        let (curve, voltage_offset, voltage_offset_range) = match table {
+            ClocksTableGen::Vega10(table) => (table.sclk_levels.clone(), None, None),
+            ClocksTableGen::Vega20(table) => (
+                table.vddc_curve.clone(),
+                table.voltage_offset,
+                table.od_range.voltage_offset,
+            ),
+        };
+
+        Self {
+            sclk: table.get_current_sclk_range(),
+            mclk: table.get_current_mclk_range(),
+            curve: Some(curve).filter(|curve| !curve.is_empty()),
+            voltage_offset,
+            ranges: NormalizedRanges {
+                min_sclk: table.get_min_sclk_range(),
+                max_sclk: table.get_max_sclk_range(),
+                min_mclk: table.get_min_mclk_range(),
+                max_mclk: table.get_max_mclk_range(),
+                min_voltage: table.get_min_voltage_range(),
+                max_voltage: table.get_max_voltage_range(),
+                voltage_offset: voltage_offset_range,
+            },
+        }
+    }
+}
+
+impl ClocksTableGen {
+    /// Gets a format-independent view of the table.
+    pub fn normalized(&self) -> NormalizedTable {
+        NormalizedTable::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NormalizedRanges;
+    use crate::{
+        gpu_handle::overdrive::{
+            tests::{TABLE_PHOENIX, TABLE_VEGA56},
+            ClocksLevel, ClocksTableGen, Range,
+        },
+        include_table,
+    };
+    use pretty_assertions::assert_eq;
+    use std::str::FromStr;
+
+    #[test]
+    fn normalize_vega10() {
+        let table = ClocksTableGen::from_str(include_table!("rx580")).unwrap();
+        let normalized = table.normalized();
+
+        assert_eq!(normalized.sclk, Range::full(300, 1366));
+        assert_eq!(normalized.mclk, Range::full(300, 1750));
+        assert_eq!(
+            normalized.curve.unwrap().first(),
+            Some(&ClocksLevel::new(300, 750))
+        );
+        assert_eq!(normalized.voltage_offset, None);
+        assert_eq!(normalized.ranges.max_mclk, Some(Range::full(300, 2250)));
+        assert_eq!(normalized.ranges.max_voltage, Some(Range::full(750, 1200)));
+    }
+
+    #[test]
+    fn normalize_vega20_offset() {
+        let table = ClocksTableGen::from_str(include_table!("rx7800xt")).unwrap();
+        let normalized = table.normalized();
+
+        assert_eq!(normalized.sclk, Range::full(500, 2660));
+        assert_eq!(normalized.mclk, Range::full(97, 1219));
+        assert_eq!(normalized.curve, None);
+        assert_eq!(normalized.voltage_offset, Some(0));
+        assert_eq!(normalized.ranges.voltage_offset, Some(Range::full(-450, 0)));
+    }
+
+    #[test]
+    fn normalize_igpu() {
+        let table = ClocksTableGen::from_str(TABLE_PHOENIX).unwrap();
+        let normalized = table.normalized();
+
+        assert_eq!(normalized.mclk, Range::empty());
+        assert_eq!(
+            normalized.ranges,
+            NormalizedRanges {
+                min_sclk: Some(Range::full(800, 2700)),
+                max_sclk: Some(Range::full(800, 2700)),
+                min_mclk: None,
+                max_mclk: None,
+                min_voltage: None,
+                max_voltage: None,
+                voltage_offset: None,
+            }
+        );
+        assert!(ClocksTableGen::from_str(TABLE_VEGA56)
+            .unwrap()
+            .normalized()
+            .curve
+            .is_some());
+    }
+}
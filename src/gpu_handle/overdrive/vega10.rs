@@ -1,5 +1,8 @@
 //! The format used by Vega10 and older GPUs.
-use super::{parse_range_line, push_level_line, ClocksLevel, ClocksTable, ClocksTableGen, Range};
+use super::{
+    check_value_in_range, parse_range_line, push_level_line, ClocksLevel, ClocksTable,
+    ClocksTableGen, Range,
+};
 use crate::{
     error::{Error, ErrorKind::ParseError},
     Result,
@@ -9,8 +12,10 @@ use serde::{Deserialize, Serialize};
 use std::{cmp, io::Write, str::FromStr};
 
 /// Vega10 clocks table.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Table {
     /// List of core clock levels.
     pub sclk_levels: Vec<ClocksLevel>,
@@ -40,11 +45,11 @@ impl ClocksTable for Table {
     }
 
     fn get_max_sclk_range(&self) -> Option<Range> {
-        Some(self.od_range.sclk)
+        Some(self.od_range.sclk).filter(|range| !range.is_empty())
     }
 
     fn get_min_sclk_range(&self) -> Option<Range> {
-        Some(self.od_range.sclk)
+        Some(self.od_range.sclk).filter(|range| !range.is_empty())
     }
 
     fn get_max_mclk_range(&self) -> Option<Range> {
@@ -168,13 +173,38 @@ impl ClocksTable for Table {
     fn get_max_sclk_voltage(&self) -> Option<i32> {
         self.sclk_levels.last().map(|level| level.voltage)
     }
+
+    fn normalize(&mut self) {
+        let voltage_range = self.od_range.vddc.unwrap_or_else(Range::empty);
+
+        for (levels, clocks_range) in [
+            (&mut self.sclk_levels, Some(self.od_range.sclk)),
+            (&mut self.mclk_levels, self.od_range.mclk),
+        ] {
+            let clocks_range = clocks_range.unwrap_or_else(Range::empty);
+            for level in levels {
+                level.clockspeed = clocks_range.clamp(level.clockspeed);
+                level.voltage = voltage_range.clamp(level.voltage);
+            }
+        }
+    }
+
+    fn apply_voltage_delta(&mut self, delta: i32) -> Result<()> {
+        let range = self.od_range.vddc.unwrap_or_else(Range::empty);
+        for level in self.sclk_levels.iter_mut().chain(&mut self.mclk_levels) {
+            level.voltage = range.clamp(level.voltage.saturating_add(delta));
+        }
+        Ok(())
+    }
 }
 
 /// The ranges for overclocking values which the GPU allows to be used.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct OdRange {
-    /// Clocks range for sclk (in MHz). Should be present on all GPUs.
+    /// Clocks range for sclk (in MHz). Empty if the GPU does not report an `OD_RANGE` section.
     pub sclk: Range,
     /// Clocks range for mclk (in MHz). Present on discrete GPUs only.
     pub mclk: Option<Range>,
@@ -182,10 +212,66 @@ pub struct OdRange {
     pub vddc: Option<Range>,
 }
 
+impl OdRange {
+    /// Creates a builder for constructing the ranges from scratch.
+    pub fn builder() -> OdRangeBuilder {
+        OdRangeBuilder::new()
+    }
+}
+
+/// Builder for constructing an [`OdRange`], e.g. for validating levels with [`TableBuilder::od_range`].
+///
+/// All ranges are empty by default, meaning that the values are not limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OdRangeBuilder {
+    od_range: OdRange,
+}
+
+impl OdRangeBuilder {
+    /// Creates a new builder with all ranges empty.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the core clock range.
+    pub fn sclk(mut self, range: Range) -> Self {
+        self.od_range.sclk = range;
+        self
+    }
+
+    /// Sets the memory clock range.
+    pub fn mclk(mut self, range: Range) -> Self {
+        self.od_range.mclk = Some(range);
+        self
+    }
+
+    /// Sets the voltage range.
+    pub fn vddc(mut self, range: Range) -> Self {
+        self.od_range.vddc = Some(range);
+        self
+    }
+
+    /// Builds the ranges.
+    pub fn build(self) -> OdRange {
+        self.od_range
+    }
+}
+
 impl FromStr for Table {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self> {
+        Self::parse_lines(s.lines())
+    }
+}
+
+impl Table {
+    /// Parses the table line by line.
+    pub(super) fn parse_lines<I, S>(lines: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
         let mut sclk_levels = Vec::with_capacity(7);
         let mut mclk_levels = Vec::with_capacity(2);
         let mut sclk_range = None;
@@ -194,9 +280,11 @@ impl FromStr for Table {
 
         let mut current_section = None;
 
-        let mut i = 1;
-        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
-            match line {
+        let lines = lines
+            .into_iter()
+            .filter(|line| !line.as_ref().trim().is_empty());
+        for (i, line) in (1..).zip(lines) {
+            match line.as_ref().trim() {
                 "OD_SCLK:" => current_section = Some(Section::Sclk),
                 "OD_MCLK:" => current_section = Some(Section::Mclk),
                 "OD_RANGE:" => current_section = Some(Section::Range),
@@ -231,17 +319,13 @@ impl FromStr for Table {
                     }
                 },
             }
-            i += 1;
         }
 
         sclk_levels.shrink_to_fit();
         mclk_levels.shrink_to_fit();
 
         let od_range = OdRange {
-            sclk: sclk_range.ok_or_else(|| ParseError {
-                msg: "No sclk range found".to_owned(),
-                line: i,
-            })?,
+            sclk: sclk_range.unwrap_or_else(Range::empty),
             mclk: mclk_range,
             vddc: vddc_range,
         };
@@ -254,6 +338,123 @@ impl FromStr for Table {
     }
 }
 
+impl Table {
+    /// Creates a builder for constructing a table from scratch.
+    pub fn builder() -> TableBuilder {
+        TableBuilder::new()
+    }
+
+    /// Gets the current voltage used at the highest memory clock level.
+    pub fn get_max_mclk_voltage(&self) -> Option<i32> {
+        self.mclk_levels.last().map(|level| level.voltage)
+    }
+
+    fn check_voltage_in_range(&self, voltage: i32) -> Result<()> {
+        let range = self.od_range.vddc.ok_or_else(|| {
+            Error::not_allowed("GPU does not report allowed OD ranges".to_owned())
+        })?;
+        check_value_in_range(range, voltage, "voltage")
+    }
+
+    /// Sets the voltage of a given memory clock level.
+    pub fn set_mclk_level_voltage(&mut self, index: usize, voltage: i32) -> Result<()> {
+        self.check_voltage_in_range(voltage)?;
+
+        let level = self.mclk_levels.get_mut(index).ok_or_else(|| {
+            Error::not_allowed(format!("The GPU did not report memory clock level {index}"))
+        })?;
+        level.voltage = voltage;
+
+        Ok(())
+    }
+
+    /// Sets the voltage to be used at the highest memory clock level.
+    /// Lower levels are adjusted to not exceed the new voltage.
+    pub fn set_max_mclk_voltage(&mut self, voltage: i32) -> Result<()> {
+        self.check_voltage_in_range(voltage)?;
+
+        let len = self.mclk_levels.len();
+        if len == 0 {
+            return Ok(());
+        }
+
+        self.mclk_levels[len - 1].voltage = voltage;
+        for clock_level in &mut self.mclk_levels[0..len - 1] {
+            clock_level.voltage = cmp::min(clock_level.voltage, voltage);
+        }
+
+        Ok(())
+    }
+}
+
+/// Builder for constructing a [`Table`] from scratch, e.g. when applying a saved profile.
+///
+/// The table has no levels by default.
+/// The levels are checked against the allowed OD ranges (if provided) when calling [`TableBuilder::build`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TableBuilder {
+    table: Table,
+}
+
+impl TableBuilder {
+    /// Creates a new builder with no levels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a core clock level.
+    pub fn sclk_level(mut self, clockspeed: i32, voltage: i32) -> Self {
+        self.table
+            .sclk_levels
+            .push(ClocksLevel::new(clockspeed, voltage));
+        self
+    }
+
+    /// Appends a memory clock level.
+    pub fn mclk_level(mut self, clockspeed: i32, voltage: i32) -> Self {
+        self.table
+            .mclk_levels
+            .push(ClocksLevel::new(clockspeed, voltage));
+        self
+    }
+
+    /// Sets the allowed ranges which the levels will be validated against.
+    pub fn od_range(mut self, od_range: OdRange) -> Self {
+        self.table.od_range = od_range;
+        self
+    }
+
+    /// Validates the levels and builds the table.
+    pub fn build(self) -> Result<Table> {
+        let table = self.table;
+
+        for (name, levels, clocks_range) in [
+            ("core clock", &table.sclk_levels, Some(table.od_range.sclk)),
+            ("memory clock", &table.mclk_levels, table.od_range.mclk),
+        ] {
+            for (i, level) in levels.iter().enumerate() {
+                if let Some(range) = clocks_range {
+                    check_value_in_range(range, level.clockspeed, name)?;
+                }
+                if let Some(range) = table.od_range.vddc {
+                    check_value_in_range(range, level.voltage, "voltage")?;
+                }
+
+                if let Some(previous) = i.checked_sub(1).map(|prev_i| levels[prev_i]) {
+                    if previous.clockspeed > level.clockspeed {
+                        return Err(Error::not_allowed(format!(
+                            "The {name} level {i} ({}) is lower than the previous level ({})",
+                            level.clockspeed, previous.clockspeed
+                        )));
+                    }
+                }
+            }
+        }
+
+        Ok(table)
+    }
+}
+
 fn level_command(level: ClocksLevel, i: usize, symbol: char) -> String {
     let ClocksLevel {
         clockspeed,
@@ -271,7 +472,7 @@ enum Section {
 
 #[cfg(test)]
 mod tests {
-    use super::{ClocksLevel, Table};
+    use super::{ClocksLevel, Table, TableBuilder};
     use crate::{
         gpu_handle::overdrive::{arr_commands, vega10::OdRange, ClocksTable, Range},
         include_table,
@@ -315,6 +516,20 @@ mod tests {
         assert_eq!(table.od_range, ranges);
     }
 
+    #[test]
+    fn parse_without_od_range() {
+        let (table_without_range, _) = TABLE_RX580.split_once("OD_RANGE:").unwrap();
+        let mut table = Table::from_str(table_without_range).unwrap();
+
+        assert_eq!(table.sclk_levels.len(), 8);
+        assert_eq!(table.mclk_levels.len(), 3);
+        assert_eq!(table.od_range.sclk, Range::empty());
+        assert_eq!(table.get_max_sclk_range(), None);
+        assert_eq!(table.get_max_mclk_range(), None);
+        assert_eq!(table.get_max_voltage_range(), None);
+        table.set_max_sclk(1400).unwrap_err();
+    }
+
     #[test]
     fn table_into_commands() {
         let mut table = Table::from_str(TABLE_RX580).unwrap();
@@ -351,6 +566,122 @@ mod tests {
         assert_eq!(expected_commands, commands);
     }
 
+    #[test]
+    fn build_table() {
+        let od_range = Table::from_str(TABLE_RX580).unwrap().od_range;
+
+        let table = Table::builder()
+            .sclk_level(300, 750)
+            .sclk_level(1400, 1150)
+            .mclk_level(300, 750)
+            .mclk_level(2000, 975)
+            .od_range(od_range)
+            .build()
+            .unwrap();
+
+        let commands = table.get_commands(&table.clone().into()).unwrap();
+        let expected_commands = vec![
+            "s 0 300 750",
+            "s 1 1400 1150",
+            "m 0 300 750",
+            "m 1 2000 975",
+        ];
+        assert_eq!(expected_commands, commands);
+    }
+
+    #[test]
+    fn build_table_invalid() {
+        let od_range = Table::from_str(TABLE_RX580).unwrap().od_range;
+
+        TableBuilder::new()
+            .sclk_level(300, 1300)
+            .od_range(od_range)
+            .build()
+            .unwrap_err();
+        TableBuilder::new()
+            .sclk_level(300, 1300)
+            .sclk_level(1400, 1150)
+            .mclk_level(2000, 975)
+            .od_range(od_range)
+            .build()
+            .unwrap_err();
+        TableBuilder::new()
+            .sclk_level(1400, 1150)
+            .sclk_level(300, 750)
+            .build()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn build_od_range() {
+        let od_range = OdRange::builder()
+            .sclk(Range::full(300, 2000))
+            .vddc(Range::full(750, 1200))
+            .build();
+        assert_eq!(
+            OdRange {
+                sclk: Range::full(300, 2000),
+                mclk: None,
+                vddc: Some(Range::full(750, 1200)),
+            },
+            od_range
+        );
+
+        TableBuilder::new()
+            .sclk_level(300, 1300)
+            .od_range(od_range)
+            .build()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn set_mclk_voltages() {
+        let mut table = Table::from_str(TABLE_RX580).unwrap();
+        assert_eq!(table.get_max_mclk_voltage(), Some(975));
+
+        table.set_max_mclk_voltage(800).unwrap();
+        assert_eq!(table.get_max_mclk_voltage(), Some(800));
+        assert!(table.mclk_levels.iter().all(|level| level.voltage <= 800));
+
+        table.set_mclk_level_voltage(0, 760).unwrap();
+        assert_eq!(table.mclk_levels[0].voltage, 760);
+
+        let err = table.set_mclk_level_voltage(0, 1300).unwrap_err();
+        assert!(err.to_string().contains("Given voltage 1300"), "{err}");
+        table.set_mclk_level_voltage(3, 800).unwrap_err();
+        table.set_max_mclk_voltage(700).unwrap_err();
+
+        let commands = table.get_commands(&table.clone().into()).unwrap();
+        assert_eq!(
+            &commands[8..],
+            ["m 0 300 760", "m 1 1000 800", "m 2 1750 800"]
+        );
+    }
+
+    #[test]
+    fn voltage_delta() {
+        let mut table = Table::from_str(TABLE_RX580).unwrap();
+        table.apply_voltage_delta(-50).unwrap();
+
+        let sclk_voltages: Vec<i32> = table.sclk_levels.iter().map(|l| l.voltage).collect();
+        assert_eq!(sclk_voltages, [750, 750, 862, 1075, 1100, 1100, 1100, 1100]);
+        let mclk_voltages: Vec<i32> = table.mclk_levels.iter().map(|l| l.voltage).collect();
+        assert_eq!(mclk_voltages, [750, 775, 925]);
+    }
+
+    #[test]
+    fn normalize() {
+        let mut table = Table::from_str(TABLE_RX580).unwrap();
+        table.sclk_levels[7] = ClocksLevel::new(2500, 1300);
+        table.mclk_levels[0] = ClocksLevel::new(100, 700);
+
+        table.normalize();
+
+        assert_eq!(table.sclk_levels[7], ClocksLevel::new(2000, 1200));
+        assert_eq!(table.mclk_levels[0], ClocksLevel::new(300, 750));
+        assert_eq!(table.sclk_levels[3], ClocksLevel::new(1145, 1125));
+    }
+
     #[test]
     fn generic_actions() {
         let mut table = Table::from_str(TABLE_RX580).unwrap();
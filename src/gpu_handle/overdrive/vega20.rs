@@ -1,20 +1,25 @@
 //! The format used by Vega20 and newer GPUs.
 use super::{
-    parse_line_item, parse_range_line, push_level_line, ClocksLevel, ClocksTable, ClocksTableGen,
-    Range,
+    check_value_in_range, parse_line_item, parse_range_line, push_level_line, ClocksLevel,
+    ClocksTable, ClocksTableGen, Range,
 };
 use crate::{
-    error::{Error, ErrorContext, ErrorKind::ParseError},
+    error::{
+        Error, ErrorContext,
+        ErrorKind::{self, ParseError},
+    },
     gpu_handle::trim_sysfs_line,
     Result,
 };
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::{cmp, io::Write, str::FromStr};
+use std::{io::Write, str::FromStr};
 
 /// Vega20 clocks table.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Table {
     /// The current core clock range.
     pub current_sclk_range: Range,
@@ -27,6 +32,14 @@ pub struct Table {
     ///
     /// Note: editing this value directly does not check if it's in the allowed range!
     pub voltage_offset: Option<i32>,
+    /// Core clock offset (in MHz) applied on top of the stock clocks. Used instead of the sclk range on RDNA4.
+    ///
+    /// Note: editing this value directly does not check if it's in the allowed range!
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub sclk_offset: Option<i32>,
     /// The allowed ranges for clockspeeds and voltages.
     pub od_range: OdRange,
 }
@@ -84,6 +97,13 @@ impl ClocksTable for Table {
             }
         }
 
+        if let Some(offset) = self.sclk_offset {
+            let line = sclk_offset_line(offset);
+            writer
+                .write_all(line.as_bytes())
+                .with_context(|| format!("Error when writing clockspeed offset `{line}`"))?;
+        }
+
         for (i, level) in self.vddc_curve.iter().enumerate() {
             let line = vddc_curve_line(i, level.clockspeed, level.voltage);
             writer
@@ -106,7 +126,7 @@ impl ClocksTable for Table {
             .curve_sclk_points
             .last()
             .copied()
-            .or(Some(self.od_range.sclk))
+            .or(Some(self.od_range.sclk).filter(|range| !range.is_empty()))
     }
 
     fn get_min_sclk_range(&self) -> Option<Range> {
@@ -114,7 +134,7 @@ impl ClocksTable for Table {
             .curve_sclk_points
             .first()
             .copied()
-            .or(Some(self.od_range.sclk))
+            .or(Some(self.od_range.sclk).filter(|range| !range.is_empty()))
     }
 
     fn get_max_mclk_range(&self) -> Option<Range> {
@@ -196,6 +216,52 @@ impl ClocksTable for Table {
     fn get_max_sclk_voltage(&self) -> Option<i32> {
         self.vddc_curve.last().map(|level| level.voltage)
     }
+
+    fn normalize(&mut self) {
+        if let (Some(min), Some(range)) = (self.current_sclk_range.min, self.get_min_sclk_range()) {
+            self.current_sclk_range.min = Some(range.clamp(min));
+        }
+        if let (Some(max), Some(range)) = (self.current_sclk_range.max, self.get_max_sclk_range()) {
+            self.current_sclk_range.max = Some(range.clamp(max));
+        }
+
+        if let Some(range) = self.od_range.mclk {
+            self.current_mclk_range.min = self.current_mclk_range.min.map(|min| range.clamp(min));
+            self.current_mclk_range.max = self.current_mclk_range.max.map(|max| range.clamp(max));
+        }
+
+        self.normalize_vddc_curve();
+
+        if let (Some(offset), Some(range)) = (self.voltage_offset, self.od_range.voltage_offset) {
+            self.voltage_offset = Some(range.clamp(offset));
+        }
+        if let (Some(offset), Some(range)) = (self.sclk_offset, self.od_range.sclk_offset) {
+            self.sclk_offset = Some(range.clamp(offset));
+        }
+    }
+
+    fn apply_voltage_delta(&mut self, delta: i32) -> Result<()> {
+        if !self.vddc_curve.is_empty() {
+            for (i, point) in self.vddc_curve.iter_mut().enumerate() {
+                let range = self
+                    .od_range
+                    .curve_voltage_points
+                    .get(i)
+                    .copied()
+                    .unwrap_or_else(Range::empty);
+                point.voltage = range.clamp(point.voltage.saturating_add(delta));
+            }
+            Ok(())
+        } else if let Some(offset) = self.voltage_offset {
+            let range = self.od_range.voltage_offset.unwrap_or_else(Range::empty);
+            self.voltage_offset = Some(range.clamp(offset.saturating_add(delta)));
+            Ok(())
+        } else {
+            Err(Error::not_allowed(
+                "The GPU does not report a voltage curve or offset".to_owned(),
+            ))
+        }
+    }
 }
 
 impl Table {
@@ -205,7 +271,7 @@ impl Table {
     pub fn set_voltage_offset(&mut self, offset: i32) -> Result<()> {
         if let Some(offset_range) = self.od_range.voltage_offset {
             if let Some((min, max)) = offset_range.into_full() {
-                if !(min..=max).contains(&offset) {
+                if !offset_range.contains(offset) {
                     return Err(Error::not_allowed(format!("Provided voltage offset {offset} is out of range, should be between {min} and {max}")));
                 }
             }
@@ -214,12 +280,132 @@ impl Table {
         self.voltage_offset = Some(offset);
         Ok(())
     }
+
+    /// Gets the effective voltage (in mV) after applying the voltage offset to the given stock voltage.
+    ///
+    /// GPUs which use a voltage offset do not report their stock voltage in the table,
+    /// so it has to be provided by the caller (e.g. the `in0_input` reading at stock settings).
+    /// Returns the stock voltage unchanged if no offset is set.
+    pub fn get_effective_voltage(&self, stock_voltage: i32) -> i32 {
+        stock_voltage.saturating_add(self.voltage_offset.unwrap_or(0))
+    }
+
+    /// Gets the range of effective voltages (in mV) which can be reached with the allowed voltage offsets,
+    /// for the given stock voltage.
+    ///
+    /// Returns `None` if the GPU does not report a voltage offset range.
+    pub fn get_effective_voltage_range(&self, stock_voltage: i32) -> Option<Range> {
+        self.od_range
+            .voltage_offset
+            .filter(|range| !range.is_empty())
+            .map(|range| range + stock_voltage)
+    }
+
+    /// Sets the core clock offset, checking if it's in range if the GPU provided one.
+    pub fn set_sclk_offset(&mut self, offset: i32) -> Result<()> {
+        if let Some(range) = self.od_range.sclk_offset {
+            check_value_in_range(range, offset, "core clock offset")?;
+        }
+        self.sclk_offset = Some(offset);
+        Ok(())
+    }
+
+    /// Gets the effective maximum core clock (in MHz) after applying the core clock offset to the given stock clock.
+    ///
+    /// Like with the voltage offset, the stock clock is not reported in the table and has to be provided by the caller
+    /// (e.g. the highest level in `pp_dpm_sclk`). Returns the stock clock unchanged if no offset is set.
+    pub fn get_effective_sclk(&self, stock_sclk: i32) -> i32 {
+        stock_sclk.saturating_add(self.sclk_offset.unwrap_or(0))
+    }
+
+    /// Gets the range of effective maximum core clocks (in MHz) which can be reached with the allowed offsets,
+    /// for the given stock clock.
+    ///
+    /// Returns `None` if the GPU does not report a core clock offset range.
+    pub fn get_effective_sclk_range(&self, stock_sclk: i32) -> Option<Range> {
+        self.od_range
+            .sclk_offset
+            .filter(|range| !range.is_empty())
+            .map(|range| range + stock_sclk)
+    }
+}
+
+impl Table {
+    /// Sets the voltage of an existing voltage curve point, checking if it's in range if the GPU provided one.
+    pub fn set_vddc_curve_point_voltage(&mut self, index: usize, voltage: i32) -> Result<()> {
+        if let Some(range) = self.od_range.curve_voltage_points.get(index) {
+            check_value_in_range(*range, voltage, "curve point voltage")?;
+        }
+
+        let point = self.vddc_curve.get_mut(index).ok_or_else(|| {
+            Error::not_allowed(format!(
+                "The GPU did not report voltage curve point {index}"
+            ))
+        })?;
+        point.voltage = voltage;
+
+        Ok(())
+    }
+
+    /// Writes only the voltage commands which differ from the previous table.
+    ///
+    /// Unlike [`ClocksTable::write_commands`], this never emits `s`/`m` clockspeed commands,
+    /// which makes it suitable for undervolting on cards that reject redundant clockspeed writes.
+    pub fn write_voltage_commands<W: Write>(
+        &self,
+        writer: &mut W,
+        previous_table: &Table,
+    ) -> Result<()> {
+        for (i, level) in self.vddc_curve.iter().enumerate() {
+            if previous_table.vddc_curve.get(i) == Some(level) {
+                continue;
+            }
+
+            let line = vddc_curve_line(i, level.clockspeed, level.voltage);
+            writer
+                .write_all(line.as_bytes())
+                .with_context(|| format!("Error when writing VDDC line `{line}`"))?;
+        }
+
+        if let Some(offset) = self.voltage_offset {
+            if previous_table.voltage_offset != Some(offset) {
+                let line = voltage_offset_line(offset);
+                writer
+                    .write_all(line.as_bytes())
+                    .with_context(|| format!("Error when writing voltage offset `{line}`"))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the list of voltage commands that differ from the previous table.
+    /// See [`Table::write_voltage_commands`].
+    pub fn get_voltage_commands(&self, previous_table: &Table) -> Result<Vec<String>> {
+        let mut buf = Vec::new();
+        self.write_voltage_commands(&mut buf, previous_table)?;
+        let raw_commands = String::from_utf8(buf).map_err(|_| {
+            ErrorKind::Unsupported("Generated voltage commands are not valid UTF-8".into())
+        })?;
+        Ok(raw_commands.lines().map(str::to_owned).collect())
+    }
 }
 
 impl FromStr for Table {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self> {
+        Self::parse_lines(s.lines())
+    }
+}
+
+impl Table {
+    /// Parses the table line by line.
+    pub(super) fn parse_lines<I, S>(lines: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
         let mut current_section = None;
 
         let mut current_sclk_range = None;
@@ -233,20 +419,22 @@ impl FromStr for Table {
 
         let mut voltage_offset = None;
         let mut voltage_offset_range = None;
+        let mut sclk_offset = None;
+        let mut sclk_offset_range = None;
 
-        let mut lines = s
-            .lines()
-            .map(trim_sysfs_line)
-            .filter(|line| !line.is_empty());
+        let mut lines = lines
+            .into_iter()
+            .filter(|line| !trim_sysfs_line(line.as_ref()).is_empty());
 
         let mut i = 1;
         while let Some(line) = lines.next() {
-            match line {
+            match trim_sysfs_line(line.as_ref()) {
                 "OD_SCLK:" => current_section = Some(Section::Sclk),
                 "OD_MCLK:" => current_section = Some(Section::Mclk),
                 "OD_RANGE:" => current_section = Some(Section::Range),
                 "OD_VDDC_CURVE:" => current_section = Some(Section::VddcCurve),
                 "OD_VDDGFX_OFFSET:" => current_section = Some(Section::VddGfxOffset),
+                "OD_SCLK_OFFSET:" => current_section = Some(Section::SclkOffset),
                 line => match current_section {
                     // Voltage points will overwrite maximum clock info, with the last one taking priority
                     Some(Section::Range) if line.starts_with("VDDC_CURVE_SCLK") => {
@@ -270,6 +458,7 @@ impl FromStr for Table {
                             "SCLK" => allowed_sclk_range = Some(range),
                             "MCLK" => allowed_mclk_range = Some(range),
                             "VDDGFX_OFFSET" => voltage_offset_range = Some(range),
+                            "SCLK_OFFSET" => sclk_offset_range = Some(range),
                             "CCLK" => (), // Ignore Van Gogh CPU clocks
                             other => {
                                 return Err(ParseError {
@@ -286,9 +475,13 @@ impl FromStr for Table {
                         let _ = push_level_line(line, &mut vddc_curve, i);
                     }
                     Some(Section::VddGfxOffset) => {
-                        let offset = parse_voltage_offset_line(line, i)?;
+                        let offset = parse_offset_line(line, i, "mV")?;
                         voltage_offset = Some(offset);
                     }
+                    Some(Section::SclkOffset) => {
+                        let offset = parse_offset_line(line, i, "MHz")?;
+                        sclk_offset = Some(offset);
+                    }
                     None => {
                         return Err(ParseError {
                             msg: "Unexpected line without section".to_owned(),
@@ -302,19 +495,25 @@ impl FromStr for Table {
         }
 
         let od_range = OdRange {
-            sclk: allowed_sclk_range.ok_or_else(|| ParseError {
-                msg: "No sclk range found".to_owned(),
-                line: i,
-            })?,
+            sclk: allowed_sclk_range.unwrap_or_else(Range::empty),
             mclk: allowed_mclk_range,
             curve_sclk_points,
             curve_voltage_points,
             voltage_offset: voltage_offset_range,
+            sclk_offset: sclk_offset_range,
+        };
+        // GPUs with a clock offset don't report the clock range
+        let current_sclk_range = match current_sclk_range {
+            Some(range) => range,
+            None if sclk_offset.is_some() => Range::empty(),
+            None => {
+                return Err(ParseError {
+                    msg: "No current sclk range found".to_owned(),
+                    line: i,
+                }
+                .into())
+            }
         };
-        let current_sclk_range = current_sclk_range.ok_or_else(|| ParseError {
-            msg: "No current sclk range found".to_owned(),
-            line: i,
-        })?;
 
         Ok(Self {
             current_sclk_range,
@@ -322,11 +521,17 @@ impl FromStr for Table {
             vddc_curve,
             od_range,
             voltage_offset,
+            sclk_offset,
         })
     }
 }
 
 impl Table {
+    /// Creates a builder for constructing a table from scratch.
+    pub fn builder() -> TableBuilder {
+        TableBuilder::new()
+    }
+
     /// Clears the table of all "applicable" values.
     ///
     /// This removes all values except the allowed range and voltage curve.
@@ -336,6 +541,7 @@ impl Table {
         self.current_sclk_range = Range::empty();
         self.current_mclk_range = Range::empty();
         self.voltage_offset = None;
+        self.sclk_offset = None;
     }
 
     /// Normalizes the VDDC curve making sure all of the values are within the allowed range.
@@ -343,34 +549,147 @@ impl Table {
     pub fn normalize_vddc_curve(&mut self) {
         for (i, point) in self.vddc_curve.iter_mut().enumerate() {
             if let Some(sclk_range) = self.od_range.curve_sclk_points.get(i) {
-                let normalized_clockspeed = normalize_value(point.clockspeed, *sclk_range);
+                let normalized_clockspeed = sclk_range.clamp(point.clockspeed);
                 point.clockspeed = normalized_clockspeed;
             }
 
             if let Some(voltage_range) = self.od_range.curve_voltage_points.get(i) {
-                let normalized_voltage = normalize_value(point.voltage, *voltage_range);
+                let normalized_voltage = voltage_range.clamp(point.voltage);
                 point.voltage = normalized_voltage;
             }
         }
     }
 }
 
-fn normalize_value(mut value: i32, range: Range) -> i32 {
-    if let Some(min_allowed) = range.min {
-        value = cmp::max(min_allowed, value);
+/// Builder for constructing a [`Table`] from scratch, e.g. when applying a saved profile.
+///
+/// All values are empty by default, meaning that they will not be written.
+/// The values are checked against the allowed OD ranges (if provided) when calling [`TableBuilder::build`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TableBuilder {
+    table: Table,
+}
+
+impl TableBuilder {
+    /// Creates a new builder with all values empty.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the minimum core clock.
+    pub fn min_sclk(mut self, clockspeed: i32) -> Self {
+        self.table.current_sclk_range.min = Some(clockspeed);
+        self
     }
-    if let Some(max_allowed) = range.max {
-        value = cmp::min(max_allowed, value);
+
+    /// Sets the maximum core clock.
+    pub fn max_sclk(mut self, clockspeed: i32) -> Self {
+        self.table.current_sclk_range.max = Some(clockspeed);
+        self
+    }
+
+    /// Sets the minimum memory clock.
+    pub fn min_mclk(mut self, clockspeed: i32) -> Self {
+        self.table.current_mclk_range.min = Some(clockspeed);
+        self
+    }
+
+    /// Sets the maximum memory clock.
+    pub fn max_mclk(mut self, clockspeed: i32) -> Self {
+        self.table.current_mclk_range.max = Some(clockspeed);
+        self
+    }
+
+    /// Appends a point to the voltage curve.
+    pub fn vddc_curve_point(mut self, clockspeed: i32, voltage: i32) -> Self {
+        self.table
+            .vddc_curve
+            .push(ClocksLevel::new(clockspeed, voltage));
+        self
+    }
+
+    /// Sets the voltage offset.
+    pub fn voltage_offset(mut self, offset: i32) -> Self {
+        self.table.voltage_offset = Some(offset);
+        self
+    }
+
+    /// Sets the core clock offset.
+    pub fn sclk_offset(mut self, offset: i32) -> Self {
+        self.table.sclk_offset = Some(offset);
+        self
+    }
+
+    /// Sets the allowed ranges which the values will be validated against.
+    pub fn od_range(mut self, od_range: OdRange) -> Self {
+        self.table.od_range = od_range;
+        self
     }
 
-    value
+    /// Validates the values and builds the table.
+    pub fn build(self) -> Result<Table> {
+        let table = self.table;
+
+        for (name, current_range) in [
+            ("core clock", table.current_sclk_range),
+            ("memory clock", table.current_mclk_range),
+        ] {
+            if let Some((min, max)) = current_range.into_full() {
+                if min > max {
+                    return Err(Error::not_allowed(format!(
+                        "Minimum {name} {min} is higher than the maximum {max}"
+                    )));
+                }
+            }
+        }
+
+        if let Some(min) = table.current_sclk_range.min {
+            if let Some(range) = table.get_min_sclk_range() {
+                check_value_in_range(range, min, "core clock")?;
+            }
+        }
+        if let Some(max) = table.current_sclk_range.max {
+            if let Some(range) = table.get_max_sclk_range() {
+                check_value_in_range(range, max, "core clock")?;
+            }
+        }
+
+        if let Some(range) = table.od_range.mclk {
+            for value in [table.current_mclk_range.min, table.current_mclk_range.max]
+                .into_iter()
+                .flatten()
+            {
+                check_value_in_range(range, value, "memory clock")?;
+            }
+        }
+
+        for (i, point) in table.vddc_curve.iter().enumerate() {
+            if let Some(range) = table.od_range.curve_sclk_points.get(i) {
+                check_value_in_range(*range, point.clockspeed, "curve point clockspeed")?;
+            }
+            if let Some(range) = table.od_range.curve_voltage_points.get(i) {
+                check_value_in_range(*range, point.voltage, "curve point voltage")?;
+            }
+        }
+
+        if let (Some(offset), Some(range)) = (table.voltage_offset, table.od_range.voltage_offset) {
+            check_value_in_range(range, offset, "voltage offset")?;
+        }
+        if let (Some(offset), Some(range)) = (table.sclk_offset, table.od_range.sclk_offset) {
+            check_value_in_range(range, offset, "core clock offset")?;
+        }
+
+        Ok(table)
+    }
 }
 
 /// The ranges for overclocking values which the GPU allows to be used.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct OdRange {
-    /// Clocks range for sclk (in MHz). Should be present on all GPUs.
+    /// Clocks range for sclk (in MHz). Empty if the GPU does not report an `OD_RANGE` section.
     pub sclk: Range,
     /// Clocks range for mclk (in MHz). Present on discrete GPUs only.
     pub mclk: Option<Range>,
@@ -380,6 +699,70 @@ pub struct OdRange {
     pub curve_voltage_points: Vec<Range>,
     /// Allowed voltage offset range. Present on RDNA3+.
     pub voltage_offset: Option<Range>,
+    /// Allowed core clock offset range. Present on RDNA4.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub sclk_offset: Option<Range>,
+}
+
+impl OdRange {
+    /// Creates a builder for constructing the ranges from scratch.
+    pub fn builder() -> OdRangeBuilder {
+        OdRangeBuilder::new()
+    }
+}
+
+/// Builder for constructing an [`OdRange`], e.g. for validating values with [`TableBuilder::od_range`].
+///
+/// All ranges are empty by default, meaning that the values are not limited.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OdRangeBuilder {
+    od_range: OdRange,
+}
+
+impl OdRangeBuilder {
+    /// Creates a new builder with all ranges empty.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the core clock range.
+    pub fn sclk(mut self, range: Range) -> Self {
+        self.od_range.sclk = range;
+        self
+    }
+
+    /// Sets the memory clock range.
+    pub fn mclk(mut self, range: Range) -> Self {
+        self.od_range.mclk = Some(range);
+        self
+    }
+
+    /// Appends the ranges of a voltage curve point.
+    pub fn curve_point(mut self, sclk: Range, voltage: Range) -> Self {
+        self.od_range.curve_sclk_points.push(sclk);
+        self.od_range.curve_voltage_points.push(voltage);
+        self
+    }
+
+    /// Sets the voltage offset range.
+    pub fn voltage_offset(mut self, range: Range) -> Self {
+        self.od_range.voltage_offset = Some(range);
+        self
+    }
+
+    /// Sets the core clock offset range.
+    pub fn sclk_offset(mut self, range: Range) -> Self {
+        self.od_range.sclk_offset = Some(range);
+        self
+    }
+
+    /// Builds the ranges.
+    pub fn build(self) -> OdRange {
+        self.od_range
+    }
 }
 
 #[derive(Debug)]
@@ -389,6 +772,7 @@ enum Section {
     VddcCurve,
     Range,
     VddGfxOffset,
+    SclkOffset,
 }
 
 fn parse_clockspeed_line(line: &str, i: usize) -> Result<(i32, usize)> {
@@ -422,11 +806,11 @@ fn parse_min_max_line(line: &str, i: usize, range: &mut Option<Range>) -> Result
     }
 }
 
-fn parse_voltage_offset_line(line: &str, i: usize) -> Result<i32> {
-    match line.to_lowercase().strip_suffix("mv") {
+fn parse_offset_line(line: &str, i: usize, unit: &str) -> Result<i32> {
+    match line.to_lowercase().strip_suffix(&unit.to_lowercase()) {
         Some(raw_value) => Ok(raw_value.parse()?),
         None => Err(ParseError {
-            msg: format!("Could not find expected `mV` suffix in offset line {line}"),
+            msg: format!("Could not find expected `{unit}` suffix in offset line {line}"),
             line: i,
         }
         .into()),
@@ -445,9 +829,13 @@ fn voltage_offset_line(offset: i32) -> String {
     format!("vo {offset}\n")
 }
 
+fn sclk_offset_line(offset: i32) -> String {
+    format!("s {offset}\n")
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{OdRange, Table};
+    use super::{OdRange, Table, TableBuilder};
     use crate::{
         gpu_handle::overdrive::{
             arr_commands, tests::TABLE_PHOENIX, ClocksLevel, ClocksTable, Range,
@@ -467,6 +855,7 @@ mod tests {
     const TABLE_7900XT: &str = include_table!("rx7900xt");
     const TABLE_7800XT: &str = include_table!("rx7800xt");
     const TABLE_VANGOGH: &str = include_table!("vangogh");
+    const TABLE_9070XT: &str = include_table!("rx9070xt");
 
     #[test]
     fn parse_5700xt_full() {
@@ -496,6 +885,7 @@ mod tests {
             curve_sclk_points,
             curve_voltage_points,
             voltage_offset: None,
+            sclk_offset: None,
         };
         assert_eq!(table.od_range, od_range);
     }
@@ -571,6 +961,64 @@ mod tests {
         assert_eq!(750, table.vddc_curve[0].voltage);
     }
 
+    #[test]
+    fn write_voltage_commands_5700xt() {
+        let original_table = Table::from_str(TABLE_5700XT).unwrap();
+        let mut table = original_table.clone();
+        assert!(table
+            .get_voltage_commands(&original_table)
+            .unwrap()
+            .is_empty());
+
+        table.set_vddc_curve_point_voltage(1, 780).unwrap();
+        table.set_vddc_curve_point_voltage(2, 1150).unwrap();
+        table.set_vddc_curve_point_voltage(2, 1250).unwrap_err();
+        table.set_vddc_curve_point_voltage(3, 1000).unwrap_err();
+
+        let commands = table.get_voltage_commands(&original_table).unwrap();
+        assert_eq!(vec!["vc 1 1450 780", "vc 2 2100 1150"], commands);
+    }
+
+    #[test]
+    fn voltage_delta_5700xt() {
+        let mut table = Table::from_str(TABLE_5700XT).unwrap();
+        table.apply_voltage_delta(-50).unwrap();
+
+        let voltages: Vec<i32> = table.vddc_curve.iter().map(|point| point.voltage).collect();
+        assert_eq!(voltages, [750, 751, 1141]);
+    }
+
+    #[test]
+    fn voltage_delta_7800xt() {
+        let mut table = Table::from_str(TABLE_7800XT).unwrap();
+        table.apply_voltage_delta(-50).unwrap();
+        assert_eq!(table.voltage_offset, Some(-50));
+
+        table.apply_voltage_delta(-1000).unwrap();
+        assert_eq!(table.voltage_offset, Some(-450));
+
+        table.apply_voltage_delta(i32::MIN).unwrap();
+        assert_eq!(table.voltage_offset, Some(-450));
+
+        let mut table = Table::from_str(TABLE_PHOENIX).unwrap();
+        table.apply_voltage_delta(-50).unwrap_err();
+    }
+
+    #[test]
+    fn normalize_6900xt() {
+        let mut table = Table::from_str(TABLE_6900XT).unwrap();
+        table.set_max_sclk_unchecked(5000).unwrap();
+        table.voltage_offset = Some(-1000);
+
+        table.normalize();
+
+        assert_eq!(table.current_sclk_range, Range::full(500, 3000));
+        // The default minimum memory clock is outside of the allowed range
+        assert_eq!(table.current_mclk_range, Range::full(674, 1000));
+        // No offset range is reported on RDNA2
+        assert_eq!(table.voltage_offset, Some(-1000));
+    }
+
     #[test]
     fn write_commands_5500xt() {
         let mut table = Table::from_str(TABLE_5500XT).unwrap();
@@ -595,12 +1043,14 @@ mod tests {
             current_mclk_range: Range::full(500, 1000),
             vddc_curve: vec![ClocksLevel::new(300, 600), ClocksLevel::new(1000, 1000)],
             voltage_offset: None,
+            sclk_offset: None,
             od_range: OdRange {
                 sclk: Range::empty(),
                 mclk: None,
                 curve_sclk_points: Vec::new(),
                 curve_voltage_points: Vec::new(),
                 voltage_offset: None,
+                sclk_offset: None,
             },
         };
 
@@ -616,6 +1066,103 @@ mod tests {
         assert_eq!(expected_commands, commands);
     }
 
+    #[test]
+    fn build_table_5700xt() {
+        let od_range = Table::from_str(TABLE_5700XT).unwrap().od_range;
+
+        let table = Table::builder()
+            .min_sclk(850)
+            .max_sclk(2100)
+            .max_mclk(900)
+            .vddc_curve_point(850, 750)
+            .vddc_curve_point(1450, 850)
+            .vddc_curve_point(2100, 1150)
+            .od_range(od_range)
+            .build()
+            .unwrap();
+
+        let commands = table.get_commands(&table.clone().into()).unwrap();
+        let expected_commands = vec![
+            "s 0 850",
+            "s 1 2100",
+            "m 1 900",
+            "vc 0 850 750",
+            "vc 1 1450 850",
+            "vc 2 2100 1150",
+        ];
+        assert_eq!(expected_commands, commands);
+    }
+
+    #[test]
+    fn build_table_invalid() {
+        let od_range = Table::from_str(TABLE_5700XT).unwrap().od_range;
+
+        TableBuilder::new()
+            .max_sclk(2500)
+            .od_range(od_range.clone())
+            .build()
+            .unwrap_err();
+        TableBuilder::new()
+            .vddc_curve_point(800, 700)
+            .od_range(od_range)
+            .build()
+            .unwrap_err();
+        TableBuilder::new()
+            .min_sclk(2000)
+            .max_sclk(1000)
+            .build()
+            .unwrap_err();
+        TableBuilder::new().max_sclk(5000).build().unwrap();
+    }
+
+    #[test]
+    fn build_od_range() {
+        let od_range = OdRange::builder()
+            .sclk(Range::full(500, 2500))
+            .curve_point(Range::full(800, 2150), Range::full(750, 1200))
+            .voltage_offset(Range::full(-450, 0))
+            .build();
+        assert_eq!(
+            OdRange {
+                sclk: Range::full(500, 2500),
+                mclk: None,
+                curve_sclk_points: vec![Range::full(800, 2150)],
+                curve_voltage_points: vec![Range::full(750, 1200)],
+                voltage_offset: Some(Range::full(-450, 0)),
+                sclk_offset: None,
+            },
+            od_range
+        );
+
+        TableBuilder::new()
+            .vddc_curve_point(2200, 1000)
+            .od_range(od_range)
+            .build()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn build_table_with_default_range() {
+        let od_range = OdRange {
+            sclk: Range::full(500, 2500),
+            ..Default::default()
+        };
+        let table = TableBuilder::new()
+            .max_sclk(2000)
+            .od_range(od_range.clone())
+            .build()
+            .unwrap();
+        assert_eq!(
+            Table {
+                current_sclk_range: Range::max(2000),
+                od_range,
+                ..Default::default()
+            },
+            table
+        );
+        assert_eq!(Table::default(), TableBuilder::new().build().unwrap());
+    }
+
     #[test]
     fn parse_6900xt_full() {
         let table = Table::from_str(TABLE_6900XT).unwrap();
@@ -746,12 +1293,71 @@ mod tests {
         table.set_voltage_offset(100).unwrap_err();
     }
 
+    #[test]
+    fn effective_voltage_7800xt() {
+        let mut table = Table::from_str(TABLE_7800XT).unwrap();
+        assert_eq!(table.get_effective_voltage(1100), 1100);
+        assert_eq!(
+            table.get_effective_voltage_range(1100),
+            Some(Range::full(650, 1100))
+        );
+
+        table.set_voltage_offset(-100).unwrap();
+        assert_eq!(table.get_effective_voltage(1100), 1000);
+    }
+
+    #[test]
+    fn effective_voltage_without_range() {
+        let table = Table::from_str(TABLE_5700XT).unwrap();
+        assert_eq!(table.get_effective_voltage(1100), 1100);
+        assert_eq!(table.get_effective_voltage_range(1100), None);
+        assert_eq!(table.get_effective_sclk(2100), 2100);
+        assert_eq!(table.get_effective_sclk_range(2100), None);
+    }
+
+    #[test]
+    fn parse_9070xt_sclk_offset() {
+        let mut table = Table::from_str(TABLE_9070XT).unwrap();
+        assert_eq!(table.current_sclk_range, Range::empty());
+        assert_eq!(table.current_mclk_range, Range::full(97, 1258));
+        assert_eq!(table.sclk_offset, Some(0));
+        assert_eq!(table.voltage_offset, Some(0));
+        assert_eq!(table.od_range.sclk_offset, Some(Range::full(-500, 1000)));
+        assert_eq!(table.od_range.voltage_offset, Some(Range::full(-200, 0)));
+
+        assert_eq!(
+            table.get_effective_sclk_range(2970),
+            Some(Range::full(2470, 3970))
+        );
+        table.set_sclk_offset(1500).unwrap_err();
+        table.set_sclk_offset(200).unwrap();
+        assert_eq!(table.get_effective_sclk(2970), 3170);
+        assert_eq!(table.get_effective_voltage(i32::MAX), i32::MAX);
+
+        let commands = table
+            .get_commands(&Table::from_str(TABLE_9070XT).unwrap().into())
+            .unwrap();
+        assert_eq!(commands, ["m 0 97", "m 1 1258", "s 200", "vo 0"]);
+    }
+
     #[test]
     fn parse_phoenix_full() {
         let table = Table::from_str(TABLE_PHOENIX).unwrap();
         assert_yaml_snapshot!(table);
     }
 
+    #[test]
+    fn parse_phoenix_without_od_range() {
+        let (table_without_range, _) = TABLE_PHOENIX.split_once("OD_RANGE:").unwrap();
+        let mut table = Table::from_str(table_without_range).unwrap();
+
+        assert_eq!(table.current_sclk_range, Range::full(800, 2700));
+        assert_eq!(table.od_range.sclk, Range::empty());
+        assert_eq!(table.get_max_sclk_range(), None);
+        assert_eq!(table.get_min_sclk_range(), None);
+        table.set_max_sclk(2000).unwrap_err();
+    }
+
     #[test]
     fn parse_vangogh_full() {
         let table = Table::from_str(TABLE_VANGOGH).unwrap();
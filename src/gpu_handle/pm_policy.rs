@@ -0,0 +1,187 @@
+//! Power management policies in the `pm_policy` directory.
+//!
+//! Each policy file lists the available levels as `<number> : <name>`, with the active one marked by `*`.
+//! A policy is selected by writing its number to the file.
+use crate::{error::Error, Result};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// A policy which can be set through a `pm_policy` file.
+pub trait PmPolicy: FromStr + fmt::Display + Copy + Eq {
+    /// Name of the file in the `pm_policy` directory.
+    const FILE_NAME: &'static str;
+}
+
+/// SoC P-state policy. Controls which SoC P-states the firmware is allowed to use, trading latency for power.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum SocPstatePolicy {
+    /// Firmware default behaviour
+    Default,
+    /// Restrict to P-state 0
+    Pstate0,
+    /// Restrict to P-state 1
+    Pstate1,
+    /// Restrict to P-state 2
+    Pstate2,
+}
+
+impl PmPolicy for SocPstatePolicy {
+    const FILE_NAME: &'static str = "soc_pstate";
+}
+
+impl FromStr for SocPstatePolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "soc_pstate_default" => Ok(Self::Default),
+            "soc_pstate_0" => Ok(Self::Pstate0),
+            "soc_pstate_1" => Ok(Self::Pstate1),
+            "soc_pstate_2" => Ok(Self::Pstate2),
+            _ => Err(Error::basic_parse_error(format!(
+                "Unknown SoC P-state policy {s}"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for SocPstatePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Default => "soc_pstate_default",
+            Self::Pstate0 => "soc_pstate_0",
+            Self::Pstate1 => "soc_pstate_1",
+            Self::Pstate2 => "soc_pstate_2",
+        })
+    }
+}
+
+/// XGMI per-link power down policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum XgmiPlpdPolicy {
+    /// Links are never powered down
+    Disallow,
+    /// Firmware default behaviour
+    Default,
+    /// Links are powered down more aggressively to save power
+    Optimized,
+}
+
+impl PmPolicy for XgmiPlpdPolicy {
+    const FILE_NAME: &'static str = "xgmi_plpd";
+}
+
+impl FromStr for XgmiPlpdPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "plpd_disallow" => Ok(Self::Disallow),
+            "plpd_default" => Ok(Self::Default),
+            "plpd_optimized" => Ok(Self::Optimized),
+            _ => Err(Error::basic_parse_error(format!(
+                "Unknown XGMI PLPD policy {s}"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for XgmiPlpdPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Disallow => "plpd_disallow",
+            Self::Default => "plpd_default",
+            Self::Optimized => "plpd_optimized",
+        })
+    }
+}
+
+/// The available levels of a policy along with the active one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PmPolicyLevels<T> {
+    /// Policies supported by the GPU
+    pub levels: Vec<T>,
+    /// The currently active policy
+    pub active: Option<T>,
+}
+
+/// Parses a policy file, keeping the number of each policy.
+pub(crate) fn parse_levels<T: PmPolicy<Err = Error>>(
+    data: &str,
+) -> Result<PmPolicyLevels<(u32, T)>> {
+    let mut levels = Vec::new();
+    let mut active = None;
+
+    for (i, line) in data
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .enumerate()
+    {
+        let (number, name) = line
+            .split_once(':')
+            .ok_or_else(|| Error::unexpected_eol(":", i + 1))?;
+        let number = number.trim().parse()?;
+
+        let name = name.trim();
+        let (name, is_active) = match name.strip_suffix('*') {
+            Some(name) => (name.trim_end(), true),
+            None => (name, false),
+        };
+
+        let level = (number, T::from_str(name)?);
+        if is_active {
+            active = Some(level);
+        }
+        levels.push(level);
+    }
+
+    Ok(PmPolicyLevels { levels, active })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_levels, SocPstatePolicy, XgmiPlpdPolicy};
+
+    #[test]
+    fn parse_soc_pstate() {
+        let data =
+            "0 : soc_pstate_default\n1 : soc_pstate_0\n2 : soc_pstate_1*\n3 : soc_pstate_2\n";
+        let policy = parse_levels::<SocPstatePolicy>(data).unwrap();
+        assert_eq!(
+            vec![
+                (0, SocPstatePolicy::Default),
+                (1, SocPstatePolicy::Pstate0),
+                (2, SocPstatePolicy::Pstate1),
+                (3, SocPstatePolicy::Pstate2)
+            ],
+            policy.levels
+        );
+        assert_eq!(Some((2, SocPstatePolicy::Pstate1)), policy.active);
+    }
+
+    #[test]
+    fn parse_xgmi_plpd() {
+        let data = "1 : plpd_default *\n2 : plpd_optimized\n";
+        let policy = parse_levels::<XgmiPlpdPolicy>(data).unwrap();
+        assert_eq!(
+            vec![(1, XgmiPlpdPolicy::Default), (2, XgmiPlpdPolicy::Optimized)],
+            policy.levels
+        );
+        assert_eq!(Some((1, XgmiPlpdPolicy::Default)), policy.active);
+
+        assert!(parse_levels::<XgmiPlpdPolicy>("0 : plpd_unknown").is_err());
+        assert!(parse_levels::<XgmiPlpdPolicy>("plpd_default").is_err());
+    }
+}
@@ -1,9 +1,12 @@
+use crate::{error::Error, Result};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 /// List of power levels.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PowerLevels<T> {
     /// List of possible levels.
     pub levels: Vec<T>,
@@ -18,6 +21,187 @@ impl<T> PowerLevels<T> {
     }
 }
 
+impl<T> PowerLevels<PowerLevel<T>> {
+    /// Drops the raw lines, keeping only the parsed values.
+    pub fn into_values(self) -> PowerLevels<T> {
+        PowerLevels {
+            levels: self.levels.into_iter().map(|level| level.value).collect(),
+            active: self.active,
+        }
+    }
+}
+
+/// A parsed power level along with the line it was parsed from,
+/// see [`GpuHandle::get_clock_levels_with_raw`](super::GpuHandle::get_clock_levels_with_raw).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PowerLevel<T> {
+    /// Parsed value of the level
+    pub value: T,
+    /// The line as reported by the kernel, e.g. `1: 2660Mhz *`
+    pub raw: String,
+}
+
+impl PowerLevels<u64> {
+    /// Checks if the clockspeed levels contain real data.
+    ///
+    /// Some GPUs report bogus levels (e.g. all of them being 0 MHz and marked as active) when the clock is not in use
+    /// or the driver could not read it, which should not be displayed as the actual DPM states.
+    pub fn is_valid(&self) -> bool {
+        !self.levels.is_empty() && self.levels.iter().all(|level| *level != 0)
+    }
+}
+
+/// Parses the lines of a DPM file without interpreting the values, see [`GpuHandle::get_clock_levels_raw`](super::GpuHandle::get_clock_levels_raw).
+///
+/// The level index prefix and the active marker are stripped when present.
+pub(crate) fn parse_raw_levels(content: &str) -> PowerLevels<String> {
+    let mut levels = Vec::new();
+    let mut active = None;
+    let mut active_count = 0;
+
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() {
+            continue;
+        }
+
+        let (line, is_active) = match line.strip_suffix('*') {
+            Some(stripped) => (stripped.trim_end(), true),
+            None => (line, false),
+        };
+
+        let value = match line.split_once(':') {
+            Some((identifier, value)) if identifier.trim().parse::<usize>().is_ok() => value.trim(),
+            _ => line,
+        };
+
+        if is_active {
+            active_count += 1;
+            active = Some(levels.len());
+        }
+        levels.push(value.to_owned());
+    }
+
+    PowerLevels {
+        levels,
+        // Multiple active levels mean that the data is not reliable
+        active: active.filter(|_| active_count == 1),
+    }
+}
+
+/// A parsed PCIe DPM level from `pp_dpm_pcie`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PcieLevel {
+    /// Link transfer rate in MT/s (e.g. 8000 for 8.0GT/s)
+    pub transfer_rate: u32,
+    /// Link width (number of lanes)
+    pub width: u16,
+    /// Link clock (LCLK) in MHz. Only reported on RDNA and newer.
+    pub clockspeed: Option<u64>,
+    /// The original text of the level
+    pub raw: String,
+}
+
+impl PcieLevel {
+    /// Gets the transfer rate in GT/s.
+    pub fn transfer_rate_gts(&self) -> f64 {
+        f64::from(self.transfer_rate) / 1000.0
+    }
+
+    /// Gets the PCIe generation corresponding to the transfer rate (e.g. 4 for 16GT/s).
+    pub fn generation(&self) -> Option<u8> {
+        PCIE_GEN_TRANSFER_RATES
+            .iter()
+            .position(|rate| *rate == self.transfer_rate)
+            .map(|i| i as u8 + 1)
+    }
+}
+
+/// Transfer rates in MT/s of each PCIe generation, starting from Gen1.
+const PCIE_GEN_TRANSFER_RATES: [u32; 6] = [2500, 5000, 8000, 16000, 32000, 64000];
+
+/// Parses a PCIe link description like `Gen4 x16` or `gen3` into the generation and optional width.
+pub(crate) fn parse_pcie_link(link: &str) -> Result<(u8, Option<u16>)> {
+    let mut parts = link.split_whitespace();
+    let generation = parts
+        .next()
+        .and_then(|raw_gen| {
+            raw_gen
+                .get(..3)
+                .filter(|prefix| prefix.eq_ignore_ascii_case("gen"))?;
+            raw_gen[3..].parse::<u8>().ok()
+        })
+        .filter(|generation| (1..=PCIE_GEN_TRANSFER_RATES.len() as u8).contains(generation))
+        .ok_or_else(|| {
+            Error::basic_parse_error(format!("Invalid PCIe generation in \"{link}\""))
+        })?;
+
+    let width = match parts.next() {
+        Some(raw_width) => Some(
+            raw_width
+                .strip_prefix(['x', 'X'])
+                .and_then(|width| width.parse().ok())
+                .ok_or_else(|| {
+                    Error::basic_parse_error(format!("Invalid PCIe link width in \"{link}\""))
+                })?,
+        ),
+        None => None,
+    };
+
+    if parts.next().is_some() {
+        return Err(Error::basic_parse_error(format!(
+            "Unexpected data in PCIe link \"{link}\""
+        )));
+    }
+
+    Ok((generation, width))
+}
+
+impl FromStr for PcieLevel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let raw = s.trim();
+        let (raw_speed, rest) = raw
+            .split_once(',')
+            .ok_or_else(|| Error::unexpected_eol(",", 1))?;
+
+        let transfer_rate_gts: f64 = raw_speed
+            .trim()
+            .strip_suffix("GT/s")
+            .ok_or_else(|| Error::basic_parse_error("Missing GT/s suffix in PCIe speed"))?
+            .parse()?;
+
+        let mut split = rest.split_whitespace();
+        let width = split
+            .next()
+            .and_then(|raw_width| raw_width.strip_prefix('x'))
+            .ok_or_else(|| Error::unexpected_eol("PCIe link width", 1))?
+            .parse()?;
+
+        let clockspeed = match split.next() {
+            Some(raw_clockspeed) => Some(
+                raw_clockspeed
+                    .to_lowercase()
+                    .strip_suffix("mhz")
+                    .ok_or_else(|| Error::basic_parse_error("Missing MHz suffix in PCIe clock"))?
+                    .parse()?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            transfer_rate: (transfer_rate_gts * 1000.0).round() as u32,
+            width,
+            clockspeed,
+            raw: raw.to_owned(),
+        })
+    }
+}
+
 macro_rules! impl_get_clocks_levels {
     ($name:ident, $level:expr, $out:ty) => {
         /// Gets clocks levels.
@@ -29,9 +213,11 @@ macro_rules! impl_get_clocks_levels {
 
 /// Type of a power level.
 #[allow(missing_docs)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[non_exhaustive]
 pub enum PowerLevelKind {
     CoreClock,
     MemoryClock,
@@ -39,6 +225,11 @@ pub enum PowerLevelKind {
     FabricClock,
     DCEFClock,
     PcieSpeed,
+    /// A DPM clock file not covered by the other variants, given by the full file name (e.g. `pp_dpm_vclk1`).
+    /// The values are expected to be in MHz.
+    /// Serialized as the plain file name, so it can also be used as a map key.
+    #[cfg_attr(feature = "serde", serde(untagged))]
+    Custom(String),
 }
 
 impl PowerLevelKind {
@@ -52,6 +243,7 @@ impl PowerLevelKind {
             FabricClock => "pp_dpm_fclk",
             DCEFClock => "pp_dpm_dcefclk",
             PcieSpeed => "pp_dpm_pcie",
+            Custom(filename) => filename,
         }
     }
 
@@ -59,8 +251,120 @@ impl PowerLevelKind {
     pub fn value_suffix(&self) -> Option<&str> {
         use PowerLevelKind::*;
         match self {
-            CoreClock | MemoryClock | SOCClock | FabricClock | DCEFClock => Some("mhz"),
+            CoreClock | MemoryClock | SOCClock | FabricClock | DCEFClock | Custom(_) => Some("mhz"),
             PcieSpeed => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_pcie_link, parse_raw_levels, PcieLevel, PowerLevelKind, PowerLevels};
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn power_level_kind_serde_roundtrip() {
+        for kind in [
+            PowerLevelKind::CoreClock,
+            PowerLevelKind::Custom("pp_dpm_vclk1".to_owned()),
+        ] {
+            let json = serde_json::to_string(&kind).unwrap();
+            assert_eq!(kind, serde_json::from_str(&json).unwrap());
+        }
+
+        let levels = BTreeMap::from([
+            (PowerLevelKind::MemoryClock, vec![1]),
+            (
+                PowerLevelKind::Custom("pp_dpm_vclk1".to_owned()),
+                vec![0, 2],
+            ),
+        ]);
+        let json = serde_json::to_string(&levels).unwrap();
+        assert_eq!(r#"{"memory_clock":[1],"pp_dpm_vclk1":[0,2]}"#, json);
+        assert_eq!(levels, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn parse_pcie_level() {
+        let level: PcieLevel = "8.0GT/s, x16".parse().unwrap();
+        assert_eq!(
+            level,
+            PcieLevel {
+                transfer_rate: 8000,
+                width: 16,
+                clockspeed: None,
+                raw: "8.0GT/s, x16".to_owned(),
+            }
+        );
+
+        let level: PcieLevel = "2.5GT/s, x1 310Mhz".parse().unwrap();
+        assert_eq!(level.transfer_rate, 2500);
+        assert_eq!(level.transfer_rate_gts(), 2.5);
+        assert_eq!(level.width, 1);
+        assert_eq!(level.clockspeed, Some(310));
+
+        assert!("16.0GT/s".parse::<PcieLevel>().is_err());
+        assert!("16.0, x16".parse::<PcieLevel>().is_err());
+    }
+
+    #[test]
+    fn power_levels_validity() {
+        let levels = PowerLevels {
+            levels: vec![500, 2660],
+            active: Some(0),
+        };
+        assert!(levels.is_valid());
+
+        let levels = PowerLevels {
+            levels: vec![0, 0],
+            active: None,
+        };
+        assert!(!levels.is_valid());
+        assert!(!PowerLevels::<u64>::default().is_valid());
+    }
+
+    #[test]
+    fn parse_raw_levels_unknown_format() {
+        let content = "0: 500Mhz\n1: 1.2 GHz *\nS: 2400MHz\n";
+        assert_eq!(
+            PowerLevels {
+                levels: vec![
+                    "500Mhz".to_owned(),
+                    "1.2 GHz".to_owned(),
+                    "S: 2400MHz".to_owned()
+                ],
+                active: Some(1),
+            },
+            parse_raw_levels(content)
+        );
+    }
+
+    #[test]
+    fn parse_raw_levels_multiple_active() {
+        let levels = parse_raw_levels("0: 0Mhz *\n1: 0Mhz *\n");
+        assert_eq!(vec!["0Mhz", "0Mhz"], levels.levels);
+        assert_eq!(None, levels.active);
+    }
+
+    #[test]
+    fn parse_pcie_links() {
+        assert_eq!((4, Some(16)), parse_pcie_link("Gen4 x16").unwrap());
+        assert_eq!((3, None), parse_pcie_link("gen3").unwrap());
+        assert_eq!((1, Some(8)), parse_pcie_link(" GEN1  X8 ").unwrap());
+        assert!(parse_pcie_link("Gen0 x16").is_err());
+        assert!(parse_pcie_link("Gen7").is_err());
+        assert!(parse_pcie_link("PCIe4 x16").is_err());
+        assert!(parse_pcie_link("Gen4 16").is_err());
+        assert!(parse_pcie_link("Gen4 x16 x8").is_err());
+        assert!(parse_pcie_link("").is_err());
+    }
+
+    #[test]
+    fn pcie_level_generation() {
+        let level: PcieLevel = "16.0GT/s, x16 619Mhz".parse().unwrap();
+        assert_eq!(Some(4), level.generation());
+        let level: PcieLevel = "2.5GT/s, x1".parse().unwrap();
+        assert_eq!(Some(1), level.generation());
+    }
+}
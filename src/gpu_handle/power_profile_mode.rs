@@ -1,18 +1,27 @@
 //! `pp-power-profile-mode`
-#![allow(missing_docs)] // temp
+//!
+//! All of the table formats reported by the kernel are parsed into a single [`PowerProfileModesTable`] model:
+//! a list of modes, each with one or more per-clock-type components of heuristic values.
 use crate::{
-    error::{Error, ErrorKind},
+    error::{Error, ErrorContext, ErrorKind},
+    gpu_handle::read_sysfs_lines,
     Result,
 };
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Write},
+    io::Read,
+};
 
 /// Table of predefined power profile modes
-
-/// https://kernel.org/doc/html/latest/gpu/amdgpu/thermal.html#pp-power-profile-mode
-#[derive(Debug)]
+///
+/// <https://kernel.org/doc/html/latest/gpu/amdgpu/thermal.html#pp-power-profile-mode>
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct PowerProfileModesTable {
     /// List of available modes
     pub modes: BTreeMap<u16, PowerProfile>,
@@ -20,49 +29,105 @@ pub struct PowerProfileModesTable {
     pub value_names: Vec<String>,
     /// The currently active mode
     pub active: u16,
+    /// The layout which the table was parsed from.
+    /// Missing in tables serialized by older versions, which are assumed to be nested.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub format: PowerProfileModesFormat,
 }
 
-#[derive(Debug)]
+/// Layout of the `pp_power_profile_mode` file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PowerProfileModesFormat {
+    /// One row per mode with a single set of values. Used by pre-RDNA GPUs.
+    Flat,
+    /// One row per mode followed by a row for each clock type. Used by RDNA and newer.
+    #[default]
+    Nested,
+    /// Columns as modes and rows as values. Used at least by RDNA3 laptop GPUs.
+    Rotated,
+    /// Only the list of mode names without any values. Used by integrated GPUs.
+    Basic,
+}
+
+/// A single power profile mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct PowerProfile {
+    /// Name of the profile as reported by the kernel
     pub name: String,
     /// On RDNA and newer, each profile has multiple components for different clock types.
     /// Older generations have only one set of values.
     pub components: Vec<PowerProfileComponent>,
 }
 
-#[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize, Default, Clone))]
+/// Heuristic values of a power profile for a specific clock type.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct PowerProfileComponent {
     /// Filled on RDNA and newer
     pub clock_type: Option<String>,
+    /// Heuristic values, matching the names in [`PowerProfileModesTable::value_names`].
+    /// Values which are not applicable are empty.
     pub values: Vec<Option<i32>>,
 }
 
 impl PowerProfileModesTable {
     /// Parse the table from a given string
     pub fn parse(s: &str) -> Result<Self> {
-        let mut lines = s.lines().map(|line| line.split_whitespace());
+        Self::parse_lines(s.lines())
+    }
 
-        let mut split = lines
+    /// Parses the table from a reader, such as a file with a captured `pp_power_profile_mode` dump.
+    ///
+    /// Only the first two lines are needed to detect the table layout, the rest is parsed as it is read.
+    pub fn parse_from_reader<R: Read>(reader: R) -> Result<Self> {
+        let mut error = None;
+        let table = Self::parse_lines(read_sysfs_lines(reader, &mut error));
+        error
+            .map_or(Ok(()), Err)
+            .context("Could not read power profile modes table")?;
+        table
+    }
+
+    fn parse_lines<I, S>(lines: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut lines = lines.into_iter();
+
+        let header = lines
             .next()
             .ok_or_else(|| Error::unexpected_eol("Power profile line", 1))?;
-        let start = split
+        let header = header.as_ref();
+        let start = header
+            .split_whitespace()
             .next()
             .ok_or_else(|| Error::unexpected_eol("Value description", 1))?;
 
         match start {
-            "NUM" => Self::parse_flat(s),
-            "PROFILE_INDEX(NAME)" => Self::parse_nested(s),
+            "NUM" => Self::parse_flat(header, lines),
+            "PROFILE_INDEX(NAME)" => Self::parse_nested(header, lines),
             _ if start.parse::<u16>().is_ok() => {
-                if lines
-                    .next()
-                    .and_then(|mut line| line.next())
-                    .is_some_and(|term| term.parse::<u16>().is_ok())
-                {
-                    Self::parse_basic(s)
+                let second_line = lines.next();
+                let is_basic = second_line
+                    .as_ref()
+                    .and_then(|line| line.as_ref().split_whitespace().next())
+                    .is_some_and(|term| term.parse::<u16>().is_ok());
+                let rows = second_line.into_iter().chain(lines);
+
+                if is_basic {
+                    Self::parse_basic(header, rows)
                 } else {
-                    Self::parse_rotated(s)
+                    Self::parse_rotated(header, rows)
                 }
             }
             _ => Err(Error::basic_parse_error(
@@ -71,17 +136,270 @@ impl PowerProfileModesTable {
         }
     }
 
+    /// Gets the currently active profile.
+    pub fn active_mode(&self) -> Option<&PowerProfile> {
+        self.modes.get(&self.active)
+    }
+
+    /// Gets the name of the currently active profile.
+    pub fn active_name(&self) -> Option<&str> {
+        self.active_mode().map(|mode| mode.name.as_str())
+    }
+
+    /// Finds the index and the profile matching the given standard profile type.
+    pub fn find_profile(&self, profile: &StandardProfile) -> Option<(u16, &PowerProfile)> {
+        self.modes
+            .iter()
+            .find(|(_, mode)| mode.standard_profile() == *profile)
+            .map(|(index, mode)| (*index, mode))
+    }
+
+    /// Finds the index and the profile with the given name (case-insensitive).
+    pub fn find_profile_by_name(&self, name: &str) -> Option<(u16, &PowerProfile)> {
+        self.modes
+            .iter()
+            .find(|(_, mode)| mode.name.eq_ignore_ascii_case(name))
+            .map(|(index, mode)| (*index, mode))
+    }
+
+    /// Finds the custom profile and its index.
+    pub fn find_custom_profile(&self) -> Result<(u16, &PowerProfile)> {
+        self.find_profile(&StandardProfile::Custom).ok_or_else(|| {
+            ErrorKind::NotAllowed("Could not find a custom power profile".to_owned()).into()
+        })
+    }
+
+    /// Captures the current heuristics of the custom profile.
+    /// They can be applied again with [`PowerProfileModesTable::custom_heuristics_commands_by_name`].
+    pub fn custom_heuristics(&self) -> Result<CustomHeuristics> {
+        let (_, custom_profile) = self.find_custom_profile()?;
+
+        let components = custom_profile
+            .components
+            .iter()
+            .map(|component| NamedHeuristics {
+                clock_type: component.clock_type.clone(),
+                values: self
+                    .value_names
+                    .iter()
+                    .zip(&component.values)
+                    .filter_map(|(name, value)| Some((name.clone(), (*value)?)))
+                    .collect(),
+            })
+            .collect();
+
+        Ok(CustomHeuristics { components })
+    }
+
+    /// Generates the commands for setting the heuristics of the custom profile,
+    /// with the values for every component given by position.
+    pub fn custom_heuristics_commands(
+        &self,
+        components: &[Vec<Option<i32>>],
+    ) -> Result<Vec<String>> {
+        let updates = self.custom_heuristics_updates(components)?;
+        self.custom_heuristics_update_commands(&updates)
+    }
+
+    /// Generates the commands for setting the heuristics of the custom profile by name.
+    ///
+    /// The clock types and value names are validated against the table.
+    /// Values which are not specified keep their current value, and components which are not specified are not changed.
+    pub fn custom_heuristics_commands_by_name(
+        &self,
+        heuristics: &[NamedHeuristics],
+    ) -> Result<Vec<String>> {
+        let updates = self.named_heuristics_updates(heuristics)?;
+        self.custom_heuristics_update_commands(&updates)
+    }
+
+    /// Validates positional heuristics and turns them into a list of component indices with their new values.
+    pub(crate) fn custom_heuristics_updates(
+        &self,
+        components: &[Vec<Option<i32>>],
+    ) -> Result<Vec<(usize, Vec<Option<i32>>)>> {
+        let (_, custom_profile) = self.find_custom_profile()?;
+
+        let component_names: Vec<String> = custom_profile
+            .components
+            .iter()
+            .enumerate()
+            .map(|(i, component)| match &component.clock_type {
+                Some(clock_type) => clock_type.clone(),
+                None => format!("#{i}"),
+            })
+            .collect();
+
+        if custom_profile.components.len() != components.len() {
+            return Err(ErrorKind::NotAllowed(format!(
+                "Expected {} power profile components ({}), got {}",
+                custom_profile.components.len(),
+                component_names.join(", "),
+                components.len()
+            ))
+            .into());
+        }
+
+        for ((component, values), component_name) in custom_profile
+            .components
+            .iter()
+            .zip(components)
+            .zip(&component_names)
+        {
+            if component.values.len() != values.len() {
+                return Err(ErrorKind::NotAllowed(format!(
+                    "Component {component_name} expects {} heuristic values ({}), got {}",
+                    component.values.len(),
+                    self.value_names.join(", "),
+                    values.len()
+                ))
+                .into());
+            }
+        }
+
+        Ok(components.iter().cloned().enumerate().collect())
+    }
+
+    /// Resolves named heuristics into a list of component indices with their new values.
+    pub(crate) fn named_heuristics_updates(
+        &self,
+        heuristics: &[NamedHeuristics],
+    ) -> Result<Vec<(usize, Vec<Option<i32>>)>> {
+        let (_, custom_profile) = self.find_custom_profile()?;
+        let component_count = custom_profile.components.len();
+
+        let mut updates = Vec::with_capacity(heuristics.len());
+
+        for named in heuristics {
+            let component_index = match &named.clock_type {
+                Some(clock_type) => custom_profile
+                    .components
+                    .iter()
+                    .position(|component| {
+                        component
+                            .clock_type
+                            .as_deref()
+                            .is_some_and(|name| name.eq_ignore_ascii_case(clock_type))
+                    })
+                    .ok_or_else(|| {
+                        let available: Vec<&str> = custom_profile
+                            .components
+                            .iter()
+                            .filter_map(|component| component.clock_type.as_deref())
+                            .collect();
+                        ErrorKind::NotAllowed(format!(
+                            "Unknown clock type {clock_type}, available clock types: {available:?}"
+                        ))
+                    })?,
+                None if component_count == 1 => 0,
+                None => {
+                    return Err(ErrorKind::NotAllowed(format!(
+                        "The clock type must be specified, the custom profile has {component_count} components"
+                    ))
+                    .into())
+                }
+            };
+
+            let mut values = custom_profile.components[component_index].values.clone();
+            for (name, value) in &named.values {
+                let value_index = self
+                    .value_names
+                    .iter()
+                    .position(|value_name| value_name.eq_ignore_ascii_case(name))
+                    .filter(|value_index| *value_index < values.len())
+                    .ok_or_else(|| {
+                        ErrorKind::NotAllowed(format!(
+                            "Unknown heuristic {name}, available heuristics: {:?}",
+                            self.value_names
+                        ))
+                    })?;
+                values[value_index] = Some(*value);
+            }
+
+            updates.push((component_index, values));
+        }
+
+        Ok(updates)
+    }
+
+    /// Generates the commands for the given component updates of the custom profile.
+    pub(crate) fn custom_heuristics_update_commands(
+        &self,
+        updates: &[(usize, Vec<Option<i32>>)],
+    ) -> Result<Vec<String>> {
+        let (index, custom_profile) = self.find_custom_profile()?;
+        let component_count = custom_profile.components.len();
+
+        let mut commands = Vec::with_capacity(updates.len());
+        for (component_index, values) in updates {
+            let component_index = (component_count > 1).then_some(*component_index);
+            commands.extend(self.component_commands(index, component_index, values)?);
+        }
+        Ok(commands)
+    }
+
+    /// Stores the given component updates in the custom profile, to reflect values which were written to the GPU.
+    pub(crate) fn apply_custom_heuristics_updates(
+        &mut self,
+        updates: Vec<(usize, Vec<Option<i32>>)>,
+    ) {
+        if let Some(custom_profile) = self.modes.values_mut().find(|mode| mode.is_custom()) {
+            for (component_index, values) in updates {
+                if let Some(component) = custom_profile.components.get_mut(component_index) {
+                    component.values = values;
+                }
+            }
+        }
+    }
+
+    /// Generates the commands for a single component of a profile.
+    ///
+    /// The rotated format has all values in a single component, but the kernel expects them to be written
+    /// separately for each clock type, which is given by the value name prefix (e.g. `Gfx_` or `Fclk_`).
+    fn component_commands(
+        &self,
+        index: u16,
+        component_index: Option<usize>,
+        values: &[Option<i32>],
+    ) -> Result<Vec<String>> {
+        if self.format != PowerProfileModesFormat::Rotated {
+            return Ok(vec![heuristics_command(index, component_index, values)]);
+        }
+
+        if values.len() != self.value_names.len() {
+            return Err(ErrorKind::NotAllowed(format!(
+                "Expected {} heuristic values, got {}",
+                self.value_names.len(),
+                values.len()
+            ))
+            .into());
+        }
+
+        let mut commands = vec![];
+        let mut group_start = 0;
+        for (i, name) in self.value_names.iter().enumerate() {
+            let next_prefix = self
+                .value_names
+                .get(i + 1)
+                .map(|next_name| value_name_prefix(next_name));
+            if next_prefix != Some(value_name_prefix(name)) {
+                commands.push(heuristics_command(
+                    index,
+                    Some(commands.len()),
+                    &values[group_start..=i],
+                ));
+                group_start = i + 1;
+            }
+        }
+        Ok(commands)
+    }
+
     /// Parse the format used by pre-RDNA GPUs
-    fn parse_flat(s: &str) -> Result<Self> {
+    fn parse_flat<S: AsRef<str>>(header: &str, rows: impl Iterator<Item = S>) -> Result<Self> {
         let mut modes = BTreeMap::new();
         let mut active = None;
 
-        let mut lines = s.lines();
-
-        let header_line = lines
-            .next()
-            .ok_or_else(|| Error::unexpected_eol("Info header", 1))?;
-        let mut header_split = header_line.split_whitespace();
+        let mut header_split = header.split_whitespace();
 
         if header_split.next() != Some("NUM") {
             return Err(
@@ -97,12 +415,12 @@ impl PowerProfileModesTable {
 
         let value_names: Vec<String> = header_split.map(str::to_owned).collect();
 
-        for (line, row) in s.lines().map(str::trim).enumerate() {
-            let mut split = row.split_whitespace().peekable();
+        for (line, row) in (2..).zip(rows) {
+            let mut split = row.as_ref().split_whitespace().peekable();
             if let Some(num) = split.next().and_then(|part| part.parse::<u16>().ok()) {
                 let name_part = split
                     .next()
-                    .ok_or_else(|| Error::unexpected_eol("Mode name", line + 1))?
+                    .ok_or_else(|| Error::unexpected_eol("Mode name", line))?
                     .trim_end_matches(':');
 
                 // Handle space within the mode name:
@@ -131,7 +449,7 @@ impl PowerProfileModesTable {
                             let parsed = value.parse().map_err(|_| {
                                 Error::from(ErrorKind::ParseError {
                                     msg: format!("Expected an integer, got '{value}'"),
-                                    line: line + 1,
+                                    line,
                                 })
                             })?;
                             Ok(Some(parsed))
@@ -154,20 +472,16 @@ impl PowerProfileModesTable {
             modes,
             value_names,
             active: active.ok_or_else(|| Error::basic_parse_error("No active level found"))?,
+            format: PowerProfileModesFormat::Flat,
         })
     }
 
     /// Parse the format used by RDNA and higher
-    fn parse_nested(s: &str) -> Result<Self> {
+    fn parse_nested<S: AsRef<str>>(header: &str, rows: impl Iterator<Item = S>) -> Result<Self> {
         let mut modes = BTreeMap::new();
         let mut active = None;
 
-        let mut lines = s.lines();
-
-        let header_line = lines
-            .next()
-            .ok_or_else(|| Error::unexpected_eol("Info header", 1))?;
-        let mut header_split = header_line.split_whitespace();
+        let mut header_split = header.split_whitespace();
 
         if header_split.next() != Some("PROFILE_INDEX(NAME)") {
             return Err(ErrorKind::Unsupported(
@@ -184,8 +498,9 @@ impl PowerProfileModesTable {
 
         let value_names: Vec<String> = header_split.map(str::to_owned).collect();
 
-        let mut lines = lines.map(str::trim).enumerate().peekable();
+        let mut lines = rows.enumerate().peekable();
         while let Some((line, row)) = lines.next() {
+            let row = row.as_ref().trim();
             if row.contains('(') {
                 return Err(ErrorKind::ParseError {
                     msg: format!("Unexpected mode heuristics line '{row}'"),
@@ -212,9 +527,10 @@ impl PowerProfileModesTable {
 
                 while lines
                     .peek()
-                    .is_some_and(|(_, row)| row.contains(['(', ')']))
+                    .is_some_and(|(_, row)| row.as_ref().contains(['(', ')']))
                 {
                     let (line, clock_type_line) = lines.next().unwrap();
+                    let clock_type_line = clock_type_line.as_ref().trim();
 
                     let name_start = clock_type_line
                         .char_indices()
@@ -264,22 +580,17 @@ impl PowerProfileModesTable {
             modes,
             value_names,
             active: active.ok_or_else(|| Error::basic_parse_error("No active level found"))?,
+            format: PowerProfileModesFormat::Nested,
         })
     }
 
     /// Parse "rotated" format (with columns as profiles, and rows as values).
     /// Used at least by RDNA3 laptop GPUs (example data: 7700s)
-    fn parse_rotated(s: &str) -> Result<Self> {
+    fn parse_rotated<S: AsRef<str>>(header: &str, rows: impl Iterator<Item = S>) -> Result<Self> {
         let mut modes = BTreeMap::new();
         let mut active = None;
 
-        let mut lines = s.lines().map(str::trim).enumerate();
-
-        let mut header_split = lines
-            .next()
-            .ok_or_else(|| Error::basic_parse_error("Missing header"))?
-            .1
-            .split_whitespace();
+        let mut header_split = header.split_whitespace();
 
         while let Some(raw_index) = header_split.next() {
             let index: u16 = raw_index.parse()?;
@@ -304,11 +615,11 @@ impl PowerProfileModesTable {
 
         let mut value_names = vec![];
 
-        for (i, line) in lines {
-            let mut split = line.split_whitespace();
+        for (i, line) in (2..).zip(rows) {
+            let mut split = line.as_ref().split_whitespace();
             let value_name = split
                 .next()
-                .ok_or_else(|| Error::unexpected_eol("Value name", i + 1))?;
+                .ok_or_else(|| Error::unexpected_eol("Value name", i))?;
 
             value_names.push(value_name.to_owned());
 
@@ -338,20 +649,22 @@ impl PowerProfileModesTable {
             modes,
             value_names,
             active: active.ok_or_else(|| Error::basic_parse_error("No active level found"))?,
+            format: PowerProfileModesFormat::Rotated,
         })
     }
 
     /// Parse the format used by integrated GPUs
-    fn parse_basic(s: &str) -> Result<Self> {
+    fn parse_basic<S: AsRef<str>>(header: &str, rows: impl Iterator<Item = S>) -> Result<Self> {
         let mut modes = BTreeMap::new();
         let mut active = None;
 
-        for (line, row) in s.lines().map(str::trim).enumerate() {
+        // The header is already the first mode
+        let mut parse_row = |line, row: &str| -> Result<()> {
             let mut split = row.split_whitespace();
             if let Some(num) = split.next().and_then(|part| part.parse::<u16>().ok()) {
                 let name_part = split
                     .next()
-                    .ok_or_else(|| Error::unexpected_eol("No name after mode number", line + 1))?;
+                    .ok_or_else(|| Error::unexpected_eol("No name after mode number", line))?;
 
                 let name = if let Some(name) = name_part.strip_suffix('*') {
                     active = Some(num);
@@ -368,26 +681,234 @@ impl PowerProfileModesTable {
                     },
                 );
             }
+            Ok(())
+        };
+
+        parse_row(1, header)?;
+        for (line, row) in (2..).zip(rows) {
+            parse_row(line, row.as_ref())?;
         }
 
         Ok(Self {
             modes,
             value_names: vec![],
             active: active.ok_or_else(|| Error::basic_parse_error("No active level found"))?,
+            format: PowerProfileModesFormat::Basic,
         })
     }
 }
 
+/// Saved heuristics of the custom power profile, see [`PowerProfileModesTable::custom_heuristics`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CustomHeuristics {
+    /// Values of each component of the profile
+    pub components: Vec<NamedHeuristics>,
+}
+
+/// Heuristic values for a single component of a power profile, keyed by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct NamedHeuristics {
+    /// Clock type of the component, matching [`PowerProfileComponent::clock_type`].
+    /// Can be left empty if the profile has only one component.
+    pub clock_type: Option<String>,
+    /// Values keyed by the names in [`PowerProfileModesTable::value_names`]
+    pub values: BTreeMap<String, i32>,
+}
+
+/// Gets the clock type prefix of a heuristic name in the rotated format, e.g. `Gfx` in `Gfx_FPS`
+fn value_name_prefix(name: &str) -> &str {
+    name.split_once('_').map_or(name, |(prefix, _)| prefix)
+}
+
+fn heuristics_command(
+    index: u16,
+    component_index: Option<usize>,
+    values: &[Option<i32>],
+) -> String {
+    let mut command = match component_index {
+        Some(component_index) => format!("{index} {component_index}"),
+        None => format!("{index}"),
+    };
+    for value in values {
+        match value {
+            Some(value) => write!(command, " {value}").unwrap(),
+            None => command.push_str(" -"),
+        }
+    }
+    command.push('\n');
+    command
+}
+
+/// Formats the table in the same layout as it was parsed from.
+impl Display for PowerProfileModesTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let active_marker = |i: u16| if i == self.active { "*" } else { " " };
+
+        match self.format {
+            PowerProfileModesFormat::Flat => {
+                write!(f, "NUM        MODE_NAME")?;
+                for name in &self.value_names {
+                    write!(f, " {name}")?;
+                }
+                writeln!(f)?;
+
+                for (i, profile) in &self.modes {
+                    write!(f, "{i:>3} {:>14}{}:", profile.name, active_marker(*i))?;
+                    let values = profile
+                        .components
+                        .first()
+                        .map(|component| component.values.as_slice())
+                        .unwrap_or_default();
+                    for (value, name) in values.iter().zip(&self.value_names) {
+                        write!(f, " {:>width$}", FormatValue(*value), width = name.len())?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+            PowerProfileModesFormat::Nested => {
+                write!(f, "PROFILE_INDEX(NAME) CLOCK_TYPE(NAME)")?;
+                for name in &self.value_names {
+                    write!(f, " {name}")?;
+                }
+                writeln!(f)?;
+
+                for (i, profile) in &self.modes {
+                    writeln!(f, "{i:>2} {:>14}{}:", profile.name, active_marker(*i))?;
+                    for (component_i, component) in profile.components.iter().enumerate() {
+                        let clock_type = component.clock_type.as_deref().unwrap_or_default();
+                        write!(f, "{:>20}({clock_type:>13})", component_i)?;
+                        for value in &component.values {
+                            write!(f, " {:>7}", FormatValue(*value))?;
+                        }
+                        writeln!(f)?;
+                    }
+                }
+            }
+            PowerProfileModesFormat::Rotated => {
+                write!(f, "{:30}", "")?;
+                for (i, profile) in &self.modes {
+                    let name = format!("{}{}", profile.name, active_marker(*i));
+                    write!(f, "{i:<2}{name:<16}")?;
+                }
+                writeln!(f)?;
+
+                for (value_i, name) in self.value_names.iter().enumerate() {
+                    write!(f, "{name:<30}")?;
+                    for profile in self.modes.values() {
+                        let value = profile
+                            .components
+                            .first()
+                            .and_then(|component| component.values.get(value_i))
+                            .copied()
+                            .flatten();
+                        write!(f, "{:<18}", FormatValue(value))?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+            PowerProfileModesFormat::Basic => {
+                for (i, profile) in &self.modes {
+                    writeln!(f, "{i:>2} {:>14}{}", profile.name, active_marker(*i))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct FormatValue(Option<i32>);
+
+impl Display for FormatValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(value) => value.to_string().fmt(f),
+            None => "-".fmt(f),
+        }
+    }
+}
+
 impl PowerProfile {
     /// If this is the custom profile (checked by name)
     pub fn is_custom(&self) -> bool {
-        self.name.eq_ignore_ascii_case("CUSTOM")
+        self.standard_profile() == StandardProfile::Custom
+    }
+
+    /// Gets the standard profile type based on the name.
+    pub fn standard_profile(&self) -> StandardProfile {
+        StandardProfile::from(self.name.as_str())
+    }
+}
+
+/// Power profile types defined by the kernel.
+///
+/// The indices of the profiles differ between GPU generations, so they should be looked up by type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum StandardProfile {
+    /// `BOOTUP_DEFAULT`
+    BootupDefault,
+    /// `3D_FULL_SCREEN`
+    FullScreen3D,
+    /// `POWER_SAVING`
+    PowerSaving,
+    /// `VIDEO`
+    Video,
+    /// `VR`
+    VR,
+    /// `COMPUTE`
+    Compute,
+    /// `CUSTOM`
+    Custom,
+    /// A profile not known by this library, with its original name
+    Unknown(String),
+}
+
+impl From<&str> for StandardProfile {
+    fn from(name: &str) -> Self {
+        match name.to_ascii_uppercase().as_str() {
+            "BOOTUP_DEFAULT" => Self::BootupDefault,
+            "3D_FULL_SCREEN" => Self::FullScreen3D,
+            "POWER_SAVING" => Self::PowerSaving,
+            "VIDEO" => Self::Video,
+            "VR" => Self::VR,
+            "COMPUTE" => Self::Compute,
+            "CUSTOM" => Self::Custom,
+            _ => Self::Unknown(name.to_owned()),
+        }
+    }
+}
+
+/// Formats the profile with the name used by the kernel.
+impl Display for StandardProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::BootupDefault => "BOOTUP_DEFAULT",
+            Self::FullScreen3D => "3D_FULL_SCREEN",
+            Self::PowerSaving => "POWER_SAVING",
+            Self::Video => "VIDEO",
+            Self::VR => "VR",
+            Self::Compute => "COMPUTE",
+            Self::Custom => "CUSTOM",
+            Self::Unknown(name) => name,
+        };
+        f.write_str(name)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::PowerProfileModesTable;
+    use super::{
+        NamedHeuristics, PowerProfileModesFormat, PowerProfileModesTable, StandardProfile,
+    };
+    use crate::{error::ErrorKind, tests::whitespace_variants};
     use insta::assert_yaml_snapshot;
 
     const TABLE_VEGA56: &str = include_test_data!("vega56/pp_power_profile_mode");
@@ -403,6 +924,53 @@ mod tests {
         assert_yaml_snapshot!(table);
     }
 
+    #[test]
+    fn parse_irregular_whitespace() {
+        for data in [
+            TABLE_VEGA56,
+            TABLE_RX580,
+            TABLE_4800H,
+            TABLE_RX6900XT,
+            TABLE_RX7700S,
+            TABLE_RX7800XT,
+        ] {
+            let expected = PowerProfileModesTable::parse(data).unwrap();
+            for variant in whitespace_variants(data) {
+                assert_eq!(
+                    expected,
+                    PowerProfileModesTable::parse(&variant).unwrap(),
+                    "{variant:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parse_from_reader() {
+        for data in [TABLE_RX580, TABLE_4800H, TABLE_RX6900XT, TABLE_RX7700S] {
+            let table = PowerProfileModesTable::parse_from_reader(data.as_bytes()).unwrap();
+            assert_eq!(PowerProfileModesTable::parse(data).unwrap(), table);
+        }
+    }
+
+    #[test]
+    fn deserialize_without_format() {
+        let table = PowerProfileModesTable::parse(TABLE_RX6900XT).unwrap();
+        let mut value = serde_json::to_value(&table).unwrap();
+        value.as_object_mut().unwrap().remove("format").unwrap();
+
+        let deserialized: PowerProfileModesTable = serde_json::from_value(value).unwrap();
+        assert_eq!(PowerProfileModesFormat::Nested, deserialized.format);
+        assert_eq!(table, deserialized);
+    }
+
+    #[test]
+    fn parse_from_reader_io_error() {
+        let data = [TABLE_RX580.as_bytes(), &[0xff, b'\n']].concat();
+        let err = PowerProfileModesTable::parse_from_reader(data.as_slice()).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::IoError(_)), "{err:?}");
+    }
+
     #[test]
     fn parse_full_rx580() {
         let table = PowerProfileModesTable::parse(TABLE_RX580).unwrap();
@@ -427,6 +995,217 @@ mod tests {
         assert_yaml_snapshot!(table);
     }
 
+    #[test]
+    fn standard_profiles() {
+        let table = PowerProfileModesTable::parse(TABLE_RX7800XT).unwrap();
+        let profiles: Vec<StandardProfile> = table
+            .modes
+            .values()
+            .map(|profile| profile.standard_profile())
+            .collect();
+        assert_eq!(
+            profiles,
+            [
+                StandardProfile::BootupDefault,
+                StandardProfile::FullScreen3D,
+                StandardProfile::PowerSaving,
+                StandardProfile::Video,
+                StandardProfile::VR,
+                StandardProfile::Compute,
+                StandardProfile::Custom,
+                StandardProfile::Unknown("WINDOW_3D".to_owned()),
+            ]
+        );
+        assert!(table.modes[&6].is_custom());
+        assert_eq!(table.find_profile_by_name("window_3d").unwrap().0, 7);
+        assert_eq!(table.active_name(), Some("BOOTUP_DEFAULT"));
+        assert_eq!(
+            table.active_mode().map(|mode| mode.standard_profile()),
+            Some(StandardProfile::BootupDefault)
+        );
+        assert_eq!(table.find_profile(&StandardProfile::Compute).unwrap().0, 5);
+        assert_eq!(
+            table
+                .find_profile(&StandardProfile::Unknown("window_3d".to_owned()))
+                .map(|(index, _)| index),
+            None
+        );
+        assert_eq!(StandardProfile::from("custom"), StandardProfile::Custom);
+        assert_eq!(StandardProfile::FullScreen3D.to_string(), "3D_FULL_SCREEN");
+    }
+
+    #[test]
+    fn custom_heuristics_commands() {
+        let table = PowerProfileModesTable::parse(TABLE_RX580).unwrap();
+        let commands = table
+            .custom_heuristics_commands(&[vec![
+                Some(0),
+                Some(5),
+                None,
+                Some(10),
+                Some(60),
+                Some(25),
+            ]])
+            .unwrap();
+        assert_eq!(commands, ["6 0 5 - 10 60 25\n"]);
+        assert!(table.custom_heuristics_commands(&[vec![], vec![]]).is_err());
+    }
+
+    #[test]
+    fn custom_heuristics_capture() {
+        let table = PowerProfileModesTable::parse(TABLE_RX6900XT).unwrap();
+        let heuristics = table.custom_heuristics().unwrap();
+        assert_eq!(heuristics.components.len(), 3);
+        assert_eq!(
+            heuristics.components[2].clock_type.as_deref(),
+            Some("MEMLK")
+        );
+        assert_eq!(heuristics.components[2].values["BoosterFreq"], 800);
+
+        let commands = table
+            .custom_heuristics_commands_by_name(&heuristics.components)
+            .unwrap();
+        assert_eq!(
+            commands,
+            [
+                "6 0 0 5 1 0 4 800 4587520 -65536 0\n",
+                "6 1 0 5 1 0 1 0 3276800 -65536 -6553\n",
+                "6 2 0 5 1 0 4 800 327680 -65536 0\n",
+            ]
+        );
+
+        let table = PowerProfileModesTable::parse(TABLE_RX580).unwrap();
+        let heuristics = table.custom_heuristics().unwrap();
+        assert!(heuristics.components[0].values.is_empty());
+    }
+
+    #[test]
+    fn custom_heuristics_mismatch_messages() {
+        let table = PowerProfileModesTable::parse(TABLE_RX6900XT).unwrap();
+
+        let err = table.custom_heuristics_commands(&[vec![]]).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::NotAllowed(String::new()),);
+        assert!(err
+            .to_string()
+            .contains("Expected 3 power profile components (GFXCLK, SOCCLK, MEMLK), got 1"));
+
+        let err = table
+            .custom_heuristics_commands(&[vec![Some(0); 9], vec![Some(0); 2], vec![Some(0); 9]])
+            .unwrap_err();
+        assert!(err.to_string().contains(
+            "Component SOCCLK expects 9 heuristic values (FPS, MinFreqType, MinActiveFreqType, MinActiveFreq, BoosterFreqType, BoosterFreq, PD_Data_limit_c, PD_Data_error_coeff, PD_Data_error_rate_coeff), got 2"
+        ));
+    }
+
+    #[test]
+    fn custom_heuristics_by_name_flat() {
+        let table = PowerProfileModesTable::parse(TABLE_RX580).unwrap();
+        let heuristics = NamedHeuristics {
+            clock_type: None,
+            values: [
+                ("sclk_up_hyst".to_owned(), 10),
+                ("MCLK_ACTIVE_LEVEL".to_owned(), 25),
+            ]
+            .into(),
+        };
+        let commands = table
+            .custom_heuristics_commands_by_name(&[heuristics])
+            .unwrap();
+        assert_eq!(commands, ["6 10 - - - - 25\n"]);
+
+        let unknown = NamedHeuristics {
+            clock_type: None,
+            values: [("SCLK_UP".to_owned(), 10)].into(),
+        };
+        assert!(table
+            .custom_heuristics_commands_by_name(&[unknown])
+            .is_err());
+    }
+
+    #[test]
+    fn custom_heuristics_by_name_nested() {
+        let table = PowerProfileModesTable::parse(TABLE_RX6900XT).unwrap();
+        let heuristics = NamedHeuristics {
+            clock_type: Some("socclk".to_owned()),
+            values: [("MinActiveFreq".to_owned(), 500)].into(),
+        };
+        let commands = table
+            .custom_heuristics_commands_by_name(&[heuristics])
+            .unwrap();
+        assert_eq!(commands, ["6 1 0 5 1 500 1 0 3276800 -65536 -6553\n"]);
+
+        let missing_clock_type = NamedHeuristics {
+            clock_type: None,
+            values: [("FPS".to_owned(), 60)].into(),
+        };
+        assert!(table
+            .custom_heuristics_commands_by_name(&[missing_clock_type])
+            .is_err());
+
+        let unknown_clock_type = NamedHeuristics {
+            clock_type: Some("FCLK".to_owned()),
+            values: [("FPS".to_owned(), 60)].into(),
+        };
+        assert!(table
+            .custom_heuristics_commands_by_name(&[unknown_clock_type])
+            .is_err());
+    }
+
+    #[test]
+    fn custom_heuristics_rotated() {
+        let table = PowerProfileModesTable::parse(TABLE_RX7700S).unwrap();
+        let heuristics = NamedHeuristics {
+            clock_type: None,
+            values: [
+                ("Gfx_FPS".to_owned(), 60),
+                ("Fclk_BoosterFreq".to_owned(), 1000),
+            ]
+            .into(),
+        };
+        let commands = table
+            .custom_heuristics_commands_by_name(&[heuristics])
+            .unwrap();
+        assert_eq!(
+            commands,
+            ["6 0 0 0 60 1 4 0 800\n", "6 1 0 0 0 3 1 0 1000\n"]
+        );
+
+        let values = table.modes[&6].components[0].values.clone();
+        let commands = table.custom_heuristics_commands(&[values]).unwrap();
+        assert_eq!(commands, ["6 0 0 0 0 1 4 0 800\n", "6 1 0 0 0 3 1 0 0\n"]);
+        assert!(table.custom_heuristics_commands(&[vec![Some(0)]]).is_err());
+    }
+
+    #[test]
+    fn format_roundtrip() {
+        for data in [
+            TABLE_VEGA56,
+            TABLE_RX580,
+            TABLE_4800H,
+            TABLE_RX6900XT,
+            TABLE_RX7700S,
+            TABLE_RX7800XT,
+        ] {
+            let table = PowerProfileModesTable::parse(data).unwrap();
+            let formatted = table.to_string();
+            let reparsed_table = PowerProfileModesTable::parse(&formatted).unwrap();
+            assert_eq!(table, reparsed_table, "formatted table:\n{formatted}");
+        }
+    }
+
+    #[test]
+    fn format_vega56() {
+        let table = PowerProfileModesTable::parse(TABLE_VEGA56).unwrap();
+        assert_eq!(
+            table.to_string().lines().take(3).collect::<Vec<_>>(),
+            [
+                "NUM        MODE_NAME BUSY_SET_POINT FPS USE_RLC_BUSY MIN_ACTIVE_LEVEL",
+                "  0 BOOTUP_DEFAULT*:             70  60            0                0",
+                "  1 3D_FULL_SCREEN :             70  60            1                3",
+            ]
+        );
+    }
+
     #[test]
     fn parse_full_rx7800xt() {
         let table = PowerProfileModesTable::parse(TABLE_RX7800XT).unwrap();
@@ -0,0 +1,106 @@
+//! Known hardware quirks, which adjust how values are read, validated and written on specific GPUs.
+//!
+//! Quirks are matched by the PCI device id when creating a [`GpuHandle`](super::GpuHandle),
+//! and more can be added with [`GpuHandle::add_quirk`](super::GpuHandle::add_quirk).
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A workaround for a hardware or firmware behaviour.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Quirk {
+    /// The firmware may report fan curve points outside of the allowed ranges.
+    /// Such points are clamped into the ranges when reading the curve, so that it can be applied back.
+    FanCurvePointsOutOfRange,
+    /// The voltage offset (in mV) must not go further from 0 than the given value,
+    /// even if the reported allowed range is wider.
+    MaxVoltageOffset(i32),
+    /// Memory clock commands must be written before core clock commands in `pp_od_clk_voltage`.
+    MclkBeforeSclk,
+}
+
+/// An entry in the quirks table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuirkEntry {
+    /// PCI vendor id, in the same format as reported by [`GpuHandle::get_pci_id`](super::GpuHandle::get_pci_id)
+    pub vendor_id: &'static str,
+    /// PCI device id
+    pub device_id: &'static str,
+    /// The quirk applied to matching GPUs
+    pub quirk: Quirk,
+}
+
+/// Quirks which are applied automatically.
+pub const BUILTIN_QUIRKS: &[QuirkEntry] = &[
+    // Navi 3x can report fan curve points below the allowed temperature range,
+    // for example when only some of the points were configured
+    QuirkEntry {
+        vendor_id: "1002",
+        device_id: "744C",
+        quirk: Quirk::FanCurvePointsOutOfRange,
+    },
+    QuirkEntry {
+        vendor_id: "1002",
+        device_id: "747E",
+        quirk: Quirk::FanCurvePointsOutOfRange,
+    },
+    QuirkEntry {
+        vendor_id: "1002",
+        device_id: "7480",
+        quirk: Quirk::FanCurvePointsOutOfRange,
+    },
+];
+
+/// Gets the built-in quirks for the given PCI id. The ids are compared case-insensitively.
+pub fn builtin_quirks(vendor_id: &str, device_id: &str) -> Vec<Quirk> {
+    BUILTIN_QUIRKS
+        .iter()
+        .filter(|entry| {
+            entry.vendor_id.eq_ignore_ascii_case(vendor_id)
+                && entry.device_id.eq_ignore_ascii_case(device_id)
+        })
+        .map(|entry| entry.quirk)
+        .collect()
+}
+
+/// Moves memory clock commands before all other commands, keeping their relative order.
+#[cfg(feature = "overdrive")]
+pub(crate) fn order_mclk_first(commands: &mut [String]) {
+    commands.sort_by_key(|command| !command.starts_with("m "));
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "overdrive")]
+    use super::order_mclk_first;
+    use super::{builtin_quirks, Quirk};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn match_builtin_quirks() {
+        assert_eq!(
+            vec![Quirk::FanCurvePointsOutOfRange],
+            builtin_quirks("1002", "744c")
+        );
+        assert!(builtin_quirks("1002", "67DF").is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "overdrive")]
+    fn mclk_first() {
+        let mut commands = vec![
+            "s 0 500".to_owned(),
+            "s 1 2500".to_owned(),
+            "m 1 1250".to_owned(),
+            "vo -50".to_owned(),
+            "m 0 97".to_owned(),
+        ];
+        order_mclk_first(&mut commands);
+        assert_eq!(
+            vec!["m 1 1250", "m 0 97", "s 0 500", "s 1 2500", "vo -50"],
+            commands
+        );
+    }
+}
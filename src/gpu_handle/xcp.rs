@@ -0,0 +1,154 @@
+//! Compute partitions (XCP) of datacenter GPUs.
+//!
+//! On MI300-class hardware the GPU can be split into multiple compute partitions, each of which is exposed as its own
+//! render node and KFD topology node. The topology nodes report the PCI location of the physical GPU,
+//! which is used to find the partitions belonging to a [`GpuHandle`].
+use super::GpuHandle;
+use crate::{
+    error::{Error, ErrorContext, ErrorKind},
+    sysfs::SysFS,
+    Result,
+};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Default path of the KFD topology nodes.
+pub(crate) const KFD_TOPOLOGY_PATH: &str = "/sys/class/kfd/kfd/topology/nodes";
+
+/// A handle on a single compute partition of a GPU, see [`GpuHandle::get_xcp_partitions`].
+///
+/// The handle's SysFS path is the KFD topology node of the partition.
+#[derive(Debug, Clone)]
+pub struct XcpHandle {
+    parent: GpuHandle,
+    index: usize,
+    node_path: PathBuf,
+    properties: HashMap<String, u64>,
+}
+
+impl XcpHandle {
+    /// Gets the handle of the physical GPU which this partition belongs to.
+    pub fn parent(&self) -> &GpuHandle {
+        &self.parent
+    }
+
+    /// Gets the index of the partition on its GPU.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Gets the minor number of the partition's DRM render node.
+    pub fn get_render_minor(&self) -> Option<u32> {
+        self.get_property("drm_render_minor")
+            .and_then(|minor| u32::try_from(minor).ok())
+    }
+
+    /// Gets the path of the partition's render node, e.g. `/dev/dri/renderD129`.
+    pub fn get_render_node_path(&self) -> Option<PathBuf> {
+        self.get_render_minor()
+            .map(|minor| PathBuf::from(format!("/dev/dri/renderD{minor}")))
+    }
+
+    /// Gets the number of compute dies (XCCs) assigned to the partition.
+    pub fn get_xcc_count(&self) -> Option<u64> {
+        self.get_property("num_xcc")
+    }
+
+    /// Gets a property of the partition's KFD topology node.
+    pub fn get_property(&self, name: &str) -> Option<u64> {
+        self.properties.get(name).copied()
+    }
+
+    /// Gets all of the properties of the partition's KFD topology node.
+    pub fn get_properties(&self) -> &HashMap<String, u64> {
+        &self.properties
+    }
+}
+
+impl SysFS for XcpHandle {
+    fn get_path(&self) -> &Path {
+        &self.node_path
+    }
+}
+
+/// Finds the partitions of the given GPU in a KFD topology directory, ordered by their node number.
+pub(crate) fn find_partitions(parent: &GpuHandle, topology_path: &Path) -> Result<Vec<XcpHandle>> {
+    let slot_name = parent
+        .get_pci_slot_name()
+        .ok_or_else(|| ErrorKind::Unsupported("The PCI slot name is not known".to_owned()))?;
+    let (domain, location_id) = pci_location(slot_name)
+        .ok_or_else(|| Error::basic_parse_error(format!("Invalid PCI slot name {slot_name}")))?;
+
+    let mut nodes: Vec<(u32, PathBuf, HashMap<String, u64>)> = fs::read_dir(topology_path)
+        .with_context(|| format!("Could not read directory {topology_path:?}"))?
+        .flatten()
+        .filter_map(|entry| {
+            let node: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let properties = fs::read_to_string(entry.path().join("properties")).ok()?;
+            Some((node, entry.path(), parse_properties(&properties)))
+        })
+        .filter(|(_, _, properties)| {
+            properties.get("domain") == Some(&domain)
+                && properties.get("location_id") == Some(&location_id)
+        })
+        .collect();
+    nodes.sort_unstable_by_key(|(node, _, _)| *node);
+
+    Ok(nodes
+        .into_iter()
+        .enumerate()
+        .map(|(index, (_, node_path, properties))| XcpHandle {
+            parent: parent.clone(),
+            index,
+            node_path,
+            properties,
+        })
+        .collect())
+}
+
+/// Parses the `key value` lines of a KFD topology `properties` file, skipping values which are not numbers.
+fn parse_properties(data: &str) -> HashMap<String, u64> {
+    data.lines()
+        .filter_map(|line| {
+            let (key, value) = line.trim().split_once(char::is_whitespace)?;
+            Some((key.to_owned(), value.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// Converts a PCI slot name into the domain and location ID used by KFD (`bus << 8 | device << 3 | function`).
+fn pci_location(slot_name: &str) -> Option<(u64, u64)> {
+    let (domain, rest) = slot_name.split_once(':')?;
+    let (bus, rest) = rest.split_once(':')?;
+    let (device, function) = rest.split_once('.')?;
+
+    let parse = |value: &str| u64::from_str_radix(value, 16).ok();
+    Some((
+        parse(domain)?,
+        parse(bus)? << 8 | parse(device)? << 3 | parse(function)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_properties, pci_location};
+
+    #[test]
+    fn pci_slot_location() {
+        assert_eq!(Some((0, 0x900)), pci_location("0000:09:00.0"));
+        assert_eq!(Some((1, 0xc109)), pci_location("0001:c1:01.1"));
+        assert_eq!(None, pci_location("card0"));
+    }
+
+    #[test]
+    fn node_properties() {
+        let properties =
+            parse_properties("simd_count 76\nlocation_id 2304\ndomain 0\nmarketing_name MI300X\n");
+        assert_eq!(Some(&2304), properties.get("location_id"));
+        assert_eq!(Some(&76), properties.get("simd_count"));
+        assert_eq!(None, properties.get("marketing_name"));
+    }
+}
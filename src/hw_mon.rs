@@ -1,4 +1,6 @@
 //! Hardware monitoring
+#[cfg(feature = "overdrive")]
+use crate::gpu_handle::overdrive::Range;
 use crate::{
     error::{ErrorContext, ErrorKind},
     sysfs::SysFS,
@@ -7,8 +9,13 @@ use crate::{
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
+    fs,
+    os::unix::fs::PermissionsExt,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+    time::Duration,
 };
 
 /// Represents a hardware monitor.
@@ -16,17 +23,36 @@ use std::{
 #[derive(Clone, Debug)]
 pub struct HwMon {
     path: PathBuf,
+    temp_channels: Arc<Mutex<Option<Vec<TempChannel>>>>,
+}
+
+/// A temperature sensor channel found in the hwmon directory.
+#[derive(Clone, Debug)]
+struct TempChannel {
+    index: u32,
+    label: Option<String>,
+    /// The critical limits are static, so they are only read during the scan
+    crit: Option<f32>,
+    crit_hyst: Option<f32>,
 }
 
 impl HwMon {
     /// Most of the time you may want to access `HwMon`s through the
     /// [GpuHandle](../gpu_handle/struct.GpuHandle.html) they're bound to.
     pub fn new_from_path(path: PathBuf) -> Result<Self> {
-        let hw_mon = Self { path };
+        let hw_mon = Self {
+            path,
+            temp_channels: Arc::default(),
+        };
         hw_mon.read_file("name")?;
         Ok(hw_mon)
     }
 
+    /// Checks if the monitor still exists, as the directory can be removed when the driver re-probes the device.
+    pub(crate) fn exists(&self) -> bool {
+        self.path.join("name").exists()
+    }
+
     fn read_temp(&self, file: &str) -> Result<f32> {
         let temp_str = self.read_file(file)?;
         Ok(temp_str
@@ -37,32 +63,54 @@ impl HwMon {
     }
 
     /// Returns a HashMap of temperatures(in degress celsius), indexed by the labels (example: "edge").
+    ///
+    /// The available sensors and their critical limits are found by scanning the directory on the first call,
+    /// afterwards only the current temperatures are read.
     pub fn get_temps(&self) -> HashMap<String, Temperature> {
-        let mut temps = HashMap::new();
-
-        let mut i = 1;
-
-        while let Ok(current) = self.read_temp(&format!("temp{i}_input")) {
-            let temperature = Temperature {
-                current: Some(current),
-                crit: self.read_temp(&format!("temp{i}_crit")).ok(),
-                crit_hyst: self.read_temp(&format!("temp{i}_crit_hyst")).ok(),
-            };
-
-            match self.read_file(format!("temp{i}_label")) {
-                Ok(label) => {
-                    temps.insert(label, temperature);
-                }
-                Err(_) => {
-                    temps.insert(i.to_string(), temperature);
-                    break;
-                }
-            }
-
-            i += 1;
-        }
+        let mut channels = self
+            .temp_channels
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let channels = channels.get_or_insert_with(|| self.scan_temp_channels());
+
+        channels
+            .iter()
+            .filter_map(|channel| {
+                let i = channel.index;
+                let temperature = Temperature {
+                    current: Some(self.read_temp(&format!("temp{i}_input")).ok()?),
+                    crit: channel.crit,
+                    crit_hyst: channel.crit_hyst,
+                };
+                let label = channel.label.clone().unwrap_or_else(|| i.to_string());
+                Some((label, temperature))
+            })
+            .collect()
+    }
 
-        temps
+    fn scan_temp_channels(&self) -> Vec<TempChannel> {
+        let mut channels: Vec<TempChannel> = fs::read_dir(&self.path)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let file_name = entry.file_name();
+                let index = file_name
+                    .to_str()?
+                    .strip_prefix("temp")?
+                    .strip_suffix("_input")?
+                    .parse()
+                    .ok()?;
+                Some(TempChannel {
+                    index,
+                    label: self.read_file(format!("temp{index}_label")).ok(),
+                    crit: self.read_temp(&format!("temp{index}_crit")).ok(),
+                    crit_hyst: self.read_temp(&format!("temp{index}_crit_hyst")).ok(),
+                })
+            })
+            .collect();
+        channels.sort_unstable_by_key(|channel| channel.index);
+        channels
     }
 
     fn read_clockspeed(&self, file: &str) -> Result<u64> {
@@ -112,6 +160,23 @@ impl HwMon {
         self.write_file("power1_cap", value.to_string())
     }
 
+    /// Sets a temporary power cap in watts, which is restored when the returned guard is dropped.
+    ///
+    /// The previous cap is restored, or the default one if the previous cap could not be read.
+    /// Since the guard is also dropped during unwinding, a panic does not leave the GPU power limited.
+    pub fn set_power_cap_temporary(&self, cap: f64) -> Result<PowerCapGuard> {
+        let restore_cap = self
+            .get_power_cap()
+            .or_else(|_| self.get_power_cap_default())
+            .context("Could not read the power cap to restore")?;
+        self.set_power_cap(cap)?;
+
+        Ok(PowerCapGuard {
+            hw_mon: self.clone(),
+            restore_cap: Some(restore_cap),
+        })
+    }
+
     /// Gets the maximum possible power cap for the GPU in watts. If overclocking is disabled, this is probably the same as the default cap.
     pub fn get_power_cap_max(&self) -> Result<f64> {
         self.read_power("power1_cap_max")
@@ -127,6 +192,57 @@ impl HwMon {
         self.read_power("power1_cap_default")
     }
 
+    /// Gets the allowed range of the power cap along with the default cap, in whole watts.
+    ///
+    /// The range bounds which are not reported by the GPU are left empty.
+    /// The bounds are rounded inwards, so that any whole value in the range is accepted by the driver.
+    #[cfg(feature = "overdrive")]
+    pub fn get_power_cap_range(&self) -> Result<PowerCapRange> {
+        let optional = |result: Result<f64>| match result {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if err.is_not_found() => Ok(None),
+            Err(err) => Err(err),
+        };
+
+        let min = optional(self.get_power_cap_min())?.map(|value| value.ceil() as i32);
+        let max = optional(self.get_power_cap_max())?.map(|value| value.floor() as i32);
+        if min.is_none() && max.is_none() {
+            return Err(
+                ErrorKind::Unsupported("The power cap range is not reported".to_owned()).into(),
+            );
+        }
+
+        Ok(PowerCapRange {
+            range: Range { min, max },
+            default: optional(self.get_power_cap_default())?.map(|value| value.round() as i32),
+        })
+    }
+
+    /// Reads the power usage along with the power cap settings in one call.
+    ///
+    /// Values which are not reported by the GPU are left empty.
+    pub fn get_power_report(&self) -> PowerReport {
+        let average = self.get_power_average().ok();
+        let input = self.get_power_input().ok();
+        let cap = self.get_power_cap().ok();
+
+        let cap_utilization = average
+            .or(input)
+            .zip(cap)
+            .filter(|(_, cap)| *cap > 0.0)
+            .map(|(usage, cap)| usage / cap * 100.0);
+
+        PowerReport {
+            average,
+            input,
+            cap,
+            cap_min: self.get_power_cap_min().ok(),
+            cap_max: self.get_power_cap_max().ok(),
+            cap_default: self.get_power_cap_default().ok(),
+            cap_utilization,
+        }
+    }
+
     /// Gets the pulse width modulation fan level.
     pub fn get_fan_pwm(&self) -> Result<u8> {
         let pwm = self.read_file("pwm1")?;
@@ -138,6 +254,22 @@ impl HwMon {
         self.write_file("pwm1", pwm.to_string())
     }
 
+    /// Gradually changes the pulse width modulation fan level to the target, as configured by the ramp.
+    ///
+    /// Blocks until the target is reached. Manual fan control needs to be enabled beforehand.
+    pub fn set_fan_pwm_ramped(&self, target: u8, ramp: &PwmRamp) -> Result<()> {
+        let mut current = self.get_fan_pwm()?;
+        while current != target {
+            current = ramp.next_value(current, target);
+            self.set_fan_pwm(current)?;
+
+            if current != target {
+                thread::sleep(ramp.interval);
+            }
+        }
+        Ok(())
+    }
+
     /// Gets the current fan speed in RPM.
     pub fn get_fan_current(&self) -> Result<u32> {
         let s = self.read_file("fan1_input")?;
@@ -174,19 +306,68 @@ impl HwMon {
             let repr = pwm1_enable
                 .parse()
                 .context("Unexpected pwm1_enable (driver bug?)")?;
-            FanControlMethod::from_repr(repr).ok_or_else(|| {
-                ErrorKind::Unsupported(
-                    "Unexpected pwm1_enable (driver bug or unsupported?)".to_owned(),
-                )
-                .into()
-            })
+            Ok(FanControlMethod::from_repr(repr))
         })
     }
 
     /// Sets the fan control method (`pwm1_enable`).
+    ///
+    /// An [`FanControlMethod::Unknown`] method which was previously read is written back unchanged.
     pub fn set_fan_control_method(&self, method: FanControlMethod) -> Result<()> {
-        let repr = method as u32;
-        self.write_file("pwm1_enable", repr.to_string())
+        self.write_file("pwm1_enable", method.to_repr().to_string())
+    }
+
+    /// Configures the fan control mode along with its value, and checks that the control method was applied.
+    ///
+    /// This takes care of the order in which the files need to be written, as the PWM and target RPM
+    /// values are only accepted when manual control is enabled.
+    pub fn configure_fan(&self, mode: FanMode) -> Result<()> {
+        match mode {
+            FanMode::Automatic => {
+                self.set_fan_control_method(FanControlMethod::Auto)?;
+                self.verify_fan_control_method(FanControlMethod::Auto)
+            }
+            FanMode::FixedPwm(percent) => {
+                if percent > 100 {
+                    return Err(ErrorKind::NotAllowed(format!(
+                        "Fan speed {percent}% is above 100%"
+                    ))
+                    .into());
+                }
+                let pwm = ((u16::from(percent) * 255 + 50) / 100) as u8;
+
+                self.set_fan_control_method(FanControlMethod::Manual)?;
+                self.verify_fan_control_method(FanControlMethod::Manual)?;
+                self.set_fan_pwm(pwm)
+            }
+            FanMode::TargetRpm(rpm) => {
+                if let (Ok(min), Ok(max)) = (self.get_fan_min(), self.get_fan_max()) {
+                    if !(min..=max).contains(&rpm) {
+                        return Err(ErrorKind::NotAllowed(format!(
+                            "Fan speed {rpm} RPM is outside of the allowed range {min}..={max}"
+                        ))
+                        .into());
+                    }
+                }
+
+                self.set_fan_control_method(FanControlMethod::Manual)?;
+                self.verify_fan_control_method(FanControlMethod::Manual)?;
+                self.set_fan_target(rpm)
+            }
+        }
+    }
+
+    fn verify_fan_control_method(&self, expected: FanControlMethod) -> Result<()> {
+        let actual = self.get_fan_control_method()?;
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(ErrorKind::Rejected {
+                requested: format!("{expected:?}").to_lowercase(),
+                actual: format!("{actual:?}").to_lowercase(),
+            }
+            .into())
+        }
     }
 
     /// Gets the GPU voltage in millivolts.
@@ -198,6 +379,74 @@ impl HwMon {
     pub fn get_northbridge_voltage(&self) -> Result<u64> {
         self.read_file_parsed("in1_input")
     }
+
+    /// Lists all of the sensor channels present in the hardware monitor, sorted by kind and index.
+    ///
+    /// This allows showing all of the available sensors without calling each of the specific getters.
+    pub fn list_sensors(&self) -> Result<Vec<Sensor>> {
+        let entries = fs::read_dir(&self.path)
+            .with_context(|| format!("Could not read directory {:?}", self.path))?;
+        let channels: BTreeSet<(SensorKind, u32)> = entries
+            .flatten()
+            .filter_map(|entry| parse_sensor_file_name(entry.file_name().to_str()?))
+            .collect();
+
+        Ok(channels
+            .into_iter()
+            .map(|(kind, index)| Sensor {
+                kind,
+                index,
+                label: self
+                    .read_file(format!("{}{index}_label", kind.prefix()))
+                    .ok(),
+                readable: self.file_has_mode(&self.sensor_value_file(kind, index), 0o444),
+                writable: kind
+                    .control_file(index)
+                    .is_some_and(|file| self.file_has_mode(&file, 0o222)),
+            })
+            .collect())
+    }
+
+    /// Reads the current value of a sensor, in the unit given by [`SensorKind::unit`].
+    pub fn read_sensor(&self, sensor: &Sensor) -> Result<f64> {
+        let raw_value: f64 = self
+            .read_file(self.sensor_value_file(sensor.kind, sensor.index))?
+            .parse()
+            .context("Unexpected sensor value (driver bug?)")?;
+        Ok(raw_value / sensor.kind.divisor())
+    }
+
+    fn sensor_value_file(&self, kind: SensorKind, index: u32) -> String {
+        match kind {
+            SensorKind::Power => {
+                let average = format!("power{index}_average");
+                if self.path.join(&average).exists() {
+                    average
+                } else {
+                    format!("power{index}_input")
+                }
+            }
+            SensorKind::Pwm => format!("pwm{index}"),
+            _ => format!("{}{index}_input", kind.prefix()),
+        }
+    }
+
+    fn file_has_mode(&self, file: &str, mode: u32) -> bool {
+        fs::metadata(self.path.join(file))
+            .is_ok_and(|metadata| metadata.permissions().mode() & mode != 0)
+    }
+}
+
+/// Parses the kind and index of a sensor channel from the name of one of its files, e.g. `temp1_input`.
+fn parse_sensor_file_name(file_name: &str) -> Option<(SensorKind, u32)> {
+    SensorKind::ALL.into_iter().find_map(|kind| {
+        let rest = file_name.strip_prefix(kind.prefix())?;
+        let index = rest.split_once('_').map_or(rest, |(index, _)| index);
+        if index.is_empty() || !index.bytes().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        Some((kind, index.parse().ok()?))
+    })
 }
 
 impl SysFS for HwMon {
@@ -209,6 +458,7 @@ impl SysFS for HwMon {
 /// Temperature reported by the GPU.
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Temperature {
     /// The current temperature.
     pub current: Option<f32>,
@@ -218,27 +468,313 @@ pub struct Temperature {
     pub crit_hyst: Option<f32>,
 }
 
+/// A sensor channel of a hardware monitor, see [`HwMon::list_sensors`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Sensor {
+    /// Kind of the sensor
+    pub kind: SensorKind,
+    /// Index of the channel in the file names, e.g. `1` for `temp1_input`
+    pub index: u32,
+    /// Label reported by the driver, e.g. `edge`
+    pub label: Option<String>,
+    /// If the current value can be read
+    pub readable: bool,
+    /// If the sensor has a setting which can be written to (usually as root), such as the power cap or the fan PWM
+    pub writable: bool,
+}
+
+impl Sensor {
+    /// Unit of the values returned by [`HwMon::read_sensor`].
+    pub fn unit(&self) -> Option<&'static str> {
+        self.kind.unit()
+    }
+}
+
+/// Kind of a hardware monitor sensor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SensorKind {
+    /// Temperature (`temp*`)
+    Temperature,
+    /// Voltage (`in*`)
+    Voltage,
+    /// Power usage (`power*`)
+    Power,
+    /// Fan speed (`fan*`)
+    Fan,
+    /// Fan PWM (`pwm*`)
+    Pwm,
+    /// Clockspeed (`freq*`)
+    Frequency,
+}
+
+impl SensorKind {
+    const ALL: [SensorKind; 6] = [
+        SensorKind::Temperature,
+        SensorKind::Voltage,
+        SensorKind::Power,
+        SensorKind::Fan,
+        SensorKind::Pwm,
+        SensorKind::Frequency,
+    ];
+
+    /// Unit of the sensor values. PWM values are unitless, from 0 to 255.
+    pub fn unit(self) -> Option<&'static str> {
+        match self {
+            SensorKind::Temperature => Some("°C"),
+            SensorKind::Voltage => Some("mV"),
+            SensorKind::Power => Some("W"),
+            SensorKind::Fan => Some("RPM"),
+            SensorKind::Pwm => None,
+            SensorKind::Frequency => Some("MHz"),
+        }
+    }
+
+    fn prefix(self) -> &'static str {
+        match self {
+            SensorKind::Temperature => "temp",
+            SensorKind::Voltage => "in",
+            SensorKind::Power => "power",
+            SensorKind::Fan => "fan",
+            SensorKind::Pwm => "pwm",
+            SensorKind::Frequency => "freq",
+        }
+    }
+
+    /// Divisor to convert the raw value into [`SensorKind::unit`]
+    fn divisor(self) -> f64 {
+        match self {
+            SensorKind::Temperature => 1000.0,
+            SensorKind::Power | SensorKind::Frequency => 1000000.0,
+            SensorKind::Voltage | SensorKind::Fan | SensorKind::Pwm => 1.0,
+        }
+    }
+
+    fn control_file(self, index: u32) -> Option<String> {
+        match self {
+            SensorKind::Power => Some(format!("power{index}_cap")),
+            SensorKind::Fan => Some(format!("fan{index}_target")),
+            SensorKind::Pwm => Some(format!("pwm{index}")),
+            SensorKind::Temperature | SensorKind::Voltage | SensorKind::Frequency => None,
+        }
+    }
+}
+
+/// Guard which restores the power cap when dropped, see [`HwMon::set_power_cap_temporary`].
+#[must_use = "the power cap is restored as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct PowerCapGuard {
+    hw_mon: HwMon,
+    restore_cap: Option<f64>,
+}
+
+impl PowerCapGuard {
+    /// Gets the power cap in watts which will be restored.
+    pub fn get_restore_cap(&self) -> Option<f64> {
+        self.restore_cap
+    }
+
+    /// Restores the power cap now, returning the error if it could not be written.
+    ///
+    /// Errors are ignored when the guard is simply dropped.
+    pub fn restore(mut self) -> Result<()> {
+        self.restore_inner()
+    }
+
+    fn restore_inner(&mut self) -> Result<()> {
+        match self.restore_cap.take() {
+            Some(cap) => self.hw_mon.set_power_cap(cap),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for PowerCapGuard {
+    fn drop(&mut self) {
+        let _ = self.restore_inner();
+    }
+}
+
+/// Allowed power cap values in watts, see [`HwMon::get_power_cap_range`].
+#[cfg(feature = "overdrive")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PowerCapRange {
+    /// Minimum and maximum allowed power cap
+    pub range: Range,
+    /// Default power cap
+    pub default: Option<i32>,
+}
+
+/// Power usage and power cap settings, see [`HwMon::get_power_report`].
+///
+/// All values are in watts.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PowerReport {
+    /// Average power usage
+    pub average: Option<f64>,
+    /// Instantaneous power usage
+    pub input: Option<f64>,
+    /// Current power cap
+    pub cap: Option<f64>,
+    /// Minimum allowed power cap
+    pub cap_min: Option<f64>,
+    /// Maximum allowed power cap
+    pub cap_max: Option<f64>,
+    /// Default power cap
+    pub cap_default: Option<f64>,
+    /// Power usage as a percentage of the current cap.
+    /// The average usage is used when available, otherwise the instantaneous one.
+    pub cap_utilization: Option<f64>,
+}
+
 /// The way the fan speed is controlled.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[non_exhaustive]
 pub enum FanControlMethod {
     /// No fan speed control.
-    None = 0,
+    None,
     /// Manual fan speed control via the PWM interface.
-    Manual = 1,
+    Manual,
     /// Automatic fan speed control (by the kernel).
-    Auto = 2,
+    Auto,
+    /// A board-specific mode which is not known to this library, given by its `pwm1_enable` value.
+    Unknown(u32),
 }
 
 impl FanControlMethod {
     /// Create [FanControlMethod] from a digit in the SysFS.
-    pub fn from_repr(repr: u32) -> Option<Self> {
+    pub fn from_repr(repr: u32) -> Self {
         match repr {
-            0 => Some(Self::None),
-            1 => Some(Self::Manual),
-            2 => Some(Self::Auto),
-            _ => None,
+            0 => Self::None,
+            1 => Self::Manual,
+            2 => Self::Auto,
+            other => Self::Unknown(other),
         }
     }
+
+    /// Gets the digit representing the method in the SysFS.
+    pub fn to_repr(self) -> u32 {
+        match self {
+            Self::None => 0,
+            Self::Manual => 1,
+            Self::Auto => 2,
+            Self::Unknown(repr) => repr,
+        }
+    }
+}
+
+/// Fan control mode, see [`HwMon::configure_fan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FanMode {
+    /// Automatic fan speed control by the kernel
+    Automatic,
+    /// Fixed fan speed, in percent
+    FixedPwm(u8),
+    /// Fixed fan speed in RPM
+    TargetRpm(u32),
+}
+
+/// Settings for changing the fan PWM gradually, see [`HwMon::set_fan_pwm_ramped`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PwmRamp {
+    step: u8,
+    interval: Duration,
+}
+
+impl PwmRamp {
+    /// Creates a ramp which changes the PWM by at most `step` every `interval`.
+    pub fn new(step: u8, interval: Duration) -> Self {
+        Self {
+            step: step.max(1),
+            interval,
+        }
+    }
+
+    /// Gets the maximum PWM change per step.
+    pub fn get_step(&self) -> u8 {
+        self.step
+    }
+
+    /// Gets the time between steps.
+    pub fn get_interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Calculates the next PWM value when moving from `current` towards `target`.
+    ///
+    /// This can be used to drive the ramp from an existing loop instead of [`HwMon::set_fan_pwm_ramped`].
+    pub fn next_value(&self, current: u8, target: u8) -> u8 {
+        if current < target {
+            current.saturating_add(self.step).min(target)
+        } else {
+            current.saturating_sub(self.step).max(target)
+        }
+    }
+}
+
+impl Default for PwmRamp {
+    fn default() -> Self {
+        Self::new(5, Duration::from_millis(100))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_sensor_file_name, PwmRamp, SensorKind};
+    use std::time::Duration;
+
+    #[test]
+    fn sensor_file_names() {
+        assert_eq!(
+            Some((SensorKind::Temperature, 1)),
+            parse_sensor_file_name("temp1_input")
+        );
+        assert_eq!(
+            Some((SensorKind::Voltage, 0)),
+            parse_sensor_file_name("in0_label")
+        );
+        assert_eq!(Some((SensorKind::Pwm, 1)), parse_sensor_file_name("pwm1"));
+        assert_eq!(
+            Some((SensorKind::Pwm, 1)),
+            parse_sensor_file_name("pwm1_enable")
+        );
+        assert_eq!(
+            Some((SensorKind::Power, 1)),
+            parse_sensor_file_name("power1_cap_max")
+        );
+        assert_eq!(None, parse_sensor_file_name("name"));
+        assert_eq!(None, parse_sensor_file_name("uevent"));
+        assert_eq!(None, parse_sensor_file_name("power"));
+    }
+
+    #[test]
+    fn pwm_ramp_next_value() {
+        let ramp = PwmRamp::new(10, Duration::ZERO);
+        assert_eq!(45, ramp.next_value(35, 100));
+        assert_eq!(100, ramp.next_value(95, 100));
+        assert_eq!(25, ramp.next_value(35, 0));
+        assert_eq!(0, ramp.next_value(5, 0));
+        assert_eq!(255, ramp.next_value(250, 255));
+        assert_eq!(100, ramp.next_value(100, 100));
+    }
+
+    #[test]
+    fn pwm_ramp_zero_step() {
+        assert_eq!(1, PwmRamp::new(0, Duration::ZERO).get_step());
+    }
 }
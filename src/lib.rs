@@ -5,9 +5,15 @@
 #[cfg(test)]
 #[macro_use]
 mod tests;
+#[cfg(feature = "demo")]
+pub mod demo;
 pub mod error;
 pub mod gpu_handle;
 pub mod hw_mon;
+pub mod monitoring;
+pub mod profile;
+#[cfg(feature = "serde")]
+pub mod schema;
 pub mod sysfs;
 
 type Result<T> = std::result::Result<T, error::Error>;
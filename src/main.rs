@@ -0,0 +1,223 @@
+//! Command line tool for inspecting and configuring AMD GPUs, built on top of the library.
+use amdgpu_sysfs::{
+    gpu_handle::{fan_control::FanCurve, GpuHandle},
+    monitoring::{Sample, Sampler},
+    profile::TuningProfile,
+    schema::Versioned,
+};
+use clap::{Parser, Subcommand};
+use std::{error::Error, fs, ops::ControlFlow, path::PathBuf, time::Duration};
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// Inspect and configure AMD GPUs through sysfs
+#[derive(Parser)]
+#[command(name = "amdgpu-sysfs", version)]
+struct Cli {
+    /// Path to the GPU device directory, e.g. /sys/class/drm/card0/device.
+    /// The first amdgpu device is used when not specified
+    #[arg(long, short, global = true)]
+    device: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Show general information about the GPU
+    Info,
+    /// Show the current sensor readings
+    Metrics {
+        /// Keep printing new readings until interrupted
+        #[arg(long, short)]
+        watch: bool,
+        /// Interval between readings in milliseconds
+        #[arg(long, short, default_value_t = 1000)]
+        interval: u64,
+    },
+    /// Set the power cap in watts
+    SetPowerCap {
+        /// New power cap
+        watts: f64,
+    },
+    /// Set the PMFW fan curve points
+    SetFanCurve {
+        /// Curve points in the `temperature:speed` format, e.g. `50:30 60:45 70:60 80:80 90:100`
+        #[arg(required = true, value_parser = parse_curve_point)]
+        points: Vec<(i32, u8)>,
+    },
+    /// Print the current tuning settings as JSON
+    Snapshot {
+        /// Write the snapshot to a file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    if let Err(err) = run(cli) {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let gpu_handle = match cli.device {
+        Some(device) => GpuHandle::new_from_path(device)?,
+        None => GpuHandle::enumerate()?
+            .into_iter()
+            .find(|gpu_handle| gpu_handle.get_driver() == "amdgpu")
+            .ok_or("No amdgpu devices found")?,
+    };
+
+    match cli.command {
+        Command::Info => print_info(&gpu_handle),
+        Command::Metrics { watch, interval } => {
+            let sampler = Sampler::new(gpu_handle).interval(Duration::from_millis(interval));
+            if watch {
+                sampler.run(|sample| {
+                    print_sample(sample);
+                    println!();
+                    ControlFlow::Continue(())
+                });
+            } else {
+                print_sample(&sampler.sample());
+            }
+        }
+        Command::SetPowerCap { watts } => {
+            let profile = TuningProfile {
+                power_cap: Some(watts),
+                ..Default::default()
+            };
+            profile.apply(&gpu_handle)?;
+            println!("Power cap set to {watts}W");
+        }
+        Command::SetFanCurve { points } => {
+            let current_curve = gpu_handle.get_fan_curve()?;
+            if points.len() != current_curve.point_count() {
+                return Err(format!(
+                    "The fan curve must have exactly {} points",
+                    current_curve.point_count()
+                )
+                .into());
+            }
+
+            let mut builder = FanCurve::builder().points(points);
+            if let Some(allowed_ranges) = current_curve.allowed_ranges {
+                builder = builder.allowed_ranges(allowed_ranges);
+            }
+            gpu_handle.set_fan_curve(&builder.build()?)?.commit()?;
+            println!("Fan curve applied");
+        }
+        Command::Snapshot { output } => {
+            let profile = TuningProfile::capture(&gpu_handle)?;
+            let json = serde_json::to_string_pretty(&Versioned::new(profile))?;
+            match output {
+                Some(path) => fs::write(path, json)?,
+                None => println!("{json}"),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_curve_point(value: &str) -> std::result::Result<(i32, u8), String> {
+    let (temperature, speed) = value
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid curve point '{value}', expected temperature:speed"))?;
+    let temperature = temperature
+        .parse()
+        .map_err(|err| format!("Invalid temperature '{temperature}': {err}"))?;
+    let speed = speed
+        .parse()
+        .map_err(|err| format!("Invalid speed '{speed}': {err}"))?;
+    Ok((temperature, speed))
+}
+
+fn print_info(gpu_handle: &GpuHandle) {
+    println!("{gpu_handle}");
+    if let Ok(vram) = gpu_handle.get_total_vram() {
+        println!("VRAM: {} MiB", vram / 1024 / 1024);
+    }
+    if let Ok(link) = gpu_handle.get_link_status() {
+        println!("PCIe link: {link}");
+    }
+    if let Ok(level) = gpu_handle.get_power_force_performance_level() {
+        println!("Performance level: {level}");
+    }
+
+    if let Some(hw_mon) = gpu_handle.hw_monitors.first() {
+        if let Ok(cap) = hw_mon.get_power_cap() {
+            print!("Power cap: {cap}W");
+            if let (Ok(min), Ok(max)) = (hw_mon.get_power_cap_min(), hw_mon.get_power_cap_max()) {
+                print!(" (allowed range {min}W - {max}W)");
+            }
+            println!();
+        }
+    }
+
+    if let Ok(curve) = gpu_handle.get_fan_curve() {
+        if curve.is_default() {
+            println!("Fan curve: firmware default");
+        } else {
+            let points: Vec<String> = curve
+                .points
+                .iter()
+                .map(|point| format!("{}:{}", point.temperature, point.speed))
+                .collect();
+            println!("Fan curve: {}", points.join(" "));
+        }
+    }
+}
+
+fn print_sample(sample: &Sample) {
+    if let Some(busy_percent) = sample.busy_percent {
+        println!("GPU usage: {busy_percent}%");
+    }
+    if let (Some(used), Some(total)) = (sample.vram_used, sample.vram_total) {
+        println!(
+            "VRAM usage: {} / {} MiB",
+            used / 1024 / 1024,
+            total / 1024 / 1024
+        );
+    }
+    for (label, temperature) in &sample.temperatures {
+        if let Some(current) = temperature.current {
+            println!("Temperature ({label}): {current}°C");
+        }
+    }
+    if let Some(power) = sample.power {
+        match sample.power_cap {
+            Some(cap) => println!("Power: {power}W / {cap}W"),
+            None => println!("Power: {power}W"),
+        }
+    }
+    if let Some(clockspeed) = sample.gpu_clockspeed {
+        println!("GPU clock: {clockspeed}MHz");
+    }
+    if let Some(clockspeed) = sample.vram_clockspeed {
+        println!("VRAM clock: {clockspeed}MHz");
+    }
+    if let Some(rpm) = sample.fan_rpm {
+        match sample.fan_pwm {
+            Some(pwm) => println!("Fan: {rpm} RPM (PWM {pwm})"),
+            None => println!("Fan: {rpm} RPM"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_curve_point;
+
+    #[test]
+    fn parse_curve_points() {
+        assert_eq!(parse_curve_point("50:30"), Ok((50, 30)));
+        assert_eq!(parse_curve_point("-5:0"), Ok((-5, 0)));
+        assert!(parse_curve_point("50").is_err());
+        assert!(parse_curve_point("50:300").is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use super::{Metric, Sample, Sampler};
+use crate::gpu_handle::GpuHandle;
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    sync::{mpsc, Arc, Mutex, PoisonError},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Collects data from multiple GPUs concurrently, with a bounded number of threads and a timeout for each GPU.
+///
+/// A device which stops responding (e.g. after a GPU hang) only causes its own result to be missing,
+/// instead of stalling the collection for all of the other GPUs.
+/// The thread which is stuck on such a device is left behind and replaced by a new one.
+///
+/// # Example
+///
+/// ```no_run
+/// use amdgpu_sysfs::{gpu_handle::GpuHandle, monitoring::ConcurrentCollector};
+/// use std::time::Duration;
+///
+/// let handles = GpuHandle::enumerate().unwrap();
+/// let collector = ConcurrentCollector::new().timeout(Duration::from_secs(2));
+///
+/// for (handle, sample) in handles.iter().zip(collector.sample(&handles)) {
+///     match sample {
+///         Some(sample) => println!("{:?}: {:?}W", handle.get_pci_slot_name(), sample.power),
+///         None => println!("{:?} did not respond", handle.get_pci_slot_name()),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConcurrentCollector {
+    max_threads: usize,
+    timeout: Duration,
+    metrics: BTreeSet<Metric>,
+}
+
+enum Message<T> {
+    Started(usize, Instant),
+    Finished(usize, T),
+}
+
+type Queue = Arc<Mutex<VecDeque<(usize, GpuHandle)>>>;
+
+impl ConcurrentCollector {
+    /// Creates a collector which uses up to 4 threads and a 5 second timeout.
+    pub fn new() -> Self {
+        Self {
+            max_threads: 4,
+            timeout: Duration::from_secs(5),
+            metrics: Metric::ALL.into_iter().collect(),
+        }
+    }
+
+    /// Sets the maximum number of GPUs that are read at the same time.
+    pub fn max_threads(mut self, max_threads: usize) -> Self {
+        self.max_threads = max_threads.max(1);
+        self
+    }
+
+    /// Sets how long to wait for a single GPU.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the metrics to be collected by [`ConcurrentCollector::sample`].
+    pub fn metrics(mut self, metrics: impl IntoIterator<Item = Metric>) -> Self {
+        self.metrics = metrics.into_iter().collect();
+        self
+    }
+
+    /// Collects a [`Sample`] from every GPU.
+    ///
+    /// The results are in the same order as the handles, with `None` for GPUs that did not respond in time.
+    pub fn sample(&self, handles: &[GpuHandle]) -> Vec<Option<Sample>> {
+        let metrics = self.metrics.clone();
+        self.collect(handles, move |gpu_handle| {
+            Sampler::new(gpu_handle.clone())
+                .metrics(metrics.iter().copied())
+                .sample()
+        })
+    }
+
+    /// Runs the function for every GPU and collects the results.
+    ///
+    /// The results are in the same order as the handles, with `None` for GPUs where the function
+    /// did not finish in time. A panic in the function is treated the same way as a timeout.
+    pub fn collect<T, F>(&self, handles: &[GpuHandle], f: F) -> Vec<Option<T>>
+    where
+        T: Send + 'static,
+        F: Fn(&GpuHandle) -> T + Send + Sync + 'static,
+    {
+        let mut results: Vec<Option<T>> = handles.iter().map(|_| None).collect();
+        if handles.is_empty() {
+            return results;
+        }
+
+        let queue: Queue = Arc::new(Mutex::new(handles.iter().cloned().enumerate().collect()));
+        let f = Arc::new(f);
+        let (sender, receiver) = mpsc::channel();
+
+        for _ in 0..self.max_threads.min(handles.len()) {
+            spawn_worker(&queue, &f, &sender);
+        }
+
+        let mut running: HashMap<usize, Instant> = HashMap::new();
+        let mut remaining = handles.len();
+
+        while remaining > 0 {
+            let message = match running.values().min() {
+                Some(started) => {
+                    let deadline = *started + self.timeout;
+                    receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => receiver
+                    .recv()
+                    .map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+            };
+
+            match message {
+                Ok(Message::Started(index, started)) => {
+                    running.insert(index, started);
+                }
+                Ok(Message::Finished(index, value)) => {
+                    if running.remove(&index).is_some() {
+                        results[index] = Some(value);
+                        remaining -= 1;
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    let now = Instant::now();
+                    let timed_out: Vec<usize> = running
+                        .iter()
+                        .filter(|(_, started)| now.duration_since(**started) >= self.timeout)
+                        .map(|(index, _)| *index)
+                        .collect();
+
+                    for index in timed_out {
+                        running.remove(&index);
+                        remaining -= 1;
+                        // The worker is stuck on this GPU, so another one takes its place
+                        spawn_worker(&queue, &f, &sender);
+                    }
+                }
+                // A sender is kept here to spawn replacement workers, so this should not happen
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        results
+    }
+}
+
+impl Default for ConcurrentCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn spawn_worker<T, F>(queue: &Queue, f: &Arc<F>, sender: &mpsc::Sender<Message<T>>)
+where
+    T: Send + 'static,
+    F: Fn(&GpuHandle) -> T + Send + Sync + 'static,
+{
+    let queue = queue.clone();
+    let f = f.clone();
+    let sender = sender.clone();
+
+    thread::spawn(move || loop {
+        let next = queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop_front();
+        let Some((index, gpu_handle)) = next else {
+            break;
+        };
+
+        if sender
+            .send(Message::Started(index, Instant::now()))
+            .is_err()
+        {
+            break;
+        }
+        let value = f(&gpu_handle);
+        if sender.send(Message::Finished(index, value)).is_err() {
+            break;
+        }
+    });
+}
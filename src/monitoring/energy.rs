@@ -0,0 +1,126 @@
+use super::Sample;
+use std::time::Duration;
+
+/// Accumulates energy consumption by integrating power readings over time.
+///
+/// Readings are taken from the power usage of samples produced by a [`Sampler`](super::Sampler).
+/// The meter starts in a stopped state and ignores samples until [`EnergyMeter::start`] is called.
+#[derive(Debug, Clone, Default)]
+pub struct EnergyMeter {
+    running: bool,
+    joules: f64,
+    measured_time: Duration,
+    last_reading: Option<(Duration, f64)>,
+}
+
+impl EnergyMeter {
+    /// Creates a stopped meter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts accumulating energy from the following samples.
+    pub fn start(&mut self) {
+        self.running = true;
+    }
+
+    /// Stops accumulating energy. The accumulated value is kept.
+    pub fn stop(&mut self) {
+        self.running = false;
+        self.last_reading = None;
+    }
+
+    /// Clears the accumulated energy without changing whether the meter is running.
+    pub fn reset(&mut self) {
+        self.joules = 0.0;
+        self.measured_time = Duration::ZERO;
+        self.last_reading = None;
+    }
+
+    /// Checks if the meter is running.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Adds a power reading from a sample.
+    /// Samples without a power reading are skipped.
+    pub fn push(&mut self, sample: &Sample) {
+        if !self.running {
+            return;
+        }
+        let Some(power) = sample.power else {
+            return;
+        };
+
+        if let Some((last_elapsed, last_power)) = self.last_reading {
+            let delta = sample.elapsed.saturating_sub(last_elapsed);
+            self.joules += (last_power + power) / 2.0 * delta.as_secs_f64();
+            self.measured_time += delta;
+        }
+        self.last_reading = Some((sample.elapsed, power));
+    }
+
+    /// Accumulated energy in joules.
+    pub fn joules(&self) -> f64 {
+        self.joules
+    }
+
+    /// Accumulated energy in watt-hours.
+    pub fn watt_hours(&self) -> f64 {
+        self.joules / 3600.0
+    }
+
+    /// Total time over which the energy was measured.
+    pub fn measured_time(&self) -> Duration {
+        self.measured_time
+    }
+
+    /// Average power over the measured time in watts.
+    pub fn average_power(&self) -> Option<f64> {
+        let secs = self.measured_time.as_secs_f64();
+        (secs > 0.0).then(|| self.joules / secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EnergyMeter;
+    use crate::monitoring::Sample;
+    use pretty_assertions::assert_eq;
+    use std::time::{Duration, SystemTime};
+
+    fn sample(secs: u64, power: Option<f64>) -> Sample {
+        let mut sample = Sample::empty(SystemTime::UNIX_EPOCH, Duration::from_secs(secs));
+        sample.power = power;
+        sample
+    }
+
+    #[test]
+    fn accumulate_energy() {
+        let mut meter = EnergyMeter::new();
+        meter.push(&sample(0, Some(100.0)));
+        assert_eq!(0.0, meter.joules());
+
+        meter.start();
+        meter.push(&sample(1, Some(100.0)));
+        meter.push(&sample(3, Some(200.0)));
+        meter.push(&sample(4, None));
+        meter.push(&sample(5, Some(200.0)));
+        assert_eq!(300.0 + 400.0, meter.joules());
+        assert_eq!(Duration::from_secs(4), meter.measured_time());
+        assert_eq!(Some(175.0), meter.average_power());
+
+        // The time between stopping and starting again is not counted
+        meter.stop();
+        meter.push(&sample(6, Some(200.0)));
+        meter.start();
+        meter.push(&sample(10, Some(3600.0)));
+        meter.push(&sample(11, Some(3600.0)));
+        assert_eq!(4300.0, meter.joules());
+
+        meter.reset();
+        assert_eq!(0.0, meter.watt_hours());
+        assert_eq!(None, meter.average_power());
+        assert!(meter.is_running());
+    }
+}
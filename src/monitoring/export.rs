@@ -0,0 +1,266 @@
+use super::Sample;
+use std::{
+    fmt::Display,
+    io::{self, Write},
+    time::UNIX_EPOCH,
+};
+
+/// Names of the columns which are always present, in order.
+const BASE_FIELDS: [&str; 11] = [
+    "timestamp_ms",
+    "elapsed_ms",
+    "busy_percent",
+    "vram_used",
+    "vram_total",
+    "power",
+    "power_cap",
+    "gpu_clockspeed",
+    "vram_clockspeed",
+    "fan_rpm",
+    "fan_pwm",
+];
+
+/// A destination for samples.
+pub trait SampleWriter {
+    /// Writes a single sample.
+    fn write_sample(&mut self, sample: &Sample) -> io::Result<()>;
+}
+
+/// Writes samples as CSV.
+///
+/// The columns are `timestamp_ms`, `elapsed_ms`, `busy_percent`, `vram_used`, `vram_total`, `power`, `power_cap`,
+/// `gpu_clockspeed`, `vram_clockspeed`, `fan_rpm`, `fan_pwm`, followed by a `temp_<label>` column for every temperature sensor
+/// and an `errors` column with the names of the metrics which failed to be read, separated by `;`.
+///
+/// The temperature columns are determined by the first written sample. Missing values are left empty.
+#[derive(Debug)]
+pub struct CsvWriter<W: Write> {
+    writer: W,
+    temperature_labels: Option<Vec<String>>,
+}
+
+impl<W: Write> CsvWriter<W> {
+    /// Creates a writer. The header is written along with the first sample.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            temperature_labels: None,
+        }
+    }
+
+    /// Unwraps the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> SampleWriter for CsvWriter<W> {
+    fn write_sample(&mut self, sample: &Sample) -> io::Result<()> {
+        let labels = match &self.temperature_labels {
+            Some(labels) => labels,
+            None => {
+                let labels: Vec<String> = sample.temperatures.keys().cloned().collect();
+
+                let header: Vec<String> = BASE_FIELDS
+                    .iter()
+                    .map(|field| field.to_string())
+                    .chain(labels.iter().map(|label| format!("temp_{label}")))
+                    .chain(std::iter::once("errors".to_owned()))
+                    .map(|field| escape_csv(&field))
+                    .collect();
+                writeln!(self.writer, "{}", header.join(","))?;
+
+                self.temperature_labels.insert(labels)
+            }
+        };
+
+        let mut row: Vec<String> = base_values(sample)
+            .into_iter()
+            .map(|value| value.unwrap_or_default())
+            .collect();
+        row.extend(labels.iter().map(|label| {
+            sample
+                .temperatures
+                .get(label)
+                .and_then(|temp| temp.current)
+                .map(|temp| temp.to_string())
+                .unwrap_or_default()
+        }));
+        let errors: Vec<String> = sample.errors.keys().map(|m| m.to_string()).collect();
+        row.push(escape_csv(&errors.join(";")));
+
+        writeln!(self.writer, "{}", row.join(","))
+    }
+}
+
+/// Writes samples as newline-delimited JSON, one object per sample.
+///
+/// The objects have the same fields as the [`CsvWriter`] columns, except for the temperatures being
+/// stored in a `temperatures` object indexed by the sensor label and the errors being stored in an
+/// `errors` object indexed by the metric name. Missing values are `null`.
+#[derive(Debug)]
+pub struct NdjsonWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    /// Creates a writer.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Unwraps the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> SampleWriter for NdjsonWriter<W> {
+    fn write_sample(&mut self, sample: &Sample) -> io::Result<()> {
+        let mut fields: Vec<String> = BASE_FIELDS
+            .iter()
+            .zip(base_values(sample))
+            .map(|(name, value)| {
+                format!("\"{name}\":{}", value.unwrap_or_else(|| "null".to_owned()))
+            })
+            .collect();
+
+        let temperatures: Vec<String> = sample
+            .temperatures
+            .iter()
+            .map(|(label, temp)| {
+                let value = temp.current.map_or_else(|| "null".to_owned(), json_number);
+                format!("{}:{value}", escape_json(label))
+            })
+            .collect();
+        fields.push(format!("\"temperatures\":{{{}}}", temperatures.join(",")));
+
+        let errors: Vec<String> = sample
+            .errors
+            .iter()
+            .map(|(metric, err)| format!("\"{metric}\":{}", escape_json(err)))
+            .collect();
+        fields.push(format!("\"errors\":{{{}}}", errors.join(",")));
+
+        writeln!(self.writer, "{{{}}}", fields.join(","))
+    }
+}
+
+fn base_values(sample: &Sample) -> [Option<String>; 11] {
+    let timestamp = sample
+        .timestamp
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    [
+        Some(timestamp.as_millis().to_string()),
+        Some(sample.elapsed.as_millis().to_string()),
+        sample.busy_percent.map(|value| value.to_string()),
+        sample.vram_used.map(|value| value.to_string()),
+        sample.vram_total.map(|value| value.to_string()),
+        sample.power.map(json_number),
+        sample.power_cap.map(json_number),
+        sample.gpu_clockspeed.map(|value| value.to_string()),
+        sample.vram_clockspeed.map(|value| value.to_string()),
+        sample.fan_rpm.map(|value| value.to_string()),
+        sample.fan_pwm.map(|value| value.to_string()),
+    ]
+}
+
+/// Formats a float so that it's valid in both CSV and JSON. Non-finite values are not representable in JSON.
+fn json_number<T: Display + Into<f64> + Copy>(value: T) -> String {
+    if value.into().is_finite() {
+        value.to_string()
+    } else {
+        "null".to_owned()
+    }
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+fn escape_json(value: &str) -> String {
+    let mut output = String::with_capacity(value.len() + 2);
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c.is_control() => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CsvWriter, NdjsonWriter, SampleWriter};
+    use crate::{
+        hw_mon::Temperature,
+        monitoring::{Metric, Sample},
+    };
+    use pretty_assertions::assert_eq;
+    use std::time::{Duration, SystemTime};
+
+    fn sample(secs: u64) -> Sample {
+        let mut sample = Sample::empty(
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000),
+            Duration::from_secs(secs),
+        );
+        sample.busy_percent = Some(42);
+        sample.power = Some(150.5);
+        sample.temperatures.insert(
+            "edge".to_owned(),
+            Temperature {
+                current: Some(55.0),
+                crit: None,
+                crit_hyst: None,
+            },
+        );
+        sample
+            .errors
+            .insert(Metric::Fan, "No \"fan\" found".to_owned());
+        sample
+    }
+
+    #[test]
+    fn csv() {
+        let mut writer = CsvWriter::new(Vec::new());
+        writer.write_sample(&sample(0)).unwrap();
+
+        let mut second = sample(1);
+        second.temperatures.clear();
+        second.errors.clear();
+        writer.write_sample(&second).unwrap();
+
+        assert_eq!(
+            "timestamp_ms,elapsed_ms,busy_percent,vram_used,vram_total,power,power_cap,gpu_clockspeed,vram_clockspeed,fan_rpm,fan_pwm,temp_edge,errors\n\
+             1700000000000,0,42,,,150.5,,,,,,55,fan\n\
+             1700000000000,1000,42,,,150.5,,,,,,,\n",
+            String::from_utf8(writer.into_inner()).unwrap()
+        );
+    }
+
+    #[test]
+    fn ndjson() {
+        let mut writer = NdjsonWriter::new(Vec::new());
+        writer.write_sample(&sample(0)).unwrap();
+
+        assert_eq!(
+            "{\"timestamp_ms\":1700000000000,\"elapsed_ms\":0,\"busy_percent\":42,\"vram_used\":null,\"vram_total\":null,\
+             \"power\":150.5,\"power_cap\":null,\"gpu_clockspeed\":null,\"vram_clockspeed\":null,\"fan_rpm\":null,\"fan_pwm\":null,\
+             \"temperatures\":{\"edge\":55},\"errors\":{\"fan\":\"No \\\"fan\\\" found\"}}\n",
+            String::from_utf8(writer.into_inner()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,26 @@
+//! Polling of real-time metrics
+//!
+//! A [`Sampler`] periodically reads a set of [`Metric`]s from a [`GpuHandle`](crate::gpu_handle::GpuHandle)
+//! and produces timestamped [`Sample`]s, which can be aggregated with [`Statistics`],
+//! checked against thresholds with a [`Watcher`] or used to measure energy consumption with an [`EnergyMeter`].
+//! Samples can be logged with a [`CsvWriter`] or an [`NdjsonWriter`].
+//!
+//! The usage of individual processes can be tracked with a [`ProcessTracker`].
+//!
+//! Multiple GPUs can be read in parallel with a [`ConcurrentCollector`], which stops waiting for devices that don't respond.
+mod concurrent;
+mod energy;
+mod export;
+mod processes;
+mod sampler;
+mod stats;
+mod watch;
+
+pub use concurrent::ConcurrentCollector;
+pub use energy::EnergyMeter;
+pub use export::{CsvWriter, NdjsonWriter, SampleWriter};
+pub use processes::{DrmClient, ProcessTracker, ProcessUsage};
+pub(crate) use sampler::read_sample;
+pub use sampler::{Metric, Sample, SampleIter, Sampler};
+pub use stats::{Aggregate, Statistics};
+pub use watch::{Condition, Event, EventKind, Value, Watch, WatchId, Watcher};
@@ -0,0 +1,433 @@
+use crate::{error::Error, gpu_handle::GpuHandle, Result};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Usage statistics of an amdgpu DRM client, as reported in `/proc/<pid>/fdinfo/<fd>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DrmClient {
+    /// Client id, unique per device. Multiple file descriptors can refer to the same client.
+    pub client_id: u64,
+    /// PCI slot name of the device used by the client
+    pub pdev: Option<String>,
+    /// Total busy time of each engine in nanoseconds, indexed by the engine name (e.g. `gfx`, `compute`)
+    pub engines: BTreeMap<String, u64>,
+    /// Memory usage in bytes, indexed by the memory region (e.g. `vram`, `gtt`)
+    pub memory: BTreeMap<String, u64>,
+}
+
+impl FromStr for DrmClient {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut driver = None;
+        let mut client_id = None;
+        let mut pdev = None;
+        let mut engines = BTreeMap::new();
+        let mut memory = BTreeMap::new();
+        let mut resident_memory = BTreeMap::new();
+
+        for line in s.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key {
+                "drm-driver" => driver = Some(value),
+                "drm-client-id" => client_id = Some(value.parse()?),
+                "drm-pdev" => pdev = Some(value.to_owned()),
+                _ => {
+                    if let Some(engine) = key.strip_prefix("drm-engine-") {
+                        // Other keys with this prefix (like `drm-engine-capacity-*`) or units are not engine times
+                        if let Some(time) = value
+                            .strip_suffix("ns")
+                            .and_then(|time| time.trim().parse().ok())
+                        {
+                            engines.insert(engine.to_owned(), time);
+                        }
+                    } else if let Some(region) = key.strip_prefix("drm-memory-") {
+                        memory.insert(region.to_owned(), parse_memory(value)?);
+                    } else if let Some(region) = key.strip_prefix("drm-resident-") {
+                        resident_memory.insert(region.to_owned(), parse_memory(value)?);
+                    }
+                }
+            }
+        }
+
+        if driver != Some("amdgpu") {
+            return Err(Error::basic_parse_error("Not an amdgpu DRM client"));
+        }
+        let client_id =
+            client_id.ok_or_else(|| Error::basic_parse_error("Missing drm-client-id"))?;
+
+        // Newer kernels report resident memory instead of (or in addition to) the legacy memory keys
+        for (region, size) in resident_memory {
+            memory.entry(region).or_insert(size);
+        }
+
+        Ok(Self {
+            client_id,
+            pdev,
+            engines,
+            memory,
+        })
+    }
+}
+
+fn parse_memory(value: &str) -> Result<u64> {
+    let (number, multiplier) = match value.split_once(' ') {
+        Some((number, "KiB")) => (number, 1024),
+        Some((number, "MiB")) => (number, 1024 * 1024),
+        Some((_, unit)) => {
+            return Err(Error::basic_parse_error(format!(
+                "Unknown memory unit {unit}"
+            )))
+        }
+        None => (value, 1),
+    };
+    Ok(number.parse::<u64>()? * multiplier)
+}
+
+/// GPU usage of a process over the last measurement interval.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ProcessUsage {
+    /// Process id
+    pub pid: u32,
+    /// Process name
+    pub name: String,
+    /// Utilization percentage of each engine. Empty if the process was not seen in the previous measurement.
+    ///
+    /// Engines with multiple rings may report more than 100%.
+    pub engines: BTreeMap<String, f64>,
+    /// Used VRAM in bytes
+    pub vram: u64,
+    /// Change of used VRAM since the previous measurement in bytes
+    pub vram_delta: i64,
+}
+
+#[derive(Debug, Clone)]
+struct ProcessSnapshot {
+    name: String,
+    engines: BTreeMap<String, u64>,
+    vram: u64,
+}
+
+/// Processes are identified by their pid and start time, so that a reused pid is not mistaken for the previous process.
+type ProcessKey = (u32, u64);
+
+/// Tracks the GPU usage of individual processes by comparing consecutive snapshots of their DRM file descriptors.
+///
+/// Only processes which the current user is allowed to inspect are reported.
+#[derive(Debug, Clone)]
+pub struct ProcessTracker {
+    pci_slot_name: Option<String>,
+    proc_path: PathBuf,
+    previous: Option<(Instant, HashMap<ProcessKey, ProcessSnapshot>)>,
+}
+
+impl ProcessTracker {
+    /// Creates a tracker for the processes using the given GPU.
+    pub fn new(gpu_handle: &GpuHandle) -> Self {
+        Self {
+            pci_slot_name: gpu_handle.get_pci_slot_name().map(str::to_owned),
+            proc_path: PathBuf::from("/proc"),
+            previous: None,
+        }
+    }
+
+    /// Sets the path of the procfs mount.
+    pub fn proc_path(mut self, path: PathBuf) -> Self {
+        self.proc_path = path;
+        self
+    }
+
+    /// Takes a snapshot and returns the usage since the previous call.
+    /// On the first call, engine utilization and VRAM deltas are not available.
+    pub fn update(&mut self) -> Result<Vec<ProcessUsage>> {
+        let now = Instant::now();
+        let current = self.collect()?;
+
+        let usage = match &self.previous {
+            Some((previous_time, previous)) => {
+                compute_usage(previous, &current, now.duration_since(*previous_time))
+            }
+            None => compute_usage(&HashMap::new(), &current, Duration::ZERO),
+        };
+
+        self.previous = Some((now, current));
+        Ok(usage)
+    }
+
+    /// Takes two snapshots separated by the given interval and returns the usage between them.
+    pub fn measure(&mut self, interval: Duration) -> Result<Vec<ProcessUsage>> {
+        self.previous = None;
+        self.update()?;
+        thread::sleep(interval);
+        self.update()
+    }
+
+    fn collect(&self) -> Result<HashMap<ProcessKey, ProcessSnapshot>> {
+        let mut processes = HashMap::new();
+
+        for entry in fs::read_dir(&self.proc_path)?.flatten() {
+            let Some(pid) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse().ok())
+            else {
+                continue;
+            };
+            // The process may exit or be inaccessible at any point, in which case it is skipped
+            if let Some((key, snapshot)) = self.collect_process(pid, &entry.path()) {
+                processes.insert(key, snapshot);
+            }
+        }
+
+        Ok(processes)
+    }
+
+    fn collect_process(&self, pid: u32, path: &Path) -> Option<(ProcessKey, ProcessSnapshot)> {
+        let mut clients = BTreeMap::new();
+
+        for fd_entry in fs::read_dir(path.join("fdinfo")).ok()?.flatten() {
+            let Ok(client) = fs::read_to_string(fd_entry.path())
+                .map_err(Error::from)
+                .and_then(|contents| contents.parse::<DrmClient>())
+            else {
+                continue;
+            };
+
+            if self.pci_slot_name.is_none() || client.pdev == self.pci_slot_name {
+                clients.insert(client.client_id, client);
+            }
+        }
+
+        if clients.is_empty() {
+            return None;
+        }
+
+        let (name, start_time) = parse_stat(&fs::read_to_string(path.join("stat")).ok()?)?;
+
+        let mut snapshot = ProcessSnapshot {
+            name,
+            engines: BTreeMap::new(),
+            vram: 0,
+        };
+        for client in clients.into_values() {
+            for (engine, time) in client.engines {
+                *snapshot.engines.entry(engine).or_default() += time;
+            }
+            snapshot.vram += client.memory.get("vram").copied().unwrap_or(0);
+        }
+
+        Some(((pid, start_time), snapshot))
+    }
+}
+
+/// Parses the process name and start time from `/proc/<pid>/stat`
+fn parse_stat(stat: &str) -> Option<(String, u64)> {
+    let name_start = stat.find('(')?;
+    let name_end = stat.rfind(')')?;
+    let name = stat.get(name_start + 1..name_end)?.to_owned();
+
+    // The start time is the 22nd field, and the fields after the name start from the 3rd one
+    let start_time = stat[name_end + 1..]
+        .split_whitespace()
+        .nth(19)?
+        .parse()
+        .ok()?;
+    Some((name, start_time))
+}
+
+fn compute_usage(
+    previous: &HashMap<ProcessKey, ProcessSnapshot>,
+    current: &HashMap<ProcessKey, ProcessSnapshot>,
+    elapsed: Duration,
+) -> Vec<ProcessUsage> {
+    let elapsed_ns = elapsed.as_nanos() as f64;
+
+    let mut usage: Vec<ProcessUsage> = current
+        .iter()
+        .map(|(key, snapshot)| {
+            let previous = previous.get(key);
+
+            let engines = match previous {
+                Some(previous) if elapsed_ns > 0.0 => snapshot
+                    .engines
+                    .iter()
+                    .map(|(engine, time)| {
+                        let previous_time = previous.engines.get(engine).copied().unwrap_or(0);
+                        let delta = time.saturating_sub(previous_time) as f64;
+                        (engine.clone(), delta / elapsed_ns * 100.0)
+                    })
+                    .collect(),
+                _ => BTreeMap::new(),
+            };
+            let vram_delta = previous
+                .map(|previous| snapshot.vram as i64 - previous.vram as i64)
+                .unwrap_or(0);
+
+            ProcessUsage {
+                pid: key.0,
+                name: snapshot.name.clone(),
+                engines,
+                vram: snapshot.vram,
+                vram_delta,
+            }
+        })
+        .collect();
+
+    usage.sort_by_key(|usage| usage.pid);
+    usage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compute_usage, parse_stat, DrmClient, ProcessSnapshot, ProcessTracker};
+    use crate::gpu_handle::GpuHandle;
+    use pretty_assertions::assert_eq;
+    use std::{
+        collections::{BTreeMap, HashMap},
+        fs,
+        time::Duration,
+    };
+
+    const FDINFO: &str = "pos:\t0
+flags:\t02100002
+mnt_id:\t24
+ino:\t1081
+drm-driver:\tamdgpu
+drm-client-id:\t5
+drm-pdev:\t0000:09:00.0
+pasid:\t32779
+drm-memory-vram:\t12348 KiB
+drm-memory-gtt: \t2048 KiB
+drm-memory-cpu: \t0 KiB
+amd-memory-visible-vram:\t12348 KiB
+drm-engine-gfx:\t1500000000 ns
+drm-engine-compute:\t0 ns
+";
+
+    #[test]
+    fn parse_fdinfo() {
+        let client: DrmClient = FDINFO.parse().unwrap();
+        assert_eq!(
+            DrmClient {
+                client_id: 5,
+                pdev: Some("0000:09:00.0".to_owned()),
+                engines: BTreeMap::from([
+                    ("compute".to_owned(), 0),
+                    ("gfx".to_owned(), 1500000000)
+                ]),
+                memory: BTreeMap::from([
+                    ("cpu".to_owned(), 0),
+                    ("gtt".to_owned(), 2048 * 1024),
+                    ("vram".to_owned(), 12348 * 1024)
+                ]),
+            },
+            client
+        );
+
+        let resident = "drm-driver: amdgpu\ndrm-client-id: 1\ndrm-resident-vram: 2 MiB\n";
+        let client: DrmClient = resident.parse().unwrap();
+        assert_eq!(Some(&(2 * 1024 * 1024)), client.memory.get("vram"));
+
+        let unknown_units = "drm-driver: amdgpu\ndrm-client-id: 1\ndrm-engine-gfx: 10 ns\ndrm-engine-capacity-gfx: 2\ndrm-engine-dma: 5 us\n";
+        let client: DrmClient = unknown_units.parse().unwrap();
+        assert_eq!(BTreeMap::from([("gfx".to_owned(), 10)]), client.engines);
+
+        assert!("pos: 0\nflags: 0\n".parse::<DrmClient>().is_err());
+        assert!("drm-driver: i915\ndrm-client-id: 1\n"
+            .parse::<DrmClient>()
+            .is_err());
+    }
+
+    #[test]
+    fn parse_process_stat() {
+        let stat = "1234 (some (weird) name) S 1 1234 1234 0 -1 4194560 2000 0 0 0 10 5 0 0 20 0 12 0 987654 1000000 500";
+        assert_eq!(
+            Some(("some (weird) name".to_owned(), 987654)),
+            parse_stat(stat)
+        );
+    }
+
+    #[test]
+    fn usage_deltas() {
+        let snapshot = |gfx: u64, vram: u64| ProcessSnapshot {
+            name: "game".to_owned(),
+            engines: BTreeMap::from([("gfx".to_owned(), gfx)]),
+            vram,
+        };
+        let previous = HashMap::from([
+            ((10, 100), snapshot(1_000_000_000, 1000)),
+            ((20, 200), snapshot(0, 5000)),
+        ]);
+        let current = HashMap::from([
+            ((10, 100), snapshot(1_500_000_000, 1500)),
+            // Reused pid with a different start time
+            ((20, 300), snapshot(5_000_000_000, 100)),
+        ]);
+
+        let usage = compute_usage(&previous, &current, Duration::from_secs(1));
+        assert_eq!(2, usage.len());
+        assert_eq!(Some(&50.0), usage[0].engines.get("gfx"));
+        assert_eq!(500, usage[0].vram_delta);
+        assert!(usage[1].engines.is_empty());
+        assert_eq!(0, usage[1].vram_delta);
+    }
+
+    #[test]
+    fn track_processes() {
+        let dir = tempfile::tempdir().unwrap();
+        let device = dir.path().join("device");
+        fs::create_dir(&device).unwrap();
+        fs::write(
+            device.join("uevent"),
+            "DRIVER=amdgpu\nPCI_SLOT_NAME=0000:09:00.0\n",
+        )
+        .unwrap();
+        let gpu_handle = GpuHandle::new_from_path(device).unwrap();
+
+        let proc_path = dir.path().join("proc");
+        let process = proc_path.join("1234");
+        fs::create_dir_all(process.join("fdinfo")).unwrap();
+        fs::create_dir_all(proc_path.join("self")).unwrap();
+        fs::write(
+            process.join("stat"),
+            "1234 (game) S 1 1234 1234 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 5000 0 0",
+        )
+        .unwrap();
+        fs::write(process.join("fdinfo/0"), "pos: 0\n").unwrap();
+        fs::write(process.join("fdinfo/5"), FDINFO).unwrap();
+        // Same client referred to by another descriptor
+        fs::write(process.join("fdinfo/6"), FDINFO).unwrap();
+        fs::write(
+            process.join("fdinfo/7"),
+            FDINFO.replace("0000:09:00.0", "0000:03:00.0"),
+        )
+        .unwrap();
+
+        let mut tracker = ProcessTracker::new(&gpu_handle).proc_path(proc_path);
+        let usage = tracker.update().unwrap();
+        assert_eq!(1, usage.len());
+        assert_eq!(1234, usage[0].pid);
+        assert_eq!("game", usage[0].name);
+        assert_eq!(12348 * 1024, usage[0].vram);
+        assert!(usage[0].engines.is_empty());
+
+        let usage = tracker.update().unwrap();
+        assert_eq!(Some(&0.0), usage[0].engines.get("gfx"));
+    }
+}
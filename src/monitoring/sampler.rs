@@ -0,0 +1,286 @@
+use crate::{gpu_handle::GpuHandle, hw_mon::Temperature, sysfs::SysFS, Result};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display},
+    ops::ControlFlow,
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// A metric which can be collected by the [`Sampler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Metric {
+    /// GPU usage percentage
+    BusyPercent,
+    /// Used and total VRAM
+    Vram,
+    /// All temperature sensors
+    Temperatures,
+    /// Power usage
+    Power,
+    /// Core and memory clockspeeds
+    Clockspeeds,
+    /// Fan speed and PWM
+    Fan,
+}
+
+impl Metric {
+    /// All of the available metrics.
+    pub const ALL: [Metric; 6] = [
+        Metric::BusyPercent,
+        Metric::Vram,
+        Metric::Temperatures,
+        Metric::Power,
+        Metric::Clockspeeds,
+        Metric::Fan,
+    ];
+}
+
+impl Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Metric::BusyPercent => "busy_percent",
+            Metric::Vram => "vram",
+            Metric::Temperatures => "temperatures",
+            Metric::Power => "power",
+            Metric::Clockspeeds => "clockspeeds",
+            Metric::Fan => "fan",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Values collected at a single point in time.
+///
+/// Values of metrics which were not requested or could not be read are empty.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Sample {
+    /// Wall clock time of the sample
+    pub timestamp: SystemTime,
+    /// Time since the sampler was created
+    pub elapsed: Duration,
+    /// GPU usage percentage
+    pub busy_percent: Option<u8>,
+    /// Used VRAM in bytes
+    pub vram_used: Option<u64>,
+    /// Total VRAM in bytes
+    pub vram_total: Option<u64>,
+    /// Temperatures indexed by the sensor label
+    pub temperatures: BTreeMap<String, Temperature>,
+    /// Power usage in watts
+    pub power: Option<f64>,
+    /// Power cap in watts
+    pub power_cap: Option<f64>,
+    /// Core clockspeed in MHz
+    pub gpu_clockspeed: Option<u64>,
+    /// Memory clockspeed in MHz
+    pub vram_clockspeed: Option<u64>,
+    /// Fan speed in RPM
+    pub fan_rpm: Option<u32>,
+    /// Fan PWM value (0-255)
+    pub fan_pwm: Option<u8>,
+    /// Errors which happened when reading the metrics.
+    /// A metric failing to be read does not affect the other ones.
+    pub errors: BTreeMap<Metric, String>,
+}
+
+impl Sample {
+    pub(crate) fn empty(timestamp: SystemTime, elapsed: Duration) -> Self {
+        Self {
+            timestamp,
+            elapsed,
+            busy_percent: None,
+            vram_used: None,
+            vram_total: None,
+            temperatures: BTreeMap::new(),
+            power: None,
+            power_cap: None,
+            gpu_clockspeed: None,
+            vram_clockspeed: None,
+            fan_rpm: None,
+            fan_pwm: None,
+            errors: BTreeMap::new(),
+        }
+    }
+}
+
+/// Periodically collects metrics from a GPU.
+///
+/// ```no_run
+/// use amdgpu_sysfs::{gpu_handle::GpuHandle, monitoring::{Metric, Sampler}};
+/// use std::{path::PathBuf, time::Duration};
+///
+/// let gpu_handle = GpuHandle::new_from_path(PathBuf::from("/sys/class/drm/card0/device")).unwrap();
+/// let sampler = Sampler::new(gpu_handle)
+///     .metrics([Metric::BusyPercent, Metric::Power])
+///     .interval(Duration::from_millis(500));
+///
+/// for sample in sampler.iter().take(10) {
+///     println!("{:?}% {:?}W", sample.busy_percent, sample.power);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    gpu_handle: GpuHandle,
+    metrics: BTreeSet<Metric>,
+    interval: Duration,
+    start: Instant,
+}
+
+impl Sampler {
+    /// Creates a sampler which collects all metrics every second.
+    pub fn new(gpu_handle: GpuHandle) -> Self {
+        Self {
+            gpu_handle,
+            metrics: Metric::ALL.into_iter().collect(),
+            interval: Duration::from_secs(1),
+            start: Instant::now(),
+        }
+    }
+
+    /// Sets the metrics to be collected.
+    pub fn metrics(mut self, metrics: impl IntoIterator<Item = Metric>) -> Self {
+        self.metrics = metrics.into_iter().collect();
+        self
+    }
+
+    /// Sets the interval between samples.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Gets the interval between samples.
+    pub fn get_interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Gets the GPU handle used for sampling.
+    pub fn gpu_handle(&self) -> &GpuHandle {
+        &self.gpu_handle
+    }
+
+    /// Collects a single sample immediately.
+    pub fn sample(&self) -> Sample {
+        read_sample(
+            &self.gpu_handle,
+            self.metrics.iter().copied(),
+            self.start.elapsed(),
+        )
+    }
+
+    /// Returns an endless iterator of samples. The first sample is collected immediately,
+    /// and each following one blocks until the interval has passed.
+    pub fn iter(&self) -> SampleIter<'_> {
+        SampleIter {
+            sampler: self,
+            next_deadline: None,
+        }
+    }
+
+    /// Collects samples at the configured interval and passes them to the callback, until it returns [`ControlFlow::Break`].
+    pub fn run<F>(&self, mut callback: F)
+    where
+        F: FnMut(&Sample) -> ControlFlow<()>,
+    {
+        for sample in self.iter() {
+            if callback(&sample).is_break() {
+                break;
+            }
+        }
+    }
+}
+
+/// Reads the given metrics into a new sample.
+pub(crate) fn read_sample(
+    gpu_handle: &GpuHandle,
+    metrics: impl IntoIterator<Item = Metric>,
+    elapsed: Duration,
+) -> Sample {
+    let mut sample = Sample::empty(SystemTime::now(), elapsed);
+
+    for metric in metrics {
+        if let Err(err) = read_metric(gpu_handle, metric, &mut sample) {
+            sample.errors.insert(metric, err.to_string());
+        }
+    }
+
+    sample
+}
+
+fn read_metric(gpu_handle: &GpuHandle, metric: Metric, sample: &mut Sample) -> Result<()> {
+    match metric {
+        Metric::BusyPercent => {
+            sample.busy_percent = Some(gpu_handle.get_activity()?.percent);
+        }
+        Metric::Vram => {
+            sample.vram_used = Some(gpu_handle.get_used_vram()?);
+            sample.vram_total = Some(gpu_handle.get_total_vram()?);
+        }
+        Metric::Temperatures => {
+            sample.temperatures = gpu_handle.with_hw_mon(|hw_mon| {
+                // Missing sensors are skipped when reading temperatures, so a stale monitor has to be detected separately
+                hw_mon.read_file("name")?;
+                Ok(hw_mon.get_temps().into_iter().collect())
+            })?;
+        }
+        Metric::Power => {
+            let (power, power_cap) = gpu_handle.with_hw_mon(|hw_mon| {
+                let power = hw_mon
+                    .get_power_average()
+                    .or_else(|_| hw_mon.get_power_input())?;
+                Ok((power, hw_mon.get_power_cap().ok()))
+            })?;
+            sample.power = Some(power);
+            sample.power_cap = power_cap;
+        }
+        Metric::Clockspeeds => {
+            let (gpu_clockspeed, vram_clockspeed) = gpu_handle.with_hw_mon(|hw_mon| {
+                Ok((
+                    hw_mon.get_gpu_clockspeed()?,
+                    hw_mon.get_vram_clockspeed().ok(),
+                ))
+            })?;
+            sample.gpu_clockspeed = Some(gpu_clockspeed);
+            sample.vram_clockspeed = vram_clockspeed;
+        }
+        Metric::Fan => {
+            let (fan_rpm, fan_pwm) = gpu_handle
+                .with_hw_mon(|hw_mon| Ok((hw_mon.get_fan_current()?, hw_mon.get_fan_pwm().ok())))?;
+            sample.fan_rpm = Some(fan_rpm);
+            sample.fan_pwm = fan_pwm;
+        }
+    }
+    Ok(())
+}
+
+/// Iterator over samples, see [`Sampler::iter`].
+#[derive(Debug)]
+pub struct SampleIter<'a> {
+    sampler: &'a Sampler,
+    next_deadline: Option<Instant>,
+}
+
+impl Iterator for SampleIter<'_> {
+    type Item = Sample;
+
+    fn next(&mut self) -> Option<Sample> {
+        if let Some(deadline) = self.next_deadline {
+            thread::sleep(deadline.saturating_duration_since(Instant::now()));
+        }
+
+        let now = Instant::now();
+        let deadline = self.next_deadline.unwrap_or(now) + self.sampler.interval;
+        // Don't try to catch up if sampling took longer than the interval
+        self.next_deadline = Some(deadline.max(now));
+
+        Some(self.sampler.sample())
+    }
+}
@@ -0,0 +1,279 @@
+use super::{Metric, Sample};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    time::Duration,
+};
+
+/// Aggregated values of a single metric.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Aggregate {
+    /// The most recent value
+    pub current: f64,
+    /// The lowest value
+    pub min: f64,
+    /// The highest value
+    pub max: f64,
+    /// The average value
+    pub average: f64,
+    /// How many values were aggregated
+    pub count: usize,
+}
+
+/// Statistics derived from samples produced by a [`Sampler`](super::Sampler).
+///
+/// Aggregates are calculated over a rolling window of the most recent samples,
+/// while counters accumulate over the entire lifetime of the statistics.
+#[derive(Debug, Clone)]
+pub struct Statistics {
+    window: Duration,
+    samples: VecDeque<Sample>,
+    last_elapsed: Option<Duration>,
+    sample_count: u64,
+    throttled_time: Duration,
+    /// Temperature sensors which were above their critical limit and have not cooled down past the hysteresis yet
+    throttled_sensors: BTreeSet<String>,
+    error_counts: BTreeMap<Metric, u64>,
+}
+
+impl Statistics {
+    /// Creates statistics which aggregate samples over the given window.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+            last_elapsed: None,
+            sample_count: 0,
+            throttled_time: Duration::ZERO,
+            throttled_sensors: BTreeSet::new(),
+            error_counts: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a sample. Samples are expected to come from the same sampler in chronological order.
+    pub fn push(&mut self, sample: &Sample) {
+        self.throttled_sensors = throttled_sensors(sample, &self.throttled_sensors);
+        if let Some(last_elapsed) = self.last_elapsed {
+            if !self.throttled_sensors.is_empty() {
+                self.throttled_time += sample.elapsed.saturating_sub(last_elapsed);
+            }
+        }
+        self.last_elapsed = Some(sample.elapsed);
+        self.sample_count += 1;
+
+        for metric in sample.errors.keys() {
+            *self.error_counts.entry(*metric).or_default() += 1;
+        }
+
+        self.samples.push_back(sample.clone());
+        while self
+            .samples
+            .front()
+            .is_some_and(|oldest| sample.elapsed.saturating_sub(oldest.elapsed) > self.window)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Aggregates a value from the samples in the current window.
+    /// Samples where the value is missing are skipped.
+    pub fn aggregate<F>(&self, f: F) -> Option<Aggregate>
+    where
+        F: Fn(&Sample) -> Option<f64>,
+    {
+        let mut values = self.samples.iter().filter_map(f);
+        let first = values.next()?;
+
+        let mut aggregate = Aggregate {
+            current: first,
+            min: first,
+            max: first,
+            average: first,
+            count: 1,
+        };
+        let mut sum = first;
+
+        for value in values {
+            aggregate.current = value;
+            aggregate.min = aggregate.min.min(value);
+            aggregate.max = aggregate.max.max(value);
+            aggregate.count += 1;
+            sum += value;
+        }
+        aggregate.average = sum / aggregate.count as f64;
+
+        Some(aggregate)
+    }
+
+    /// GPU usage percentage
+    pub fn busy_percent(&self) -> Option<Aggregate> {
+        self.aggregate(|sample| sample.busy_percent.map(f64::from))
+    }
+
+    /// Used VRAM in bytes
+    pub fn vram_used(&self) -> Option<Aggregate> {
+        self.aggregate(|sample| sample.vram_used.map(|value| value as f64))
+    }
+
+    /// Temperature of the sensor with the given label in degrees Celsius
+    pub fn temperature(&self, label: &str) -> Option<Aggregate> {
+        self.aggregate(|sample| {
+            sample
+                .temperatures
+                .get(label)
+                .and_then(|temp| temp.current)
+                .map(f64::from)
+        })
+    }
+
+    /// Power usage in watts
+    pub fn power(&self) -> Option<Aggregate> {
+        self.aggregate(|sample| sample.power)
+    }
+
+    /// Core clockspeed in MHz
+    pub fn gpu_clockspeed(&self) -> Option<Aggregate> {
+        self.aggregate(|sample| sample.gpu_clockspeed.map(|value| value as f64))
+    }
+
+    /// Memory clockspeed in MHz
+    pub fn vram_clockspeed(&self) -> Option<Aggregate> {
+        self.aggregate(|sample| sample.vram_clockspeed.map(|value| value as f64))
+    }
+
+    /// Fan speed in RPM
+    pub fn fan_rpm(&self) -> Option<Aggregate> {
+        self.aggregate(|sample| sample.fan_rpm.map(f64::from))
+    }
+
+    /// Total number of samples that were added.
+    pub fn sample_count(&self) -> u64 {
+        self.sample_count
+    }
+
+    /// Total time during which the GPU was thermally throttled.
+    ///
+    /// The GPU is considered throttled when any of the temperature sensors reaches its critical temperature,
+    /// and stays throttled until the sensor cools down below its critical hysteresis temperature (if reported).
+    pub fn throttled_time(&self) -> Duration {
+        self.throttled_time
+    }
+
+    /// Number of samples in which each metric failed to be read.
+    pub fn error_counts(&self) -> &BTreeMap<Metric, u64> {
+        &self.error_counts
+    }
+
+    /// Clears all of the samples and counters.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.window);
+    }
+}
+
+/// Finds the sensors which are throttled in the sample, given the ones which were throttled in the previous sample.
+fn throttled_sensors(sample: &Sample, previous: &BTreeSet<String>) -> BTreeSet<String> {
+    sample
+        .temperatures
+        .iter()
+        .filter(
+            |(name, temp)| match (temp.current, temp.crit, temp.crit_hyst) {
+                (Some(current), Some(crit), _) if current >= crit => true,
+                (Some(current), _, Some(crit_hyst)) => {
+                    previous.contains(*name) && current > crit_hyst
+                }
+                _ => false,
+            },
+        )
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Aggregate, Statistics};
+    use crate::{
+        hw_mon::Temperature,
+        monitoring::{Metric, Sample},
+    };
+    use pretty_assertions::assert_eq;
+    use std::time::{Duration, SystemTime};
+
+    fn sample(secs: u64, power: Option<f64>, temp: f32) -> Sample {
+        sample_with_hyst(secs, power, temp, None)
+    }
+
+    fn sample_with_hyst(
+        secs: u64,
+        power: Option<f64>,
+        temp: f32,
+        crit_hyst: Option<f32>,
+    ) -> Sample {
+        let mut sample = Sample::empty(SystemTime::UNIX_EPOCH, Duration::from_secs(secs));
+        sample.power = power;
+        sample.temperatures.insert(
+            "junction".to_owned(),
+            Temperature {
+                current: Some(temp),
+                crit: Some(100.0),
+                crit_hyst,
+            },
+        );
+        if power.is_none() {
+            sample.errors.insert(Metric::Power, "missing".to_owned());
+        }
+        sample
+    }
+
+    #[test]
+    fn rolling_aggregate() {
+        let mut stats = Statistics::new(Duration::from_secs(2));
+        stats.push(&sample(0, Some(100.0), 50.0));
+        stats.push(&sample(1, Some(200.0), 60.0));
+        stats.push(&sample(2, None, 70.0));
+        stats.push(&sample(3, Some(150.0), 80.0));
+
+        assert_eq!(
+            Some(Aggregate {
+                current: 150.0,
+                min: 150.0,
+                max: 200.0,
+                average: 175.0,
+                count: 2,
+            }),
+            stats.power()
+        );
+        assert_eq!(Some(60.0), stats.temperature("junction").map(|agg| agg.min));
+        assert_eq!(None, stats.temperature("edge"));
+        assert_eq!(4, stats.sample_count());
+        assert_eq!(Some(&1), stats.error_counts().get(&Metric::Power));
+    }
+
+    #[test]
+    fn throttled_time() {
+        let mut stats = Statistics::new(Duration::from_secs(10));
+        stats.push(&sample(0, None, 100.0));
+        stats.push(&sample(2, None, 101.0));
+        stats.push(&sample(3, None, 90.0));
+        stats.push(&sample(6, None, 100.0));
+        assert_eq!(Duration::from_secs(5), stats.throttled_time());
+
+        stats.reset();
+        assert_eq!(Duration::ZERO, stats.throttled_time());
+        assert_eq!(None, stats.power());
+    }
+
+    #[test]
+    fn throttled_time_hysteresis() {
+        let mut stats = Statistics::new(Duration::from_secs(10));
+        stats.push(&sample_with_hyst(0, None, 95.0, Some(90.0)));
+        stats.push(&sample_with_hyst(1, None, 100.0, Some(90.0)));
+        stats.push(&sample_with_hyst(3, None, 95.0, Some(90.0)));
+        stats.push(&sample_with_hyst(4, None, 92.0, Some(90.0)));
+        stats.push(&sample_with_hyst(6, None, 89.0, Some(90.0)));
+        stats.push(&sample_with_hyst(7, None, 95.0, Some(90.0)));
+        assert_eq!(Duration::from_secs(4), stats.throttled_time());
+    }
+}
@@ -0,0 +1,387 @@
+use super::Sample;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+
+/// A value from a [`Sample`] which can be used in a [`Condition`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Value {
+    /// GPU usage percentage
+    BusyPercent,
+    /// Used VRAM in bytes
+    VramUsed,
+    /// Temperature of the sensor with the given label in degrees Celsius
+    Temperature(String),
+    /// Power usage in watts
+    Power,
+    /// Power cap in watts
+    PowerCap,
+    /// Core clockspeed in MHz
+    GpuClockspeed,
+    /// Memory clockspeed in MHz
+    VramClockspeed,
+    /// Fan speed in RPM
+    FanRpm,
+    /// Fan PWM value
+    FanPwm,
+}
+
+impl Value {
+    /// Gets the value from a sample.
+    pub fn get(&self, sample: &Sample) -> Option<f64> {
+        match self {
+            Value::BusyPercent => sample.busy_percent.map(f64::from),
+            Value::VramUsed => sample.vram_used.map(|value| value as f64),
+            Value::Temperature(label) => sample
+                .temperatures
+                .get(label)
+                .and_then(|temp| temp.current)
+                .map(f64::from),
+            Value::Power => sample.power,
+            Value::PowerCap => sample.power_cap,
+            Value::GpuClockspeed => sample.gpu_clockspeed.map(|value| value as f64),
+            Value::VramClockspeed => sample.vram_clockspeed.map(|value| value as f64),
+            Value::FanRpm => sample.fan_rpm.map(f64::from),
+            Value::FanPwm => sample.fan_pwm.map(f64::from),
+        }
+    }
+}
+
+/// A condition evaluated on every sample.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Condition {
+    /// The value is higher than the threshold
+    Above(Value, f64),
+    /// The value is lower than the threshold
+    Below(Value, f64),
+    /// The first value is higher than the second one
+    Exceeds(Value, Value),
+    /// All of the conditions are met
+    All(Vec<Condition>),
+    /// Any of the conditions is met
+    Any(Vec<Condition>),
+}
+
+impl Condition {
+    /// Evaluates the condition with the thresholds relaxed by the given margin.
+    /// Returns `None` if the sample is missing a value needed for the evaluation.
+    fn evaluate(&self, sample: &Sample, margin: f64) -> Option<bool> {
+        match self {
+            Condition::Above(value, threshold) => Some(value.get(sample)? > threshold - margin),
+            Condition::Below(value, threshold) => Some(value.get(sample)? < threshold + margin),
+            Condition::Exceeds(value, limit) => {
+                Some(value.get(sample)? > limit.get(sample)? - margin)
+            }
+            Condition::All(conditions) => {
+                let mut result = Some(true);
+                for condition in conditions {
+                    match condition.evaluate(sample, margin) {
+                        Some(false) => return Some(false),
+                        None => result = None,
+                        Some(true) => (),
+                    }
+                }
+                result
+            }
+            Condition::Any(conditions) => {
+                let mut result = Some(false);
+                for condition in conditions {
+                    match condition.evaluate(sample, margin) {
+                        Some(true) => return Some(true),
+                        None => result = None,
+                        Some(false) => (),
+                    }
+                }
+                result
+            }
+        }
+    }
+}
+
+/// A condition to be watched, along with its debouncing and hysteresis settings.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Watch {
+    /// The condition which triggers the watch
+    pub condition: Condition,
+    /// How long the condition has to be met (or not met) before the watch is triggered (or cleared)
+    pub debounce: Duration,
+    /// How far past the thresholds the values have to go before a triggered watch is cleared
+    pub hysteresis: f64,
+}
+
+impl Watch {
+    /// Creates a watch which triggers and clears immediately.
+    pub fn new(condition: Condition) -> Self {
+        Self {
+            condition,
+            debounce: Duration::ZERO,
+            hysteresis: 0.0,
+        }
+    }
+
+    /// Sets the debounce duration.
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Sets the hysteresis margin.
+    pub fn hysteresis(mut self, hysteresis: f64) -> Self {
+        self.hysteresis = hysteresis;
+        self
+    }
+}
+
+/// Identifier of a watch registered in a [`Watcher`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct WatchId(usize);
+
+/// Kind of a watch event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum EventKind {
+    /// The condition started being met
+    Triggered,
+    /// The condition stopped being met
+    Cleared,
+}
+
+/// A change in the state of a watch.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Event {
+    /// The watch that changed
+    pub watch: WatchId,
+    /// Kind of the change
+    pub kind: EventKind,
+    /// Wall clock time of the sample which caused the change
+    pub timestamp: SystemTime,
+    /// Time since the sampler was created
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone)]
+struct WatchState {
+    watch: Watch,
+    active: bool,
+    pending_since: Option<Duration>,
+}
+
+/// Evaluates watches on samples produced by a [`Sampler`](super::Sampler) and reports their state changes.
+///
+/// ```no_run
+/// use amdgpu_sysfs::{
+///     gpu_handle::GpuHandle,
+///     monitoring::{Condition, Sampler, Value, Watch, Watcher},
+/// };
+/// use std::{ops::ControlFlow, path::PathBuf, time::Duration};
+///
+/// let gpu_handle = GpuHandle::new_from_path(PathBuf::from("/sys/class/drm/card0/device")).unwrap();
+/// let mut watcher = Watcher::new();
+/// let hot = watcher.add(
+///     Watch::new(Condition::Above(Value::Temperature("junction".to_owned()), 95.0))
+///         .debounce(Duration::from_secs(3))
+///         .hysteresis(5.0),
+/// );
+///
+/// Sampler::new(gpu_handle).run(|sample| {
+///     for event in watcher.process(sample) {
+///         if event.watch == hot {
+///             println!("junction temperature: {:?}", event.kind);
+///         }
+///     }
+///     ControlFlow::Continue(())
+/// });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Watcher {
+    watches: Vec<WatchState>,
+}
+
+impl Watcher {
+    /// Creates a watcher without any watches.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a watch.
+    pub fn add(&mut self, watch: Watch) -> WatchId {
+        self.watches.push(WatchState {
+            watch,
+            active: false,
+            pending_since: None,
+        });
+        WatchId(self.watches.len() - 1)
+    }
+
+    /// Checks if the watch is currently triggered.
+    pub fn is_active(&self, id: WatchId) -> bool {
+        self.watches.get(id.0).is_some_and(|state| state.active)
+    }
+
+    /// Evaluates all watches on a sample and returns the resulting state changes.
+    ///
+    /// Samples missing a value needed by a watch leave its state unchanged.
+    pub fn process(&mut self, sample: &Sample) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        for (i, state) in self.watches.iter_mut().enumerate() {
+            let margin = if state.active {
+                state.watch.hysteresis
+            } else {
+                0.0
+            };
+
+            match state.watch.condition.evaluate(sample, margin) {
+                Some(met) if met != state.active => {
+                    let pending_since = *state.pending_since.get_or_insert(sample.elapsed);
+
+                    if sample.elapsed.saturating_sub(pending_since) >= state.watch.debounce {
+                        state.active = met;
+                        state.pending_since = None;
+
+                        events.push(Event {
+                            watch: WatchId(i),
+                            kind: if met {
+                                EventKind::Triggered
+                            } else {
+                                EventKind::Cleared
+                            },
+                            timestamp: sample.timestamp,
+                            elapsed: sample.elapsed,
+                        });
+                    }
+                }
+                Some(_) => state.pending_since = None,
+                None => (),
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Condition, EventKind, Value, Watch, Watcher};
+    use crate::{hw_mon::Temperature, monitoring::Sample};
+    use pretty_assertions::assert_eq;
+    use std::time::{Duration, SystemTime};
+
+    fn sample(secs: u64, temp: f32, fan_rpm: Option<u32>) -> Sample {
+        let mut sample = Sample::empty(SystemTime::UNIX_EPOCH, Duration::from_secs(secs));
+        sample.fan_rpm = fan_rpm;
+        sample.temperatures.insert(
+            "junction".to_owned(),
+            Temperature {
+                current: Some(temp),
+                crit: None,
+                crit_hyst: None,
+            },
+        );
+        sample
+    }
+
+    fn kinds(watcher: &mut Watcher, sample: &Sample) -> Vec<EventKind> {
+        watcher
+            .process(sample)
+            .into_iter()
+            .map(|event| event.kind)
+            .collect()
+    }
+
+    #[test]
+    fn hysteresis() {
+        let mut watcher = Watcher::new();
+        let id = watcher.add(
+            Watch::new(Condition::Above(
+                Value::Temperature("junction".to_owned()),
+                95.0,
+            ))
+            .hysteresis(5.0),
+        );
+
+        assert_eq!(kinds(&mut watcher, &sample(0, 90.0, None)), vec![]);
+        assert_eq!(
+            kinds(&mut watcher, &sample(1, 96.0, None)),
+            vec![EventKind::Triggered]
+        );
+        assert!(watcher.is_active(id));
+        assert_eq!(kinds(&mut watcher, &sample(2, 92.0, None)), vec![]);
+        assert_eq!(
+            kinds(&mut watcher, &sample(3, 89.0, None)),
+            vec![EventKind::Cleared]
+        );
+        assert!(!watcher.is_active(id));
+    }
+
+    #[test]
+    fn debounce() {
+        let mut watcher = Watcher::new();
+        watcher.add(
+            Watch::new(Condition::Above(
+                Value::Temperature("junction".to_owned()),
+                95.0,
+            ))
+            .debounce(Duration::from_secs(2)),
+        );
+
+        assert_eq!(kinds(&mut watcher, &sample(0, 96.0, None)), vec![]);
+        assert_eq!(kinds(&mut watcher, &sample(1, 90.0, None)), vec![]);
+        assert_eq!(kinds(&mut watcher, &sample(2, 96.0, None)), vec![]);
+        assert_eq!(kinds(&mut watcher, &sample(3, 96.0, None)), vec![]);
+        assert_eq!(
+            kinds(&mut watcher, &sample(4, 97.0, None)),
+            vec![EventKind::Triggered]
+        );
+    }
+
+    #[test]
+    fn combined_conditions() {
+        let mut watcher = Watcher::new();
+        let id = watcher.add(Watch::new(Condition::All(vec![
+            Condition::Below(Value::FanRpm, 300.0),
+            Condition::Above(Value::Temperature("junction".to_owned()), 60.0),
+        ])));
+
+        assert_eq!(kinds(&mut watcher, &sample(0, 70.0, Some(1000))), vec![]);
+        assert_eq!(
+            kinds(&mut watcher, &sample(1, 70.0, Some(0))),
+            vec![EventKind::Triggered]
+        );
+        // A missing value keeps the current state
+        assert_eq!(kinds(&mut watcher, &sample(2, 70.0, None)), vec![]);
+        assert!(watcher.is_active(id));
+        assert_eq!(
+            kinds(&mut watcher, &sample(3, 50.0, None)),
+            vec![EventKind::Cleared]
+        );
+    }
+
+    #[test]
+    fn power_above_cap() {
+        let mut watcher = Watcher::new();
+        watcher.add(Watch::new(Condition::Exceeds(
+            Value::Power,
+            Value::PowerCap,
+        )));
+
+        let mut sample = sample(0, 50.0, None);
+        sample.power = Some(210.0);
+        sample.power_cap = Some(200.0);
+        assert_eq!(kinds(&mut watcher, &sample), vec![EventKind::Triggered]);
+    }
+}
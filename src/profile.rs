@@ -0,0 +1,757 @@
+//! Capturing and applying complete sets of GPU settings
+//!
+//! A [`TuningProfile`] holds the user-adjustable settings of a GPU, and can be saved (with the `serde` feature)
+//! and applied again later, for example at boot.
+#[cfg(feature = "overdrive")]
+use crate::gpu_handle::overdrive::{
+    check_value_in_range, ClocksTable, ClocksTableGen, NormalizedTable,
+};
+use crate::{
+    error::{Error, ErrorKind},
+    gpu_handle::{
+        fan_control::{CurvePoint, PmfwSettings},
+        GpuHandle, PerformanceLevel, PowerLevelKind,
+    },
+    hw_mon::HwMon,
+    Result,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+
+/// A set of GPU settings. Settings which are empty are left unchanged when applying the profile.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TuningProfile {
+    /// Performance level
+    pub performance_level: Option<PerformanceLevel>,
+    /// Enabled power levels of each kind. Requires the performance level to be [`PerformanceLevel::Manual`].
+    ///
+    /// This is not captured from the GPU, as the kernel does not report which levels are enabled.
+    pub enabled_power_levels: BTreeMap<PowerLevelKind, Vec<u8>>,
+    /// Clocks table values
+    #[cfg(feature = "overdrive")]
+    pub clocks: Option<ClocksSettings>,
+    /// Power cap in watts
+    pub power_cap: Option<f64>,
+    /// PMFW fan settings
+    pub fan: Option<PmfwSettings>,
+}
+
+/// Values of the clocks table. Each value is `None` if it should be left unchanged.
+#[cfg(feature = "overdrive")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ClocksSettings {
+    /// Minimum core clock in MHz
+    pub min_sclk: Option<i32>,
+    /// Maximum core clock in MHz
+    pub max_sclk: Option<i32>,
+    /// Minimum memory clock in MHz
+    pub min_mclk: Option<i32>,
+    /// Maximum memory clock in MHz
+    pub max_mclk: Option<i32>,
+    /// Voltage offset in mV
+    pub voltage_offset: Option<i32>,
+}
+
+#[cfg(feature = "overdrive")]
+impl ClocksSettings {
+    /// Applies the settings to a clocks table, checking that the values are in the allowed ranges.
+    pub fn apply_to_table(&self, table: &mut ClocksTableGen) -> Result<()> {
+        if let Some(clockspeed) = self.min_sclk {
+            table.set_min_sclk(clockspeed)?;
+        }
+        if let Some(clockspeed) = self.max_sclk {
+            table.set_max_sclk(clockspeed)?;
+        }
+        if let Some(clockspeed) = self.min_mclk {
+            table.set_min_mclk(clockspeed)?;
+        }
+        if let Some(clockspeed) = self.max_mclk {
+            table.set_max_mclk(clockspeed)?;
+        }
+        if let Some(offset) = self.voltage_offset {
+            let ClocksTableGen::Vega20(table) = table else {
+                return Err(ErrorKind::Unsupported(
+                    "Voltage offset is not supported on this GPU".to_owned(),
+                )
+                .into());
+            };
+            let range = table.od_range.voltage_offset.ok_or_else(|| {
+                ErrorKind::Unsupported("Voltage offset is not supported on this GPU".to_owned())
+            })?;
+            check_value_in_range(range, offset, "voltage offset")?;
+            table.voltage_offset = Some(offset);
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the minimum clocks of the table are not higher than the maximum ones.
+#[cfg(feature = "overdrive")]
+fn check_clocks_order(table: &ClocksTableGen) -> Result<()> {
+    for (name, range) in [
+        ("core clock", table.get_current_sclk_range()),
+        ("memory clock", table.get_current_mclk_range()),
+    ] {
+        if let Some((min, max)) = range.into_full() {
+            if min > max {
+                return Err(Error::not_allowed(format!(
+                    "Minimum {name} {min} is higher than the maximum {max}"
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "overdrive")]
+impl From<&NormalizedTable> for ClocksSettings {
+    fn from(table: &NormalizedTable) -> Self {
+        Self {
+            min_sclk: table.sclk.min,
+            max_sclk: table.sclk.max,
+            min_mclk: table.mclk.min,
+            max_mclk: table.mclk.max,
+            voltage_offset: table.voltage_offset,
+        }
+    }
+}
+
+impl TuningProfile {
+    /// Reads the current settings of the GPU. Settings which are not supported on the GPU are left empty.
+    pub fn capture(gpu_handle: &GpuHandle) -> Result<Self> {
+        let fan = optional(gpu_handle.get_pmfw_settings())?
+            .map(|mut settings| {
+                // A curve which was never configured cannot be applied back
+                if settings
+                    .curve
+                    .as_ref()
+                    .is_some_and(|curve| curve.is_default())
+                {
+                    settings.curve = None;
+                }
+                settings
+            })
+            .filter(|settings| *settings != PmfwSettings::default());
+
+        Ok(Self {
+            performance_level: optional(gpu_handle.get_power_force_performance_level())?,
+            enabled_power_levels: BTreeMap::new(),
+            #[cfg(feature = "overdrive")]
+            clocks: optional(gpu_handle.get_clocks_table())?
+                .map(|table| ClocksSettings::from(&NormalizedTable::from(&table))),
+            power_cap: optional(gpu_handle.with_hw_mon(HwMon::get_power_cap))?,
+            fan,
+        })
+    }
+
+    /// Checks that the profile can be applied on the GPU, without changing anything.
+    ///
+    /// Returns the first problem found, see [`TuningProfile::validate_for`] for a full report.
+    pub fn validate(&self, gpu_handle: &GpuHandle) -> Result<()> {
+        self.setting_checks(gpu_handle)
+            .into_iter()
+            .try_for_each(|(_, result)| result)
+    }
+
+    /// Checks every setting in the profile against the capabilities of the GPU, without changing anything.
+    ///
+    /// Unlike [`TuningProfile::validate`], this does not stop at the first problem,
+    /// which is useful for reporting everything that needs to be fixed in an imported profile.
+    pub fn validate_for(&self, gpu_handle: &GpuHandle) -> ValidationReport {
+        let issues = self
+            .setting_checks(gpu_handle)
+            .into_iter()
+            .filter_map(|(setting, result)| {
+                let err = result.err()?;
+                let kind = if err.is_not_found() || matches!(err.kind, ErrorKind::Unsupported(_)) {
+                    IssueKind::Unsupported
+                } else if matches!(err.kind, ErrorKind::NotAllowed(_)) {
+                    IssueKind::Invalid
+                } else {
+                    IssueKind::Error
+                };
+                Some(ValidationIssue {
+                    setting,
+                    kind,
+                    message: err.to_string(),
+                })
+            })
+            .collect();
+
+        ValidationReport { issues }
+    }
+
+    /// Checks each of the present settings, using the same setting names as [`TuningProfile::diff`].
+    fn setting_checks(&self, gpu_handle: &GpuHandle) -> Vec<(String, Result<()>)> {
+        let mut checks = Vec::new();
+
+        for (kind, levels) in &self.enabled_power_levels {
+            let result = (|| {
+                let performance_level = match &self.performance_level {
+                    Some(level) => level.clone(),
+                    None => gpu_handle.get_power_force_performance_level()?,
+                };
+                if performance_level != PerformanceLevel::Manual {
+                    return Err(Error::not_allowed(
+                        "Enabling power levels requires the manual performance level".to_owned(),
+                    ));
+                }
+
+                let count = gpu_handle.get_power_level_count(kind)?;
+                match levels.iter().find(|level| usize::from(**level) >= count) {
+                    Some(level) => Err(Error::not_allowed(format!(
+                        "Power level {level} does not exist in {}",
+                        kind.filename()
+                    ))),
+                    None => Ok(()),
+                }
+            })();
+            checks.push((kind.filename().to_owned(), result));
+        }
+
+        #[cfg(feature = "overdrive")]
+        if let Some(clocks) = &self.clocks {
+            match gpu_handle.get_clocks_table() {
+                Ok(mut table) => {
+                    let empty = ClocksSettings::default();
+                    let fields = [
+                        (
+                            "min_sclk",
+                            ClocksSettings {
+                                min_sclk: clocks.min_sclk,
+                                ..empty
+                            },
+                        ),
+                        (
+                            "max_sclk",
+                            ClocksSettings {
+                                max_sclk: clocks.max_sclk,
+                                ..empty
+                            },
+                        ),
+                        (
+                            "min_mclk",
+                            ClocksSettings {
+                                min_mclk: clocks.min_mclk,
+                                ..empty
+                            },
+                        ),
+                        (
+                            "max_mclk",
+                            ClocksSettings {
+                                max_mclk: clocks.max_mclk,
+                                ..empty
+                            },
+                        ),
+                        (
+                            "voltage_offset",
+                            ClocksSettings {
+                                voltage_offset: clocks.voltage_offset,
+                                ..empty
+                            },
+                        ),
+                    ];
+                    // The values are applied to the same table, as they may depend on each other
+                    let mut fields_valid = true;
+                    for (name, field) in fields {
+                        if field != empty {
+                            let result = field.apply_to_table(&mut table).and_then(|()| {
+                                gpu_handle.check_voltage_offset_quirk(field.voltage_offset)
+                            });
+                            fields_valid &= result.is_ok();
+                            checks.push((name.to_owned(), result));
+                        }
+                    }
+
+                    // Constraints between the fields are only meaningful once every value is valid on its own
+                    if fields_valid {
+                        checks.push(("clocks".to_owned(), check_clocks_order(&table)));
+                    }
+                }
+                Err(err) => checks.push(("clocks".to_owned(), Err(err))),
+            }
+        }
+
+        if let Some(cap) = self.power_cap {
+            let result = gpu_handle.with_hw_mon(|hw_mon| check_power_cap(hw_mon, cap));
+            checks.push(("power_cap".to_owned(), result));
+        }
+
+        if let Some(fan) = &self.fan {
+            let empty = PmfwSettings::default();
+            let fields = [
+                (
+                    "acoustic_limit",
+                    PmfwSettings {
+                        acoustic_limit: fan.acoustic_limit,
+                        ..empty.clone()
+                    },
+                ),
+                (
+                    "acoustic_target",
+                    PmfwSettings {
+                        acoustic_target: fan.acoustic_target,
+                        ..empty.clone()
+                    },
+                ),
+                (
+                    "target_temperature",
+                    PmfwSettings {
+                        target_temperature: fan.target_temperature,
+                        ..empty.clone()
+                    },
+                ),
+                (
+                    "minimum_pwm",
+                    PmfwSettings {
+                        minimum_pwm: fan.minimum_pwm,
+                        ..empty.clone()
+                    },
+                ),
+                (
+                    "zero_rpm_stop_temperature",
+                    PmfwSettings {
+                        zero_rpm_stop_temperature: fan.zero_rpm_stop_temperature,
+                        ..empty.clone()
+                    },
+                ),
+                (
+                    "zero_rpm_enable",
+                    PmfwSettings {
+                        zero_rpm_enable: fan.zero_rpm_enable,
+                        ..empty.clone()
+                    },
+                ),
+                (
+                    "fan_curve",
+                    PmfwSettings {
+                        curve: fan.curve.clone(),
+                        ..empty.clone()
+                    },
+                ),
+            ];
+            for (name, field) in fields {
+                if field != empty {
+                    let result = gpu_handle.prepare_pmfw_settings(&field).map(|_| ());
+                    checks.push((name.to_owned(), result));
+                }
+            }
+        }
+
+        checks
+    }
+
+    /// Applies the profile on the GPU.
+    ///
+    /// The whole profile is validated first (see [`TuningProfile::validate`]), so an invalid profile does not get partially applied.
+    pub fn apply(&self, gpu_handle: &GpuHandle) -> Result<()> {
+        self.validate(gpu_handle)?;
+
+        if let Some(level) = &self.performance_level {
+            gpu_handle.set_power_force_performance_level(level.clone())?;
+        }
+
+        #[cfg(feature = "overdrive")]
+        if let Some(clocks) = &self.clocks {
+            let mut table = gpu_handle.get_clocks_table()?;
+            clocks.apply_to_table(&mut table)?;
+            gpu_handle.set_clocks_table(&table)?.commit()?;
+        }
+
+        for (kind, levels) in &self.enabled_power_levels {
+            gpu_handle.set_enabled_power_levels(kind.clone(), levels)?;
+        }
+
+        if let Some(cap) = self.power_cap {
+            gpu_handle.with_hw_mon(|hw_mon| hw_mon.set_power_cap(cap))?;
+        }
+
+        if let Some(fan) = &self.fan {
+            gpu_handle.apply_pmfw_settings(fan)?.commit()?;
+        }
+
+        Ok(())
+    }
+
+    /// Lists the settings which applying this profile would change, compared to the `current` profile
+    /// (usually captured from the GPU with [`TuningProfile::capture`]).
+    ///
+    /// Settings which are empty in this profile are not included, as applying the profile leaves them unchanged.
+    pub fn diff(&self, current: &TuningProfile) -> Vec<SettingChange> {
+        let mut changes = Vec::new();
+
+        push_change(
+            &mut changes,
+            "performance_level",
+            self.performance_level.clone(),
+            current.performance_level.clone(),
+        );
+
+        for (kind, levels) in &self.enabled_power_levels {
+            push_change(
+                &mut changes,
+                kind.filename(),
+                Some(LevelList(levels)),
+                current
+                    .enabled_power_levels
+                    .get(kind)
+                    .map(|levels| LevelList(levels)),
+            );
+        }
+
+        #[cfg(feature = "overdrive")]
+        if let Some(clocks) = &self.clocks {
+            let current_clocks = current.clocks.unwrap_or_default();
+            let values = [
+                ("min_sclk", clocks.min_sclk, current_clocks.min_sclk),
+                ("max_sclk", clocks.max_sclk, current_clocks.max_sclk),
+                ("min_mclk", clocks.min_mclk, current_clocks.min_mclk),
+                ("max_mclk", clocks.max_mclk, current_clocks.max_mclk),
+                (
+                    "voltage_offset",
+                    clocks.voltage_offset,
+                    current_clocks.voltage_offset,
+                ),
+            ];
+            for (name, new, current) in values {
+                push_change(&mut changes, name, new, current);
+            }
+        }
+
+        push_change(&mut changes, "power_cap", self.power_cap, current.power_cap);
+
+        if let Some(fan) = &self.fan {
+            let current_fan = current.fan.clone().unwrap_or_default();
+            let values = [
+                (
+                    "acoustic_limit",
+                    fan.acoustic_limit,
+                    current_fan.acoustic_limit,
+                ),
+                (
+                    "acoustic_target",
+                    fan.acoustic_target,
+                    current_fan.acoustic_target,
+                ),
+                (
+                    "target_temperature",
+                    fan.target_temperature,
+                    current_fan.target_temperature,
+                ),
+                ("minimum_pwm", fan.minimum_pwm, current_fan.minimum_pwm),
+                (
+                    "zero_rpm_stop_temperature",
+                    fan.zero_rpm_stop_temperature,
+                    current_fan.zero_rpm_stop_temperature,
+                ),
+            ];
+            for (name, new, current) in values {
+                push_change(&mut changes, name, new, current);
+            }
+            push_change(
+                &mut changes,
+                "zero_rpm_enable",
+                fan.zero_rpm_enable,
+                current_fan.zero_rpm_enable,
+            );
+            push_change(
+                &mut changes,
+                "fan_curve",
+                fan.curve.as_ref().map(|curve| CurvePoints(&curve.points)),
+                current_fan
+                    .curve
+                    .as_ref()
+                    .map(|curve| CurvePoints(&curve.points)),
+            );
+        }
+
+        changes
+    }
+
+    /// Layers another profile on top of this one. Settings present in `other` take precedence,
+    /// while settings which are empty in `other` are kept from this profile.
+    ///
+    /// This allows combining partial profiles, such as a fan-only preset on top of a clocks preset.
+    pub fn merge(&self, other: &TuningProfile) -> TuningProfile {
+        let mut enabled_power_levels = self.enabled_power_levels.clone();
+        enabled_power_levels.extend(
+            other
+                .enabled_power_levels
+                .iter()
+                .map(|(kind, levels)| (kind.clone(), levels.clone())),
+        );
+
+        TuningProfile {
+            performance_level: other
+                .performance_level
+                .clone()
+                .or_else(|| self.performance_level.clone()),
+            enabled_power_levels,
+            #[cfg(feature = "overdrive")]
+            clocks: match (&self.clocks, &other.clocks) {
+                (Some(base), Some(other)) => Some(ClocksSettings {
+                    min_sclk: other.min_sclk.or(base.min_sclk),
+                    max_sclk: other.max_sclk.or(base.max_sclk),
+                    min_mclk: other.min_mclk.or(base.min_mclk),
+                    max_mclk: other.max_mclk.or(base.max_mclk),
+                    voltage_offset: other.voltage_offset.or(base.voltage_offset),
+                }),
+                (base, other) => other.or(*base),
+            },
+            power_cap: other.power_cap.or(self.power_cap),
+            fan: match (&self.fan, &other.fan) {
+                (Some(base), Some(other)) => Some(PmfwSettings {
+                    acoustic_limit: other.acoustic_limit.or(base.acoustic_limit),
+                    acoustic_target: other.acoustic_target.or(base.acoustic_target),
+                    target_temperature: other.target_temperature.or(base.target_temperature),
+                    minimum_pwm: other.minimum_pwm.or(base.minimum_pwm),
+                    zero_rpm_enable: other.zero_rpm_enable.or(base.zero_rpm_enable),
+                    zero_rpm_stop_temperature: other
+                        .zero_rpm_stop_temperature
+                        .or(base.zero_rpm_stop_temperature),
+                    curve: other.curve.clone().or_else(|| base.curve.clone()),
+                }),
+                (base, other) => other.clone().or_else(|| base.clone()),
+            },
+        }
+    }
+}
+
+/// Result of [`TuningProfile::validate_for`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ValidationReport {
+    /// Problems found with the settings
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Checks if the profile can be applied without any problems.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// A problem with a profile setting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ValidationIssue {
+    /// Name of the setting
+    pub setting: String,
+    /// Kind of the problem
+    pub kind: IssueKind,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.setting, self.message)
+    }
+}
+
+/// Kind of a [`ValidationIssue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum IssueKind {
+    /// The setting is not supported on the GPU
+    Unsupported,
+    /// The value is out of the allowed range or otherwise not accepted
+    Invalid,
+    /// The current state of the GPU could not be read
+    Error,
+}
+
+/// A setting which would be changed by applying a profile, see [`TuningProfile::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SettingChange {
+    /// Name of the setting
+    pub setting: String,
+    /// The current value, or `None` if it is not known
+    pub current: Option<String>,
+    /// The value after applying the profile
+    pub new: String,
+}
+
+impl fmt::Display for SettingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let current = self.current.as_deref().unwrap_or("unknown");
+        write!(f, "{}: {current} -> {}", self.setting, self.new)
+    }
+}
+
+fn push_change<T: PartialEq + fmt::Display>(
+    changes: &mut Vec<SettingChange>,
+    setting: &str,
+    new: Option<T>,
+    current: Option<T>,
+) {
+    if let Some(new) = new {
+        if current.as_ref() != Some(&new) {
+            changes.push(SettingChange {
+                setting: setting.to_owned(),
+                current: current.map(|value| value.to_string()),
+                new: new.to_string(),
+            });
+        }
+    }
+}
+
+#[derive(PartialEq)]
+struct LevelList<'a>(&'a [u8]);
+
+impl fmt::Display for LevelList<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let levels: Vec<String> = self.0.iter().map(u8::to_string).collect();
+        f.write_str(&levels.join(" "))
+    }
+}
+
+#[derive(PartialEq)]
+struct CurvePoints<'a>(&'a [CurvePoint]);
+
+impl fmt::Display for CurvePoints<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let points: Vec<String> = self
+            .0
+            .iter()
+            .map(|point| format!("{}°C {}%", point.temperature, point.speed))
+            .collect();
+        f.write_str(&points.join(", "))
+    }
+}
+
+/// Treats missing or unsupported settings as empty.
+fn optional<T>(result: Result<T>) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if err.is_not_found() || matches!(err.kind, ErrorKind::Unsupported(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn check_power_cap(hw_mon: &HwMon, cap: f64) -> Result<()> {
+    let min = optional(hw_mon.get_power_cap_min())?.unwrap_or(0.0);
+    let max = optional(hw_mon.get_power_cap_max())?;
+
+    if cap < min || max.is_some_and(|max| cap > max) {
+        return Err(Error::not_allowed(format!(
+            "Power cap {cap} is not in the allowed range {min}..{}",
+            max.map(|max| max.to_string()).unwrap_or_default()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "overdrive")]
+    use super::ClocksSettings;
+    use super::{SettingChange, TuningProfile};
+    use crate::gpu_handle::{fan_control::PmfwSettings, PerformanceLevel, PowerLevelKind};
+    use pretty_assertions::assert_eq;
+
+    fn fan(minimum_pwm: Option<u32>, target_temperature: Option<u32>) -> Option<PmfwSettings> {
+        Some(PmfwSettings {
+            minimum_pwm,
+            target_temperature,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn diff() {
+        let current = TuningProfile {
+            performance_level: Some(PerformanceLevel::Auto),
+            power_cap: Some(250.0),
+            fan: fan(Some(15), Some(83)),
+            ..Default::default()
+        };
+        let profile = TuningProfile {
+            performance_level: Some(PerformanceLevel::Manual),
+            enabled_power_levels: [(PowerLevelKind::CoreClock, vec![1, 2])].into(),
+            power_cap: Some(250.0),
+            fan: fan(Some(20), Some(83)),
+            ..Default::default()
+        };
+
+        let changes = profile.diff(&current);
+        assert_eq!(
+            vec![
+                "performance_level: auto -> manual",
+                "pp_dpm_sclk: unknown -> 1 2",
+                "minimum_pwm: 15 -> 20",
+            ],
+            changes
+                .iter()
+                .map(SettingChange::to_string)
+                .collect::<Vec<_>>()
+        );
+        assert!(current.diff(&current).is_empty());
+        assert!(TuningProfile::default().diff(&current).is_empty());
+    }
+
+    #[test]
+    fn merge() {
+        let base = TuningProfile {
+            performance_level: Some(PerformanceLevel::Manual),
+            power_cap: Some(250.0),
+            #[cfg(feature = "overdrive")]
+            clocks: Some(ClocksSettings {
+                max_sclk: Some(2500),
+                max_mclk: Some(1200),
+                ..Default::default()
+            }),
+            fan: fan(Some(15), Some(83)),
+            ..Default::default()
+        };
+        let preset = TuningProfile {
+            #[cfg(feature = "overdrive")]
+            clocks: Some(ClocksSettings {
+                max_sclk: Some(2700),
+                ..Default::default()
+            }),
+            fan: fan(None, Some(70)),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            TuningProfile {
+                performance_level: Some(PerformanceLevel::Manual),
+                power_cap: Some(250.0),
+                #[cfg(feature = "overdrive")]
+                clocks: Some(ClocksSettings {
+                    max_sclk: Some(2700),
+                    max_mclk: Some(1200),
+                    ..Default::default()
+                }),
+                fan: fan(Some(15), Some(70)),
+                ..Default::default()
+            },
+            base.merge(&preset)
+        );
+        assert_eq!(base, base.merge(&TuningProfile::default()));
+        assert_eq!(base, TuningProfile::default().merge(&base));
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn json_schema() {
+        let schema = schemars::schema_for!(TuningProfile);
+        let properties = &schema.schema.object.unwrap().properties;
+        assert!(properties.contains_key("performance_level"));
+        assert!(properties.contains_key("power_cap"));
+        assert!(properties.contains_key("fan"));
+    }
+}
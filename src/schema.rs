@@ -0,0 +1,240 @@
+//! Versioned serialization format for persisting data
+//!
+//! Types such as [`TuningProfile`](crate::profile::TuningProfile), [`ClocksTableGen`](crate::gpu_handle::overdrive::ClocksTableGen),
+//! [`PowerProfileModesTable`](crate::gpu_handle::power_profile_mode::PowerProfileModesTable)
+//! and [`Sample`](crate::monitoring::Sample) can be wrapped in [`Versioned`] when they are stored,
+//! which records the [`SCHEMA_VERSION`] next to the data:
+//!
+//! ```json
+//! { "schema_version": 1, "data": { ... } }
+//! ```
+//!
+//! Within a schema version, the serialized representation of these types only changes in backwards compatible ways:
+//! new fields are optional, and existing fields are not renamed, removed or given a different meaning.
+//! Data written by an older version can therefore always be read by a newer version of the library.
+//! Any other change increments [`SCHEMA_VERSION`], and data with a newer version than the one supported is rejected
+//! instead of being misinterpreted.
+use serde::{
+    de::{self, MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use std::{fmt, marker::PhantomData};
+
+/// The current version of the serialization format.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Data along with the schema version it was serialized with.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Versioned<T> {
+    /// Version of the format the data was serialized with
+    pub schema_version: u32,
+    /// The data
+    pub data: T,
+}
+
+impl<T> Versioned<T> {
+    /// Wraps the data with the current schema version.
+    pub fn new(data: T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            data,
+        }
+    }
+
+    /// Gets the wrapped data.
+    pub fn into_inner(self) -> T {
+        self.data
+    }
+}
+
+impl<T> From<T> for Versioned<T> {
+    fn from(data: T) -> Self {
+        Self::new(data)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Versioned<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct(
+            "Versioned",
+            &["schema_version", "data"],
+            VersionedVisitor(PhantomData),
+        )
+    }
+}
+
+struct VersionedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for VersionedVisitor<T> {
+    type Value = Versioned<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map with `schema_version` and `data` fields")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut schema_version = None;
+        let mut data = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "schema_version" => {
+                    let version = map.next_value()?;
+                    // Checked before the data is read, so that a newer format produces a clear error
+                    check_version(version)?;
+                    schema_version = Some(version);
+                }
+                "data" => data = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(Versioned {
+            schema_version: schema_version
+                .ok_or_else(|| de::Error::missing_field("schema_version"))?,
+            data: data.ok_or_else(|| de::Error::missing_field("data"))?,
+        })
+    }
+}
+
+fn check_version<E: de::Error>(version: u32) -> Result<(), E> {
+    if version == 0 || version > SCHEMA_VERSION {
+        Err(E::custom(format!(
+            "unsupported schema version {version}, the latest supported version is {SCHEMA_VERSION}"
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Versioned, SCHEMA_VERSION};
+    #[cfg(feature = "overdrive")]
+    use crate::{gpu_handle::overdrive::ClocksTableGen, profile::ClocksSettings};
+    use crate::{
+        gpu_handle::{
+            fan_control::{FanCurve, PmfwSettings},
+            power_profile_mode::PowerProfileModesTable,
+            PerformanceLevel, PowerLevelKind,
+        },
+        hw_mon::Temperature,
+        monitoring::{Metric, Sample},
+        profile::TuningProfile,
+    };
+    use serde::{de::DeserializeOwned, Serialize};
+    use std::{
+        fmt::Debug,
+        str::FromStr,
+        time::{Duration, SystemTime},
+    };
+
+    /// Checks that the data stored in the fixture can still be read,
+    /// and that the current representation has not changed.
+    fn check_compatibility<T>(value: T, fixture: &str)
+    where
+        T: Serialize + DeserializeOwned + PartialEq + Debug,
+    {
+        let stored: Versioned<T> = serde_json::from_str(fixture).unwrap();
+        assert_eq!(1, stored.schema_version);
+        assert_eq!(value, stored.data);
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(fixture).unwrap(),
+            serde_json::to_value(Versioned::new(value)).unwrap()
+        );
+    }
+
+    fn tuning_profile() -> TuningProfile {
+        TuningProfile {
+            performance_level: Some(PerformanceLevel::Manual),
+            enabled_power_levels: [(PowerLevelKind::CoreClock, vec![0, 2])].into(),
+            #[cfg(feature = "overdrive")]
+            clocks: Some(ClocksSettings {
+                max_sclk: Some(2500),
+                voltage_offset: Some(-50),
+                ..Default::default()
+            }),
+            power_cap: Some(250.0),
+            fan: Some(PmfwSettings {
+                acoustic_limit: Some(2500),
+                zero_rpm_enable: Some(false),
+                curve: Some(
+                    FanCurve::from_str(include_test_data!("rx7800xt/gpu_od/fan_ctrl/fan_curve"))
+                        .unwrap(),
+                ),
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "overdrive")]
+    fn tuning_profile_v1() {
+        check_compatibility(
+            tuning_profile(),
+            include_test_data!("schema/v1/tuning_profile.json"),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "overdrive")]
+    fn clocks_table_v1() {
+        let table =
+            ClocksTableGen::from_str(include_test_data!("rx6900xt/pp_od_clk_voltage")).unwrap();
+        check_compatibility(table, include_test_data!("schema/v1/clocks_table.json"));
+    }
+
+    #[test]
+    fn power_profile_modes_v1() {
+        let table =
+            PowerProfileModesTable::parse(include_test_data!("rx6900xt/pp_power_profile_mode"))
+                .unwrap();
+        check_compatibility(
+            table,
+            include_test_data!("schema/v1/power_profile_modes.json"),
+        );
+    }
+
+    #[test]
+    fn sample_v1() {
+        let mut sample = Sample::empty(
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            Duration::from_millis(1500),
+        );
+        sample.busy_percent = Some(42);
+        sample.vram_used = Some(512 * 1024 * 1024);
+        sample.temperatures.insert(
+            "edge".to_owned(),
+            Temperature {
+                current: Some(44.0),
+                crit: Some(94.0),
+                crit_hyst: None,
+            },
+        );
+        sample.power = Some(41.5);
+        sample
+            .errors
+            .insert(Metric::Fan, "No such file or directory".to_owned());
+
+        check_compatibility(sample, include_test_data!("schema/v1/sample.json"));
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let newer = format!(
+            "{{\"schema_version\":{},\"data\":{{\"unknown\":true}}}}",
+            SCHEMA_VERSION + 1
+        );
+        let err = serde_json::from_str::<Versioned<TuningProfile>>(&newer).unwrap_err();
+        assert!(
+            err.to_string().contains("unsupported schema version"),
+            "{err}"
+        );
+
+        assert!(serde_json::from_str::<Versioned<TuningProfile>>("{\"data\":{}}").is_err());
+    }
+}
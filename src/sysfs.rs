@@ -3,7 +3,12 @@ use crate::{
     error::{Error, ErrorContext},
     Result,
 };
-use std::{fmt::Debug, fs, path::Path, str::FromStr};
+use std::{
+    fmt::Debug,
+    fs::{self, File},
+    path::Path,
+    str::FromStr,
+};
 
 /// General functionality of a SysFS.
 pub trait SysFS {
@@ -31,6 +36,32 @@ pub trait SysFS {
 
     /// Write to a file in the `SysFS`.
     fn write_file<C: AsRef<[u8]> + Send>(&self, file: &str, contents: C) -> Result<()> {
-        Ok(fs::write(self.get_path().join(file), contents)?)
+        write(&self.get_path().join(file), contents)
     }
 }
+
+/// Writes to a file, unless writing is disabled with the `read-only` feature.
+///
+/// All writes to the device should go through this function or [`create`].
+pub(crate) fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    check_writable()?;
+    Ok(fs::write(path, contents)?)
+}
+
+/// Opens a file for writing, unless writing is disabled with the `read-only` feature.
+pub(crate) fn create(path: &Path) -> Result<File> {
+    check_writable()?;
+    Ok(File::create(path)?)
+}
+
+#[cfg(not(feature = "read-only"))]
+fn check_writable() -> Result<()> {
+    Ok(())
+}
+
+#[cfg(feature = "read-only")]
+fn check_writable() -> Result<()> {
+    Err(Error::not_allowed(
+        "Writing is disabled by the `read-only` feature".to_owned(),
+    ))
+}
@@ -6,3 +6,14 @@ macro_rules! include_test_data {
         ))
     };
 }
+
+/// Variants of the given data with irregular whitespace, as found in dumps from other systems:
+/// CRLF line endings, tabs, repeated spaces and trailing whitespace.
+pub(crate) fn whitespace_variants(data: &str) -> Vec<String> {
+    vec![
+        data.replace('\n', "\r\n"),
+        data.replace(' ', "\t"),
+        data.replace(' ', "  "),
+        data.lines().map(|line| format!("{line} \t\n")).collect(),
+    ]
+}
@@ -0,0 +1,40 @@
+#![cfg_attr(feature = "read-only", allow(unused_imports))]
+#![allow(clippy::redundant_closure_call)]
+mod sysfs;
+
+use amdgpu_sysfs::{
+    error::ErrorKind,
+    gpu_handle::{power_profile_mode::StandardProfile, GpuHandle, PerformanceLevel},
+    sysfs::SysFS,
+};
+
+test_with_handle! {
+    "internal-4800h",
+    set_power_profile_requires_manual => {
+        |gpu_handle: &GpuHandle| {
+            gpu_handle
+                .set_active_power_profile(StandardProfile::Compute)
+                .map_err(|err| err.kind)
+        },
+        Err(ErrorKind::NotAllowed(String::new()))
+    },
+    #[cfg(not(feature = "read-only"))]
+    set_power_profile_by_name => {
+        |gpu_handle: &GpuHandle| {
+            gpu_handle.set_power_force_performance_level(PerformanceLevel::Manual).unwrap();
+            gpu_handle.set_active_power_profile_by_name("compute").unwrap();
+            gpu_handle.read_file("pp_power_profile_mode")
+        },
+        Ok("5".to_owned())
+    },
+    #[cfg(not(feature = "read-only"))]
+    set_power_profile_unavailable => {
+        |gpu_handle: &GpuHandle| {
+            gpu_handle.set_power_force_performance_level(PerformanceLevel::Manual).unwrap();
+            gpu_handle
+                .set_active_power_profile(StandardProfile::PowerSaving)
+                .map_err(|err| err.kind)
+        },
+        Err(ErrorKind::Unsupported(String::new()))
+    }
+}
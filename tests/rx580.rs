@@ -1,10 +1,28 @@
+#![cfg_attr(feature = "read-only", allow(unused_imports))]
+#![allow(clippy::redundant_closure_call)]
 mod sysfs;
 
 use amdgpu_sysfs::{
-    gpu_handle::{GpuHandle, PerformanceLevel, PowerLevels},
-    hw_mon::{HwMon, Temperature},
+    error::ErrorKind,
+    gpu_handle::{
+        health::{HealthIssue, HealthStatus},
+        overdrive::Range,
+        pm_policy::{PmPolicyLevels, SocPstatePolicy, XgmiPlpdPolicy},
+        GpuHandle, Interface, PerformanceLevel, PowerLevelKind, PowerLevels, RestoreStep,
+    },
+    hw_mon::{
+        FanControlMethod, FanMode, HwMon, PowerCapRange, PowerReport, PwmRamp, SensorKind,
+        Temperature,
+    },
+    monitoring::{ConcurrentCollector, Metric, Sampler},
+    sysfs::SysFS,
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    ops::ControlFlow,
+    time::Duration,
 };
-use std::collections::HashMap;
 
 test_with_handle! {
     "rx580",
@@ -21,6 +39,7 @@ test_with_handle! {
     vram => {
         GpuHandle::get_total_vram, Ok(4096 * 1024 * 1024),
         GpuHandle::get_used_vram, Ok(512 * 1024 * 1024),
+        GpuHandle::get_vram_usage_percent, Ok(12.5),
     },
     vbios => {
         GpuHandle::get_vbios_version, Ok("113-1E3871U-O4C".to_owned())
@@ -33,6 +52,7 @@ test_with_handle! {
         GpuHandle::get_current_link_width, Ok("16".to_owned()),
         GpuHandle::get_max_link_speed, Ok("8.0 GT/s PCIe".to_owned()),
         GpuHandle::get_max_link_width, Ok("16".to_owned()),
+        |gpu_handle: &GpuHandle| gpu_handle.get_link_status().unwrap().is_degraded(), false,
     },
     pp_dpm_sclk => {
         GpuHandle::get_core_clock_levels,
@@ -70,7 +90,506 @@ test_with_handle! {
             ].map(str::to_owned).to_vec(),
             active: Some(1)
         })
-    }
+    },
+    pcie_levels => {
+        |gpu_handle: &GpuHandle| {
+            gpu_handle.get_pcie_levels().map(|levels| {
+                levels.levels.iter().map(|level| (level.transfer_rate, level.width)).collect::<Vec<_>>()
+            })
+        },
+        Ok(vec![(2500, 8), (8000, 16)])
+    },
+    #[cfg(not(feature = "read-only"))]
+    set_enabled_power_levels => {
+        |gpu_handle: &GpuHandle| {
+            gpu_handle.set_power_force_performance_level(PerformanceLevel::Manual).unwrap();
+            gpu_handle.set_enabled_power_levels(PowerLevelKind::CoreClock, &[0, 9, 10, 12]).unwrap();
+            gpu_handle.read_file("pp_dpm_sclk")
+        },
+        Ok("0 9 10 12".to_owned())
+    },
+    #[cfg(not(feature = "read-only"))]
+    enabled_power_level_helpers => {
+        |gpu_handle: &GpuHandle| {
+            gpu_handle.set_power_force_performance_level(PerformanceLevel::Manual).unwrap();
+            gpu_handle.set_enabled_power_levels_range(PowerLevelKind::CoreClock, 4..=6).unwrap();
+            let range = gpu_handle.read_file("pp_dpm_sclk").unwrap();
+            gpu_handle.enable_only_highest_power_level(PowerLevelKind::MemoryClock).unwrap();
+            let highest = gpu_handle.read_file("pp_dpm_mclk").unwrap();
+            (range, highest)
+        },
+        ("4 5 6".to_owned(), "2".to_owned())
+    },
+    #[cfg(not(feature = "read-only"))]
+    enable_all_power_levels => {
+        |gpu_handle: &GpuHandle| {
+            gpu_handle.set_power_force_performance_level(PerformanceLevel::Manual).unwrap();
+            let invalid_range = gpu_handle
+                .set_enabled_power_levels_range(PowerLevelKind::CoreClock, 6..=8)
+                .is_err();
+            gpu_handle.enable_all_power_levels(PowerLevelKind::CoreClock).unwrap();
+            (invalid_range, gpu_handle.read_file("pp_dpm_sclk").unwrap())
+        },
+        (true, "0 1 2 3 4 5 6 7".to_owned())
+    },
+    #[cfg(not(feature = "read-only"))]
+    force_power_levels => {
+        |gpu_handle: &GpuHandle| {
+            gpu_handle.force_highest_power_levels().unwrap();
+            let highest = (
+                gpu_handle.get_power_force_performance_level().unwrap(),
+                gpu_handle.read_file("pp_dpm_sclk").unwrap(),
+                gpu_handle.read_file("pp_dpm_mclk").unwrap(),
+            );
+            gpu_handle.restore_auto_power_levels().unwrap();
+            (highest, gpu_handle.get_power_force_performance_level().unwrap())
+        },
+        ((PerformanceLevel::Manual, "7".to_owned(), "2".to_owned()), PerformanceLevel::Auto)
+    },
+    #[cfg(not(feature = "read-only"))]
+    force_lowest_power_levels => {
+        |gpu_handle: &GpuHandle| {
+            gpu_handle.force_lowest_power_levels().unwrap();
+            (gpu_handle.read_file("pp_dpm_sclk").unwrap(), gpu_handle.read_file("pp_dpm_mclk").unwrap())
+        },
+        ("0".to_owned(), "0".to_owned())
+    },
+    set_enabled_power_levels_not_manual => {
+        |gpu_handle: &GpuHandle| {
+            gpu_handle
+                .set_enabled_power_levels(PowerLevelKind::CoreClock, &[1])
+                .map_err(|err| err.kind)
+        },
+        Err(ErrorKind::NotAllowed(String::new()))
+    },
+    #[cfg(not(feature = "read-only"))]
+    restore_defaults => {
+        |gpu_handle: &GpuHandle| {
+            gpu_handle.set_power_force_performance_level(PerformanceLevel::Manual).unwrap();
+            gpu_handle.hw_monitors[0].set_power_cap(100.0).unwrap();
+
+            let report = gpu_handle.restore_defaults();
+            (
+                report.is_ok(),
+                report.results.iter().map(|(step, _)| *step).collect::<Vec<_>>(),
+                gpu_handle.read_file("pp_dpm_sclk").unwrap(),
+                gpu_handle.read_file("pp_od_clk_voltage").unwrap(),
+                gpu_handle.hw_monitors[0].get_power_cap().unwrap(),
+                gpu_handle.get_power_force_performance_level().unwrap(),
+            )
+        },
+        (
+            true,
+            vec![
+                RestoreStep::PowerLevels,
+                RestoreStep::ClocksTable,
+                RestoreStep::PowerCap,
+                RestoreStep::PerformanceLevel,
+            ],
+            "0 1 2 3 4 5 6 7".to_owned(),
+            "r".to_owned(),
+            155.0,
+            PerformanceLevel::Auto,
+        )
+    },
+    health_check => {
+        |gpu_handle: &GpuHandle| {
+            let hw_mon_path = gpu_handle.hw_monitors[0].get_path();
+            let healthy = gpu_handle.health_check().status;
+
+            std::fs::write(gpu_handle.get_path().join("pcie_replay_count"), "5").unwrap();
+            std::fs::write(gpu_handle.get_path().join("mem_info_vram_used"), "garbage").unwrap();
+            let degraded = gpu_handle.health_check().issues;
+
+            std::fs::write(gpu_handle.get_path().join("gpu_busy_percent"), "100").unwrap();
+            std::fs::write(hw_mon_path.join("freq1_input"), "0").unwrap();
+            std::fs::remove_file(gpu_handle.get_path().join("mem_info_vram_used")).unwrap();
+            let hung = gpu_handle.health_check();
+
+            (healthy, degraded.len(), degraded[1].clone(), hung.status, hung.issues)
+        },
+        (
+            HealthStatus::Healthy,
+            2,
+            HealthIssue::PcieReplays { count: 2 },
+            HealthStatus::Hung,
+            vec![HealthIssue::BusyWithoutClock]
+        )
+    },
+    sampler => {
+        |gpu_handle: &GpuHandle| {
+            let sample = Sampler::new(gpu_handle.clone()).sample();
+            (
+                sample.busy_percent,
+                sample.vram_used,
+                sample.vram_total,
+                sample.temperatures.get("edge").and_then(|temp| temp.current),
+                sample.power,
+                sample.gpu_clockspeed,
+                sample.vram_clockspeed,
+                sample.fan_rpm,
+                sample.fan_pwm,
+                sample.errors,
+            )
+        },
+        (
+            Some(11),
+            Some(512 * 1024 * 1024),
+            Some(4096 * 1024 * 1024),
+            Some(44.0),
+            Some(41.045),
+            Some(798),
+            Some(1750),
+            Some(595),
+            Some(35),
+            BTreeMap::new(),
+        )
+    },
+    sampler_missing_metric => {
+        |gpu_handle: &GpuHandle| {
+            let hw_mon_path = gpu_handle.hw_monitors[0].get_path();
+            std::fs::remove_file(hw_mon_path.join("power1_average")).unwrap();
+
+            let sample = Sampler::new(gpu_handle.clone())
+                .metrics([Metric::BusyPercent, Metric::Power])
+                .sample();
+            (sample.busy_percent, sample.power, sample.fan_rpm, sample.errors.into_keys().collect::<Vec<_>>())
+        },
+        (Some(11), None, None, vec![Metric::Power])
+    },
+    sampler_iter => {
+        |gpu_handle: &GpuHandle| {
+            let sampler = Sampler::new(gpu_handle.clone())
+                .metrics([Metric::BusyPercent])
+                .interval(Duration::from_millis(10));
+
+            let mut count = 0;
+            sampler.run(|sample| {
+                assert_eq!(sample.busy_percent, Some(11));
+                count += 1;
+                if count == 3 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            });
+
+            let samples: Vec<_> = sampler.iter().take(2).collect();
+            (count, samples[1].elapsed - samples[0].elapsed >= Duration::from_millis(10))
+        },
+        (3, true)
+    },
+    new_from_card_path => {
+        |gpu_handle: &GpuHandle| {
+            let card_dir = tempfile::tempdir().unwrap();
+            fs::write(
+                card_dir.path().join("uevent"),
+                "MAJOR=226\nMINOR=0\nDEVNAME=dri/card0\nDEVTYPE=drm_minor\n",
+            )
+            .unwrap();
+            std::os::unix::fs::symlink(gpu_handle.get_path(), card_dir.path().join("device")).unwrap();
+
+            let card_handle = GpuHandle::new_from_path(card_dir.path().to_path_buf()).unwrap();
+            (
+                card_handle.get_path() == gpu_handle.get_path(),
+                card_handle.get_pci_id().map(|(vendor, device)| format!("{vendor}:{device}")),
+            )
+        },
+        (true, Some("1002:67DF".to_owned()))
+    },
+    handle_identity => {
+        |gpu_handle: &GpuHandle| {
+            let link_dir = tempfile::tempdir().unwrap();
+            let link_path = link_dir.path().join("device");
+            std::os::unix::fs::symlink(gpu_handle.get_path(), &link_path).unwrap();
+            let linked_handle = GpuHandle::new_from_path(link_path).unwrap();
+
+            // The hash only depends on the PCI slot, not the cached state
+            #[allow(clippy::mutable_key_type)]
+            let handles: HashSet<GpuHandle> = [gpu_handle.clone(), linked_handle.clone()].into();
+            (linked_handle == *gpu_handle, handles.len())
+        },
+        (true, 1)
+    },
+    #[cfg(not(feature = "read-only"))]
+    lock_pcie_link => {
+        |gpu_handle: &GpuHandle| {
+            let auto = gpu_handle.lock_pcie_link("Gen3 x16").map_err(|err| err.kind);
+            gpu_handle.set_power_force_performance_level(PerformanceLevel::Manual).unwrap();
+            let missing = gpu_handle.lock_pcie_link("Gen4").map_err(|err| err.kind);
+            gpu_handle.lock_pcie_link("gen1 x8").unwrap();
+            (auto, missing, gpu_handle.read_file("pp_dpm_pcie").unwrap())
+        },
+        (
+            Err(ErrorKind::NotAllowed(String::new())),
+            Err(ErrorKind::NotAllowed(String::new())),
+            "0".to_owned(),
+        )
+    },
+    enumerate => {
+        |gpu_handle: &GpuHandle| {
+            let vega56 = sysfs::MockSysFs::new("vega56");
+            let drm_dir = tempfile::tempdir().unwrap();
+            let add_card = |name: &str, device: &std::path::Path| {
+                let card_dir = drm_dir.path().join(name);
+                fs::create_dir(&card_dir).unwrap();
+                fs::write(card_dir.join("uevent"), "DEVTYPE=drm_minor\n").unwrap();
+                std::os::unix::fs::symlink(device, card_dir.join("device")).unwrap();
+            };
+            add_card("card10", vega56.get_path());
+            add_card("card1", gpu_handle.get_path());
+            add_card("card1-DP-1", gpu_handle.get_path());
+            fs::create_dir(drm_dir.path().join("card2")).unwrap();
+
+            GpuHandle::enumerate_from(drm_dir.path())
+                .unwrap()
+                .iter()
+                .map(|handle| handle.get_pci_id().map(|(vendor, device)| format!("{vendor}:{device}")))
+                .collect::<Vec<_>>()
+        },
+        vec![Some("1002:67DF".to_owned()), Some("1002:687F".to_owned())]
+    },
+    vram_usage_percent_unsupported => {
+        |gpu_handle: &GpuHandle| {
+            fs::remove_file(gpu_handle.get_path().join("mem_info_vram_used")).unwrap();
+            gpu_handle.get_vram_usage_percent().map_err(|err| err.kind)
+        },
+        Err(ErrorKind::Unsupported(String::new()))
+    },
+    missing_optional_attributes => {
+        |gpu_handle: &GpuHandle| {
+            fs::remove_file(gpu_handle.get_path().join("gpu_busy_percent")).unwrap();
+            fs::remove_file(gpu_handle.get_path().join("pp_od_clk_voltage")).unwrap();
+            (
+                gpu_handle.get_busy_percent().map_err(|err| err.kind),
+                gpu_handle.get_clocks_table().map_err(|err| err.kind),
+                gpu_handle.get_fan_acoustic_limit().map_err(|err| err.kind),
+                gpu_handle.get_fan_curve().map_err(|err| err.kind),
+            )
+        },
+        (
+            Err(ErrorKind::Unsupported(String::new())),
+            Err(ErrorKind::Unsupported(String::new())),
+            Err(ErrorKind::Unsupported(String::new())),
+            Err(ErrorKind::Unsupported(String::new())),
+        )
+    },
+    xcp_partitions => {
+        |gpu_handle: &GpuHandle| {
+            let topology_dir = tempfile::tempdir().unwrap();
+            let add_node = |node: u32, properties: &str| {
+                let node_dir = topology_dir.path().join(node.to_string());
+                fs::create_dir(&node_dir).unwrap();
+                fs::write(node_dir.join("properties"), properties).unwrap();
+            };
+            // CPU, two partitions of this GPU and a different GPU
+            add_node(0, "cpu_cores_count 16\nlocation_id 0\ndomain 0\n");
+            add_node(2, "location_id 2304\ndomain 0\ndrm_render_minor 129\nnum_xcc 4\n");
+            add_node(1, "location_id 2304\ndomain 0\ndrm_render_minor 128\nnum_xcc 4\n");
+            add_node(3, "location_id 768\ndomain 0\ndrm_render_minor 130\n");
+
+            gpu_handle
+                .get_xcp_partitions_from(topology_dir.path())
+                .unwrap()
+                .iter()
+                .map(|partition| {
+                    (
+                        partition.index(),
+                        partition.parent() == gpu_handle,
+                        partition.get_render_node_path(),
+                        partition.get_xcc_count(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        },
+        vec![
+            (0, true, Some("/dev/dri/renderD128".into()), Some(4)),
+            (1, true, Some("/dev/dri/renderD129".into()), Some(4)),
+        ]
+    },
+    #[cfg(not(feature = "read-only"))]
+    pm_policy => {
+        |gpu_handle: &GpuHandle| {
+            let missing = gpu_handle.get_pm_policy::<XgmiPlpdPolicy>().map_err(|err| err.kind);
+
+            fs::create_dir(gpu_handle.get_path().join("pm_policy")).unwrap();
+            fs::write(
+                gpu_handle.get_path().join("pm_policy/soc_pstate"),
+                "0 : soc_pstate_default*\n1 : soc_pstate_0\n3 : soc_pstate_2\n",
+            )
+            .unwrap();
+            let levels = gpu_handle.get_pm_policy::<SocPstatePolicy>().unwrap();
+            let unavailable = gpu_handle
+                .set_pm_policy(SocPstatePolicy::Pstate1)
+                .map_err(|err| err.kind);
+            gpu_handle.set_pm_policy(SocPstatePolicy::Pstate2).unwrap();
+
+            (
+                missing,
+                levels,
+                unavailable,
+                gpu_handle.read_file("pm_policy/soc_pstate").unwrap(),
+            )
+        },
+        (
+            Err(ErrorKind::IoError(std::io::ErrorKind::NotFound.into())),
+            PmPolicyLevels {
+                levels: vec![
+                    SocPstatePolicy::Default,
+                    SocPstatePolicy::Pstate0,
+                    SocPstatePolicy::Pstate2
+                ],
+                active: Some(SocPstatePolicy::Default),
+            },
+            Err(ErrorKind::NotAllowed(String::new())),
+            "3".to_owned(),
+        )
+    },
+    audio_function => {
+        |gpu_handle: &GpuHandle| {
+            let bus_dir = tempfile::tempdir().unwrap();
+            let gpu_dir = bus_dir.path().join("0000:09:00.0");
+            fs::create_dir(&gpu_dir).unwrap();
+            fs::write(gpu_dir.join("uevent"), gpu_handle.read_file("uevent").unwrap()).unwrap();
+            let gpu_handle = GpuHandle::new_from_path(gpu_dir).unwrap();
+
+            let missing = gpu_handle.get_audio_function().map_err(|err| err.kind);
+
+            let audio_dir = bus_dir.path().join("0000:09:00.1");
+            fs::create_dir(&audio_dir).unwrap();
+            fs::write(audio_dir.join("class"), "0x040300\n").unwrap();
+            fs::write(audio_dir.join("vendor"), "0x1002\n").unwrap();
+            fs::write(audio_dir.join("device"), "0xaaf0\n").unwrap();
+
+            let function = gpu_handle.get_audio_function().unwrap();
+            (
+                missing,
+                function.path == fs::canonicalize(&audio_dir).unwrap(),
+                function.slot_name,
+                (function.vendor_id, function.device_id),
+            )
+        },
+        (
+            Err(ErrorKind::Unsupported(String::new())),
+            true,
+            "0000:09:00.1".to_owned(),
+            ("1002".to_owned(), "AAF0".to_owned()),
+        )
+    },
+    summary => {
+        |gpu_handle: &GpuHandle| {
+            let without_card = gpu_handle.summary();
+            fs::create_dir_all(gpu_handle.get_path().join("drm/card1")).unwrap();
+            (without_card, gpu_handle.to_string())
+        },
+        (
+            "GPU (0000:09:00.0), PCI ID 1002:67DF, subsystem 1DA2:E387, driver amdgpu, VBIOS 113-1E3871U-O4C".to_owned(),
+            "card1 (0000:09:00.0), PCI ID 1002:67DF, subsystem 1DA2:E387, driver amdgpu, VBIOS 113-1E3871U-O4C".to_owned(),
+        )
+    },
+    rescan_hw_monitors => {
+        |gpu_handle: &GpuHandle| {
+            let mut gpu_handle = gpu_handle.clone();
+            let hwmon_dir = gpu_handle.get_path().join("hwmon");
+            fs::rename(hwmon_dir.join("hwmon4"), hwmon_dir.join("hwmon7")).unwrap();
+
+            let stale = gpu_handle.hw_monitors[0].get_power_cap().map_err(|err| err.is_not_found());
+            let retried = gpu_handle.with_hw_mon(HwMon::get_power_cap);
+            let remembered = gpu_handle
+                .clone()
+                .with_hw_mon(|hw_mon| Ok(hw_mon.get_path().ends_with("hwmon7")));
+            let fan_rpm = Sampler::new(gpu_handle.clone()).metrics([Metric::Fan]).sample().fan_rpm;
+
+            gpu_handle.rescan_hw_monitors();
+            let rescanned = gpu_handle.hw_monitors[0].get_path().ends_with("hwmon7");
+            (stale, retried.ok(), remembered.ok(), fan_rpm, rescanned)
+        },
+        (Err(true), Some(155.0), Some(true), Some(595), true)
+    },
+    missing_hw_mon_attribute_no_rescan => {
+        |gpu_handle: &GpuHandle| {
+            fs::remove_file(gpu_handle.hw_monitors[0].get_path().join("fan1_input")).unwrap();
+
+            let calls = std::cell::Cell::new(0);
+            let result = gpu_handle.with_hw_mon(|hw_mon| {
+                calls.set(calls.get() + 1);
+                hw_mon.get_fan_current()
+            });
+            (result.map_err(|err| err.is_not_found()), calls.get())
+        },
+        (Err(true), 1)
+    },
+    wait_until_ready => {
+        |gpu_handle: &GpuHandle| {
+            let mut gpu_handle = gpu_handle.clone();
+            let od_file = gpu_handle.get_path().join("pp_od_clk_voltage");
+            let contents = fs::read(&od_file).unwrap();
+            fs::remove_file(&od_file).unwrap();
+
+            let writer = std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(100));
+                fs::write(od_file, contents).unwrap();
+            });
+            let ready = gpu_handle
+                .wait_until_ready(Duration::from_secs(5), &[Interface::HwMon, Interface::Overdrive])
+                .map_err(|err| err.to_string());
+            writer.join().unwrap();
+
+            let missing = gpu_handle
+                .wait_until_ready(Duration::from_millis(100), &[Interface::FanControl])
+                .map_err(|err| err.to_string());
+            (ready, missing)
+        },
+        (Ok(()), Err("io error: Timed out waiting for [FanControl]".to_owned()))
+    },
+    wait_until_ready_stale_hw_mon => {
+        |gpu_handle: &GpuHandle| {
+            let mut gpu_handle = gpu_handle.clone();
+            let hwmon_dir = gpu_handle.get_path().join("hwmon");
+            fs::rename(hwmon_dir.join("hwmon4"), hwmon_dir.join("hwmon7")).unwrap();
+
+            let ready = gpu_handle
+                .wait_until_ready(Duration::from_millis(100), &[Interface::HwMon])
+                .map_err(|err| err.to_string());
+            (ready, gpu_handle.hw_monitors[0].get_path().ends_with("hwmon7"))
+        },
+        (Ok(()), true)
+    },
+    concurrent_collect_timeout => {
+        |gpu_handle: &GpuHandle| {
+            let (vega56, _mockfs) = sysfs::create_mock_gpu_handle("vega56");
+            let handles = [gpu_handle.clone(), vega56];
+
+            let collector = ConcurrentCollector::new()
+                .max_threads(1)
+                .timeout(Duration::from_millis(200));
+            collector.collect(&handles, |handle| {
+                let (_, device_id) = handle.get_pci_id().unwrap();
+                if device_id == "687F" {
+                    // Simulate a hung device
+                    std::thread::sleep(Duration::from_secs(1));
+                }
+                device_id.to_owned()
+            })
+        },
+        vec![Some("67DF".to_owned()), None]
+    },
+    concurrent_sample => {
+        |gpu_handle: &GpuHandle| {
+            let samples = ConcurrentCollector::new()
+                .metrics([Metric::BusyPercent])
+                .sample(&[gpu_handle.clone(), gpu_handle.clone()]);
+            samples.iter().map(|sample| sample.as_ref().and_then(|sample| sample.busy_percent)).collect::<Vec<_>>()
+        },
+        vec![Some(11), Some(11)]
+    },
+    dynamic_info => {
+        |gpu_handle: &GpuHandle| {
+            let info = gpu_handle.get_dynamic_info();
+            (info.busy_percent, info.fan_rpm, info.power, info.errors.contains_key(&Metric::Power))
+        },
+        (Some(11), Some(595), Some(41.045), false)
+    },
 }
 
 test_with_hw_mon! {
@@ -96,4 +615,194 @@ test_with_hw_mon! {
     gpu_voltage => {
         HwMon::get_gpu_voltage, Ok(975)
     },
+    #[cfg(not(feature = "read-only"))]
+    configure_fan => {
+        |hw_mon: &HwMon| {
+            hw_mon.configure_fan(FanMode::FixedPwm(50)).unwrap();
+            let fixed = (hw_mon.get_fan_control_method().unwrap(), hw_mon.get_fan_pwm().unwrap());
+            hw_mon.configure_fan(FanMode::TargetRpm(1500)).unwrap();
+            let target = hw_mon.get_fan_target().unwrap();
+            hw_mon.configure_fan(FanMode::Automatic).unwrap();
+            (fixed, target, hw_mon.get_fan_control_method().unwrap())
+        },
+        ((FanControlMethod::Manual, 128), 1500, FanControlMethod::Auto)
+    },
+    list_sensors => {
+        |hw_mon: &HwMon| {
+            hw_mon
+                .list_sensors()
+                .unwrap()
+                .into_iter()
+                .map(|sensor| (sensor.kind, sensor.index, sensor.label, sensor.writable))
+                .collect::<Vec<_>>()
+        },
+        vec![
+            (SensorKind::Temperature, 1, Some("edge".to_owned()), false),
+            (SensorKind::Voltage, 0, Some("vddgfx".to_owned()), false),
+            (SensorKind::Power, 1, Some("PPT".to_owned()), true),
+            (SensorKind::Fan, 1, None, true),
+            (SensorKind::Pwm, 1, None, true),
+            (SensorKind::Frequency, 1, Some("sclk".to_owned()), false),
+            (SensorKind::Frequency, 2, Some("mclk".to_owned()), false),
+        ]
+    },
+    read_sensors => {
+        |hw_mon: &HwMon| {
+            hw_mon
+                .list_sensors()
+                .unwrap()
+                .iter()
+                .map(|sensor| (sensor.unit(), hw_mon.read_sensor(sensor).ok()))
+                .collect::<Vec<_>>()
+        },
+        vec![
+            (Some("°C"), Some(44.0)),
+            (Some("mV"), Some(975.0)),
+            (Some("W"), Some(41.045)),
+            (Some("RPM"), Some(595.0)),
+            (None, Some(35.0)),
+            (Some("MHz"), Some(798.08)),
+            (Some("MHz"), Some(1750.0)),
+        ]
+    },
+    #[cfg(not(feature = "read-only"))]
+    unknown_fan_control_method => {
+        |hw_mon: &HwMon| {
+            fs::write(hw_mon.get_path().join("pwm1_enable"), "5\n").unwrap();
+            let method = hw_mon.get_fan_control_method().unwrap();
+            hw_mon.set_fan_control_method(FanControlMethod::Auto).unwrap();
+            hw_mon.set_fan_control_method(method).unwrap();
+            (method, hw_mon.read_file("pwm1_enable").unwrap())
+        },
+        (FanControlMethod::Unknown(5), "5".to_owned())
+    },
+    configure_fan_invalid => {
+        |hw_mon: &HwMon| {
+            (
+                hw_mon.configure_fan(FanMode::FixedPwm(101)).map_err(|err| err.kind),
+                hw_mon.configure_fan(FanMode::TargetRpm(5000)).map_err(|err| err.kind),
+            )
+        },
+        (
+            Err(ErrorKind::NotAllowed(String::new())),
+            Err(ErrorKind::NotAllowed(String::new())),
+        )
+    },
+    #[cfg(not(feature = "read-only"))]
+    fan_pwm_ramped => {
+        |hw_mon: &HwMon| {
+            hw_mon.set_fan_pwm_ramped(100, &PwmRamp::new(20, Duration::ZERO))?;
+            hw_mon.get_fan_pwm()
+        },
+        Ok(100)
+    },
+    power_report => {
+        |hw_mon: &HwMon| {
+            fs::write(hw_mon.get_path().join("power1_average"), "77500000").unwrap();
+            hw_mon.get_power_report()
+        },
+        PowerReport {
+            average: Some(77.5),
+            input: None,
+            cap: Some(155.0),
+            cap_min: Some(0.0),
+            cap_max: Some(201.0),
+            cap_default: Some(155.0),
+            cap_utilization: Some(50.0),
+        }
+    },
+    power_cap_range => {
+        |hw_mon: &HwMon| {
+            let range = hw_mon.get_power_cap_range().unwrap();
+            for file in ["power1_cap_min", "power1_cap_max"] {
+                fs::remove_file(hw_mon.get_path().join(file)).unwrap();
+            }
+            (range, hw_mon.get_power_cap_range().map_err(|err| err.kind))
+        },
+        (
+            PowerCapRange {
+                range: Range::full(0, 201),
+                default: Some(155),
+            },
+            Err(ErrorKind::Unsupported(String::new())),
+        )
+    },
+    power_cap_range_rounding => {
+        |hw_mon: &HwMon| {
+            let path = hw_mon.get_path();
+            fs::write(path.join("power1_cap_min"), "10600000").unwrap();
+            fs::write(path.join("power1_cap_max"), "201600000").unwrap();
+            fs::write(path.join("power1_cap_default"), "155600000").unwrap();
+            let range = hw_mon.get_power_cap_range().unwrap();
+
+            fs::write(path.join("power1_cap_default"), "invalid").unwrap();
+            (range, hw_mon.get_power_cap_range().map_err(|err| err.kind))
+        },
+        (
+            PowerCapRange {
+                range: Range::full(11, 201),
+                default: Some(156),
+            },
+            Err(ErrorKind::ParseError { msg: String::new(), line: 0 }),
+        )
+    },
+    #[cfg(not(feature = "read-only"))]
+    power_cap_temporary => {
+        |hw_mon: &HwMon| {
+            hw_mon.set_power_cap(120.0).unwrap();
+            let guard = hw_mon.set_power_cap_temporary(100.0).unwrap();
+            let temporary = hw_mon.get_power_cap().unwrap();
+            drop(guard);
+            (temporary, hw_mon.get_power_cap().unwrap())
+        },
+        (100.0, 120.0)
+    },
+    #[cfg(not(feature = "read-only"))]
+    power_cap_temporary_restores_default => {
+        |hw_mon: &HwMon| {
+            fs::write(hw_mon.get_path().join("power1_cap"), "").unwrap();
+            let guard = hw_mon.set_power_cap_temporary(100.0).unwrap();
+            let restore_cap = guard.get_restore_cap();
+            guard.restore().unwrap();
+            (restore_cap, hw_mon.get_power_cap().unwrap())
+        },
+        (Some(155.0), 155.0)
+    },
+    temperatures_cached_channels => {
+        |hw_mon: &HwMon| {
+            hw_mon.get_temps();
+            // Sensors are only discovered once
+            fs::write(hw_mon.get_path().join("temp2_input"), "50000").unwrap();
+            fs::write(hw_mon.get_path().join("temp1_input"), "45000").unwrap();
+            hw_mon.get_temps().into_iter().map(|(label, temp)| (label, temp.current)).collect::<Vec<_>>()
+        },
+        vec![("edge".to_owned(), Some(45.0))]
+    },
+    temperatures_cached_crit => {
+        |hw_mon: &HwMon| {
+            hw_mon.get_temps();
+            // Critical limits are only read during the scan
+            fs::remove_file(hw_mon.get_path().join("temp1_crit")).unwrap();
+            fs::remove_file(hw_mon.get_path().join("temp1_crit_hyst")).unwrap();
+            hw_mon.get_temps().remove("edge").map(|temp| (temp.crit, temp.crit_hyst))
+        },
+        Some((Some(94.0), Some(-273.15)))
+    },
+}
+
+#[cfg(feature = "read-only")]
+#[test]
+fn read_only() {
+    let (gpu_handle, mockfs) = sysfs::create_mock_gpu_handle("rx580");
+
+    let err = gpu_handle
+        .set_power_force_performance_level(PerformanceLevel::High)
+        .unwrap_err();
+    assert_eq!(err.kind, ErrorKind::NotAllowed(String::new()));
+    assert_eq!(
+        mockfs
+            .read_file("power_dpm_force_performance_level")
+            .unwrap(),
+        "auto"
+    );
 }
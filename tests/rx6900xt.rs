@@ -1,4 +1,14 @@
-use amdgpu_sysfs::gpu_handle::{GpuHandle, PowerLevels};
+#![cfg_attr(feature = "read-only", allow(unused_imports))]
+#![allow(clippy::redundant_closure_call)]
+use amdgpu_sysfs::{
+    error::ErrorKind,
+    gpu_handle::{
+        gpu_metrics::GpuMetrics, Activity, ActivitySource, GpuHandle, GpuIdentity, PcieLevel,
+        PerformanceLevel, PowerLevel, PowerLevelKind, PowerLevels,
+    },
+    sysfs::SysFS,
+};
+use std::fs;
 
 mod sysfs;
 
@@ -14,4 +24,170 @@ test_with_handle! {
             active: Some(0)
         })
     },
+    pp_dpm_sclk_with_raw => {
+        |gpu_handle: &GpuHandle| gpu_handle.get_clock_levels_with_raw::<u64>(PowerLevelKind::CoreClock),
+        Ok(PowerLevels {
+            levels: vec![
+                PowerLevel { value: 500, raw: "0: 500Mhz *".to_owned() },
+                PowerLevel { value: 2660, raw: "1: 2660Mhz".to_owned() },
+            ],
+            active: Some(0)
+        })
+    },
+    pp_dpm_sclk_crlf => {
+        |gpu_handle: &GpuHandle| {
+            fs::write(gpu_handle.get_path().join("pp_dpm_sclk"), "0: 500Mhz * \r\n1:\t2660Mhz\r\n").unwrap();
+            gpu_handle.get_core_clock_levels()
+        },
+        Ok(PowerLevels {
+            levels: vec![500, 2660],
+            active: Some(0)
+        })
+    },
+    pp_dpm_pcie => {
+        GpuHandle::get_pcie_levels,
+        Ok(PowerLevels {
+            levels: vec![
+                PcieLevel {
+                    transfer_rate: 2500,
+                    width: 1,
+                    clockspeed: Some(310),
+                    raw: "2.5GT/s, x1 310Mhz".to_owned(),
+                },
+                PcieLevel {
+                    transfer_rate: 16000,
+                    width: 16,
+                    clockspeed: Some(619),
+                    raw: "16.0GT/s, x16 619Mhz".to_owned(),
+                },
+            ],
+            active: Some(1)
+        })
+    },
+    pp_dpm_socclk => {
+        GpuHandle::get_soc_clock_levels,
+        Ok(PowerLevels {
+            levels: vec![480, 800, 1200],
+            active: Some(1)
+        })
+    },
+    pp_dpm_fclk => {
+        GpuHandle::get_fabric_clock_levels,
+        Ok(PowerLevels {
+            levels: vec![500, 1302, 1941],
+            active: Some(1)
+        })
+    },
+    pp_dpm_dcefclk => {
+        GpuHandle::get_dcef_clock_levels,
+        Ok(PowerLevels {
+            levels: vec![417, 960, 1200],
+            active: Some(1)
+        })
+    },
+    pp_dpm_vclk => {
+        |gpu_handle: &GpuHandle| gpu_handle.get_clock_levels::<u64>(PowerLevelKind::Custom("pp_dpm_vclk".to_owned())),
+        Ok(PowerLevels {
+            levels: vec![0, 0],
+            active: None
+        })
+    },
+    gpu_metrics => {
+        GpuHandle::get_gpu_metrics,
+        Ok(GpuMetrics {
+            format_revision: 1,
+            content_revision: 3,
+            average_gfx_activity: Some(3),
+            average_mm_activity: Some(0),
+        }),
+        GpuHandle::get_media_activity,
+        Ok(0),
+    },
+    activity => {
+        GpuHandle::get_activity,
+        Ok(Activity {
+            percent: 3,
+            source: ActivitySource::BusyPercent,
+        })
+    },
+    activity_fallback => {
+        |gpu_handle: &GpuHandle| {
+            fs::remove_file(gpu_handle.get_path().join("gpu_busy_percent")).unwrap();
+            gpu_handle.get_activity()
+        },
+        Ok(Activity {
+            percent: 3,
+            source: ActivitySource::GpuMetrics,
+        })
+    },
+    #[cfg(not(feature = "read-only"))]
+    set_power_profile_mode_requires_manual => {
+        |gpu_handle: &GpuHandle| {
+            let auto = gpu_handle.set_active_power_profile_mode(1).map_err(|err| err.kind);
+            gpu_handle.set_power_force_performance_level(PerformanceLevel::Manual).unwrap();
+            gpu_handle.set_active_power_profile_mode(1).unwrap();
+            (auto, gpu_handle.read_file("pp_power_profile_mode"))
+        },
+        (Err(ErrorKind::NotAllowed(String::new())), Ok("1".to_owned()))
+    },
+    #[cfg(not(feature = "read-only"))]
+    set_performance_level_verified => {
+        |gpu_handle: &GpuHandle| {
+            gpu_handle.set_power_force_performance_level_verified(PerformanceLevel::Manual)?;
+            gpu_handle.get_power_force_performance_level()
+        },
+        Ok(PerformanceLevel::Manual)
+    },
+    aer_stats => {
+        |gpu_handle: &GpuHandle| {
+            fs::write(
+                gpu_handle.get_path().join("aer_dev_correctable"),
+                "RxErr 0\nBadTLP 3\nBadDLLP 1\nTOTAL_ERR_COR 4\n",
+            )
+            .unwrap();
+            let stats = gpu_handle.get_aer_stats().unwrap();
+            (stats.correctable.counters.get("BadTLP").copied(), stats.fatal.total, stats.total())
+        },
+        (Some(3), 0, 4)
+    },
+    static_info => {
+        |gpu_handle: &GpuHandle| {
+            let gc_dir = gpu_handle.get_path().join("ip_discovery/die/0/GC/0");
+            fs::create_dir_all(&gc_dir).unwrap();
+            fs::write(gc_dir.join("major"), "10\n").unwrap();
+            fs::write(gc_dir.join("minor"), "3\n").unwrap();
+            fs::write(gc_dir.join("revision"), "0\n").unwrap();
+
+            let info = gpu_handle.get_static_info();
+            (info.identity == gpu_handle.get_identity(), info.total_vram, info.max_link_speed, info.max_link_width, info.gc_version)
+        },
+        (true, Some(17163091968), Some("16.0 GT/s PCIe".to_owned()), Some("16".to_owned()), Some((10, 3, 0)))
+    },
+    identity => {
+        |gpu_handle: &GpuHandle| {
+            let identity = gpu_handle.get_identity();
+            let moved = GpuIdentity {
+                pci_slot_name: Some("0000:03:00.0".to_owned()),
+                vbios_version: None,
+                ..identity.clone()
+            };
+            let other = GpuIdentity {
+                unique_id: Some("0000000000000000".to_owned()),
+                ..identity.clone()
+            };
+            (identity.clone(), identity.matches(&moved), identity.matches(&other))
+        },
+        (
+            GpuIdentity {
+                driver: "amdgpu".to_owned(),
+                pci_id: Some("1002:73BF".to_owned()),
+                pci_subsys_id: Some("1DA2:440E".to_owned()),
+                pci_slot_name: Some("0000:0c:00.0".to_owned()),
+                unique_id: Some("946d7e1ef9b172dd".to_owned()),
+                vbios_version: Some("113-D4121EXT-CO1".to_owned()),
+            },
+            true,
+            false,
+        )
+    },
 }
@@ -1,6 +1,7 @@
+#![allow(clippy::redundant_closure_call)]
 mod sysfs;
 
-use amdgpu_sysfs::gpu_handle::{GpuHandle, PowerLevels};
+use amdgpu_sysfs::gpu_handle::{GpuHandle, PowerLevelKind, PowerLevels};
 
 test_with_handle! {
     "rx6950xt",
@@ -13,4 +14,15 @@ test_with_handle! {
             active: None,
         })
     },
+    invalid_dpm_sclk_flagged => {
+        |gpu_handle: &GpuHandle| gpu_handle.get_core_clock_levels().unwrap().is_valid(),
+        false
+    },
+    raw_dpm_sclk => {
+        |gpu_handle: &GpuHandle| gpu_handle.get_clock_levels_raw(PowerLevelKind::CoreClock),
+        Ok(PowerLevels {
+            levels: vec!["0Mhz".to_owned(), "0Mhz".to_owned()],
+            active: None,
+        })
+    },
 }
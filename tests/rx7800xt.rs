@@ -1,34 +1,51 @@
+#![cfg_attr(feature = "read-only", allow(unused_imports))]
 mod sysfs;
 
-use amdgpu_sysfs::gpu_handle::{
-    fan_control::{FanCurve, FanCurveRanges, FanInfo},
-    GpuHandle,
+use amdgpu_sysfs::{
+    gpu_handle::{
+        fan_control::{CurvePoint, FanCurve, FanCurveRanges, FanInfo},
+        GpuHandle,
+    },
+    sysfs::SysFS,
 };
 
 test_with_handle! {
     "rx7800xt",
     get_fan_acoustic_limit => {
         GpuHandle::get_fan_acoustic_limit,
-        Ok(FanInfo { current: 2450, allowed_range: Some((500,  3100)) })
+        Ok(FanInfo { current: 2450, allowed_range: Some(500..=3100) })
     },
     get_fan_acoustic_target => {
         GpuHandle::get_fan_acoustic_target,
-        Ok(FanInfo { current: 2200, allowed_range: Some((500,  3100)) })
+        Ok(FanInfo { current: 2200, allowed_range: Some(500..=3100) })
     },
     get_fan_target_temperature => {
         GpuHandle::get_fan_target_temperature,
-        Ok(FanInfo { current: 95, allowed_range: Some((25,  110)) })
+        Ok(FanInfo { current: 95, allowed_range: Some(25..=110) })
     },
     get_fan_minimum_pwm => {
         GpuHandle::get_fan_minimum_pwm,
-        Ok(FanInfo { current: 97, allowed_range: Some((20,  100)) })
+        Ok(FanInfo { current: 97, allowed_range: Some(20..=100) })
     },
     get_fan_curve => {
         GpuHandle::get_fan_curve,
-        Ok(FanCurve { points: vec![(0, 0); 5].into_boxed_slice(), allowed_ranges: Some(FanCurveRanges {temperature_range: 25..=100, speed_range: 20..=100 })})
+        Ok(FanCurve { points: vec![CurvePoint::default(); 5].into_boxed_slice(), allowed_ranges: Some(FanCurveRanges {temperature_range: 25..=100, speed_range: 20..=100 })})
     },
     get_fan_zero_rpm => {
         GpuHandle::get_fan_zero_rpm_enable,
         Ok(false),
+    },
+    #[cfg(not(feature = "read-only"))]
+    set_fan_acoustic_limit_cached_range => {
+        |gpu_handle: &GpuHandle| {
+            let _ = gpu_handle.set_fan_acoustic_limit(3000).unwrap();
+            // The mock file is overwritten by the value, so the following writes rely on the cached range
+            (
+                gpu_handle.set_fan_acoustic_limit(2000).is_ok(),
+                gpu_handle.set_fan_acoustic_limit(4000).is_err(),
+                gpu_handle.read_file("gpu_od/fan_ctrl/acoustic_limit_rpm_threshold").unwrap(),
+            )
+        },
+        (true, true, "2000".to_owned())
     }
 }
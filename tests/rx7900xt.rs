@@ -1,54 +1,459 @@
+#![cfg_attr(feature = "read-only", allow(unused_imports))]
 #![allow(clippy::redundant_closure_call)]
 mod sysfs;
 
-use amdgpu_sysfs::gpu_handle::{
-    fan_control::{FanCurve, FanCurveRanges, FanInfo},
-    GpuHandle,
+use amdgpu_sysfs::{
+    error::ErrorKind,
+    gpu_handle::{
+        fan_control::{CurvePoint, FanCurve, FanCurveRanges, FanInfo, PmfwSettings},
+        gpu_od::OdAttribute,
+        overdrive::{ClocksTable, ClocksTableGen},
+        power_profile_mode::{NamedHeuristics, StandardProfile},
+        quirks::Quirk,
+        GpuHandle, PerformanceLevel, PowerLevelKind, RestoreStep,
+    },
+    profile::{ClocksSettings, IssueKind, TuningProfile},
+    sysfs::SysFS,
 };
 
 test_with_handle! {
     "rx7900xt",
     get_fan_acoustic_limit => {
         GpuHandle::get_fan_acoustic_limit,
-        Ok(FanInfo { current: 3200, allowed_range: Some((500,  3200)) })
+        Ok(FanInfo { current: 3200, allowed_range: Some(500..=3200) })
     },
     get_fan_acoustic_target => {
         GpuHandle::get_fan_acoustic_target,
-        Ok(FanInfo { current: 1450, allowed_range: Some((500,  3200)) })
+        Ok(FanInfo { current: 1450, allowed_range: Some(500..=3200) })
     },
     get_fan_target_temperature => {
         GpuHandle::get_fan_target_temperature,
-        Ok(FanInfo { current: 83, allowed_range: Some((25,  105)) })
+        Ok(FanInfo { current: 83, allowed_range: Some(25..=105) })
     },
     get_fan_minimum_pwm => {
         GpuHandle::get_fan_minimum_pwm,
-        Ok(FanInfo { current: 15, allowed_range: Some((15,  100)) })
+        Ok(FanInfo { current: 15, allowed_range: Some(15..=100) })
     },
     get_fan_curve => {
         GpuHandle::get_fan_curve,
-        Ok(FanCurve { points: vec![(0, 0); 5].into_boxed_slice(), allowed_ranges: Some(FanCurveRanges {temperature_range: 25..=100, speed_range: 15..=100 })})
+        Ok(FanCurve { points: vec![CurvePoint::default(); 5].into_boxed_slice(), allowed_ranges: Some(FanCurveRanges {temperature_range: 25..=100, speed_range: 15..=100 })})
     },
     set_invalid_fan_curve => {
         |gpu_handle: &GpuHandle| {
             let mut curve = gpu_handle.get_fan_curve().unwrap();
-            curve.points[0].0 = 5;
-            curve.points[0].1 = 0;
+            curve.points[0].temperature = 5;
+            curve.points[0].speed = 0;
             gpu_handle.set_fan_curve(&curve).unwrap_err().to_string()
         },
         "not allowed: Temperature value 5 is outside of the allowed range 25..=100",
     },
 
+    #[cfg(not(feature = "read-only"))]
     set_valid_fan_curve => {
         |gpu_handle: &GpuHandle| {
             let mut curve = gpu_handle.get_fan_curve().unwrap();
-            curve.points[0] = (25, 15);
-            curve.points[1] = (40, 30);
-            curve.points[2] = (60, 65);
-            curve.points[3] = (70, 80);
-            curve.points[4] = (85, 100);
+            curve.points[0] = CurvePoint::new(25, 15);
+            curve.points[1] = CurvePoint::new(40, 30);
+            curve.points[2] = CurvePoint::new(60, 65);
+            curve.points[3] = CurvePoint::new(70, 80);
+            curve.points[4] = CurvePoint::new(85, 100);
             let handle = gpu_handle.set_fan_curve(&curve)?;
             handle.commit()
         },
         Ok(())
+    },
+    preview_clocks_commands => {
+        |gpu_handle: &GpuHandle| {
+            let ClocksTableGen::Vega20(mut table) = gpu_handle.get_clocks_table().unwrap() else {
+                panic!("Unexpected table format");
+            };
+            table.clear();
+            table.set_min_sclk(2800).unwrap();
+            table.set_max_sclk(3000).unwrap();
+            gpu_handle.preview_clocks_commands(&table.into())
+        },
+        Ok(vec!["s 1 3000".to_owned(), "s 0 2800".to_owned(), "s 1 3000".to_owned()])
+    },
+    clocks_table_eq => {
+        |gpu_handle: &GpuHandle| {
+            let table = gpu_handle.get_clocks_table().unwrap();
+            let unchanged = table == gpu_handle.get_clocks_table().unwrap();
+
+            let mut modified = table.clone();
+            modified.set_max_sclk(3000).unwrap();
+            (unchanged, modified == table)
+        },
+        (true, false)
+    },
+    #[cfg(not(feature = "read-only"))]
+    set_clocks_table_batched => {
+        |gpu_handle: &GpuHandle| {
+            let ClocksTableGen::Vega20(mut table) = gpu_handle.get_clocks_table().unwrap() else {
+                panic!("Unexpected table format");
+            };
+            table.clear();
+            table.set_max_sclk(3000).unwrap();
+            table.set_max_mclk(1300).unwrap();
+            let _ = gpu_handle.set_clocks_table_batched(&table.into()).unwrap();
+            gpu_handle.read_file("pp_od_clk_voltage")
+        },
+        Ok("s 1 3000\nm 1 1300".to_owned())
+    },
+    clocks_table_quirks => {
+        |gpu_handle: &GpuHandle| {
+            let mut gpu_handle = gpu_handle.clone();
+            gpu_handle.add_quirk(Quirk::MclkBeforeSclk);
+            gpu_handle.add_quirk(Quirk::MaxVoltageOffset(100));
+
+            let ClocksTableGen::Vega20(mut table) = gpu_handle.get_clocks_table().unwrap() else {
+                panic!("Unexpected table format");
+            };
+            table.clear();
+            table.set_max_sclk(3000).unwrap();
+            table.set_max_mclk(1300).unwrap();
+            let commands = gpu_handle.preview_clocks_commands(&table.clone().into()).unwrap();
+
+            table.voltage_offset = Some(-150);
+            let offset_error = gpu_handle.set_clocks_table(&table.into()).map(|_| ()).map_err(|err| err.kind);
+
+            (gpu_handle.quirks().to_vec(), commands, offset_error)
+        },
+        (
+            vec![Quirk::FanCurvePointsOutOfRange, Quirk::MclkBeforeSclk, Quirk::MaxVoltageOffset(100)],
+            vec!["m 1 1300".to_owned(), "s 1 3000".to_owned()],
+            Err(ErrorKind::NotAllowed(String::new())),
+        )
+    },
+    #[cfg(not(feature = "read-only"))]
+    fan_curve_quirk => {
+        |gpu_handle: &GpuHandle| {
+            gpu_handle
+                .write_file(
+                    "gpu_od/fan_ctrl/fan_curve",
+                    "OD_FAN_CURVE:\n0: 0C 0%\n1: 45C 20%\n2: 60C 40%\n3: 80C 70%\n4: 90C 100%\nOD_RANGE:\nFAN_CURVE(hotspot temp): 25C 100C\nFAN_CURVE(fan speed): 15% 100%\n",
+                )
+                .unwrap();
+            let curve = gpu_handle.get_fan_curve().unwrap();
+            (curve.points.to_vec(), curve.validate().is_ok())
+        },
+        ([(25, 15), (45, 20), (60, 40), (80, 70), (90, 100)].map(CurvePoint::from).to_vec(), true)
+    },
+    #[cfg(not(feature = "read-only"))]
+    default_clocks_table => {
+        |gpu_handle: &GpuHandle| {
+            let table = gpu_handle.get_clocks_table().unwrap();
+            let modified_table = gpu_handle
+                .read_file("pp_od_clk_voltage")
+                .unwrap()
+                .replace("2735Mhz", "2800Mhz");
+            gpu_handle.write_file("pp_od_clk_voltage", modified_table).unwrap();
+
+            let current_table = gpu_handle.get_clocks_table().unwrap();
+            let default_table = gpu_handle.get_default_clocks_table().unwrap();
+            (table.get_max_sclk(), current_table.get_max_sclk(), default_table.get_max_sclk())
+        },
+        (Some(2735), Some(2800), Some(2735))
+    },
+    #[cfg(not(feature = "read-only"))]
+    set_active_power_profile => {
+        |gpu_handle: &GpuHandle| {
+            gpu_handle.set_active_power_profile(StandardProfile::Compute).unwrap();
+            let compute = gpu_handle.read_file("pp_power_profile_mode").unwrap();
+            let missing = gpu_handle.set_active_power_profile(StandardProfile::Unknown("MISSING".to_owned()));
+            (compute, missing.is_err())
+        },
+        ("5".to_owned(), true)
+    },
+    #[cfg(not(feature = "read-only"))]
+    restore_custom_heuristics => {
+        |gpu_handle: &GpuHandle| {
+            let heuristics = gpu_handle.capture_custom_power_profile_mode_heuristics().unwrap();
+            gpu_handle.restore_custom_power_profile_mode_heuristics(&heuristics).unwrap();
+            gpu_handle.read_file("pp_power_profile_mode")
+        },
+        Ok("6 1 0 3 0 3 0 1310720 -6553 -6553".to_owned())
+    },
+    set_fan_curve_invalid_point => {
+        |gpu_handle: &GpuHandle| {
+            let curve = FanCurve {
+                points: [(30, 20), (40, 30), (50, 40), (60, 50), (110, 100)].map(CurvePoint::from).into(),
+                allowed_ranges: None,
+            };
+            let result = gpu_handle.set_fan_curve(&curve).map(|_| ());
+            (result.is_err(), gpu_handle.get_fan_curve().unwrap().points)
+        },
+        (true, vec![CurvePoint::default(); 5].into_boxed_slice())
+    },
+    #[cfg(not(feature = "read-only"))]
+    set_partial_fan_curve => {
+        |gpu_handle: &GpuHandle| {
+            let empty_curve = FanCurve { points: Box::new([]), allowed_ranges: None };
+            let empty = gpu_handle.set_fan_curve(&empty_curve).unwrap_err().to_string();
+
+            let curve = FanCurve::builder().points([(30, 20), (60, 50)]).build().unwrap();
+            let _ = gpu_handle.set_fan_curve(&curve).unwrap();
+            // Each point is written separately, so the mock file only contains the last one
+            (empty, gpu_handle.read_file("gpu_od/fan_ctrl/fan_curve").unwrap())
+        },
+        ("not allowed: The fan curve requires 5 points, got none".to_owned(), "4 60 50".to_owned())
+    },
+    #[cfg(not(feature = "read-only"))]
+    set_fan_curve_point => {
+        |gpu_handle: &GpuHandle| {
+            let missing_point = gpu_handle.set_fan_curve_point(5, 60, 50).map(|_| ()).map_err(|err| err.kind);
+            let _ = gpu_handle.set_fan_curve_point(2, 60, 50).unwrap();
+            let written = gpu_handle.read_file("gpu_od/fan_ctrl/fan_curve").unwrap();
+            (missing_point, written)
+        },
+        (Err(ErrorKind::NotAllowed(String::new())), "2 60 50".to_owned())
+    },
+    #[cfg(not(feature = "read-only"))]
+    default_fan_settings => {
+        |gpu_handle: &GpuHandle| {
+            gpu_handle.capture_default_fan_settings().unwrap();
+            let _ = gpu_handle.set_fan_minimum_pwm(30).unwrap();
+            gpu_handle.get_default_fan_settings()
+        },
+        Ok(PmfwSettings {
+            acoustic_limit: Some(3200),
+            acoustic_target: Some(1450),
+            target_temperature: Some(83),
+            minimum_pwm: Some(15),
+            zero_rpm_enable: None,
+            zero_rpm_stop_temperature: None,
+            curve: Some(FanCurve {
+                points: vec![CurvePoint::default(); 5].into_boxed_slice(),
+                allowed_ranges: Some(FanCurveRanges { temperature_range: 25..=100, speed_range: 15..=100 }),
+            }),
+        })
+    },
+    get_pmfw_settings => {
+        |gpu_handle: &GpuHandle| gpu_handle.get_pmfw_settings().map(|settings| (settings.acoustic_limit, settings.zero_rpm_enable)),
+        Ok((Some(3200), None))
+    },
+    #[cfg(not(feature = "read-only"))]
+    apply_pmfw_settings => {
+        |gpu_handle: &GpuHandle| {
+            let settings = PmfwSettings {
+                target_temperature: Some(90),
+                minimum_pwm: Some(20),
+                ..Default::default()
+            };
+            let invalid_settings = PmfwSettings {
+                minimum_pwm: Some(20),
+                zero_rpm_enable: Some(true),
+                ..Default::default()
+            };
+            let invalid = gpu_handle.apply_pmfw_settings(&invalid_settings).is_err();
+            let unchanged_pwm = gpu_handle.get_fan_minimum_pwm().unwrap().current;
+
+            let _ = gpu_handle.apply_pmfw_settings(&settings).unwrap();
+            (
+                invalid,
+                unchanged_pwm,
+                gpu_handle.read_file("gpu_od/fan_ctrl/fan_target_temperature").unwrap(),
+                gpu_handle.read_file("gpu_od/fan_ctrl/fan_minimum_pwm").unwrap(),
+            )
+        },
+        (true, 15, "90".to_owned(), "20".to_owned())
+    },
+    capture_tuning_profile => {
+        |gpu_handle: &GpuHandle| {
+            let profile = TuningProfile::capture(gpu_handle).unwrap();
+            (
+                profile.performance_level,
+                profile.clocks,
+                profile.power_cap,
+                profile.fan.map(|fan| (fan.acoustic_limit, fan.target_temperature, fan.minimum_pwm)),
+            )
+        },
+        (
+            None,
+            Some(ClocksSettings {
+                min_sclk: Some(500),
+                max_sclk: Some(2735),
+                min_mclk: Some(97),
+                max_mclk: Some(1250),
+                voltage_offset: Some(0),
+            }),
+            Some(290.0),
+            Some((Some(3200), Some(83), Some(15))),
+        )
+    },
+    validate_tuning_profile => {
+        |gpu_handle: &GpuHandle| {
+            let validate = |profile: TuningProfile| profile.validate(gpu_handle).map_err(|err| err.kind);
+            let clocks = |clocks: ClocksSettings| TuningProfile {
+                clocks: Some(clocks),
+                ..Default::default()
+            };
+
+            (
+                validate(TuningProfile::capture(gpu_handle).unwrap()),
+                validate(TuningProfile { power_cap: Some(500.0), ..Default::default() }),
+                validate(clocks(ClocksSettings { max_sclk: Some(6000), ..Default::default() })),
+                validate(clocks(ClocksSettings { voltage_offset: Some(-500), ..Default::default() })),
+                validate(TuningProfile {
+                    fan: Some(PmfwSettings { minimum_pwm: Some(5), ..Default::default() }),
+                    ..Default::default()
+                }),
+                validate(TuningProfile {
+                    performance_level: Some(PerformanceLevel::Auto),
+                    enabled_power_levels: [(PowerLevelKind::CoreClock, vec![0])].into(),
+                    ..Default::default()
+                }),
+            )
+        },
+        (
+            Ok(()),
+            Err(ErrorKind::NotAllowed(String::new())),
+            Err(ErrorKind::NotAllowed(String::new())),
+            Err(ErrorKind::NotAllowed(String::new())),
+            Err(ErrorKind::NotAllowed(String::new())),
+            Err(ErrorKind::NotAllowed(String::new())),
+        )
+    },
+    od_attributes => {
+        |gpu_handle: &GpuHandle| {
+            let attributes = gpu_handle.get_od_attributes().unwrap();
+            let minimum_pwm = attributes
+                .iter()
+                .find(|attribute| attribute.section == "FAN_MINIMUM_PWM")
+                .cloned()
+                .unwrap();
+            let sections = attributes
+                .into_iter()
+                .map(|attribute| (attribute.path, attribute.section))
+                .collect::<Vec<_>>();
+            (sections, minimum_pwm)
+        },
+        (
+            vec![
+                ("fan_ctrl/acoustic_limit_rpm_threshold".to_owned(), "OD_ACOUSTIC_LIMIT".to_owned()),
+                ("fan_ctrl/acoustic_target_rpm_threshold".to_owned(), "OD_ACOUSTIC_TARGET".to_owned()),
+                ("fan_ctrl/fan_curve".to_owned(), "OD_FAN_CURVE".to_owned()),
+                ("fan_ctrl/fan_minimum_pwm".to_owned(), "FAN_MINIMUM_PWM".to_owned()),
+                ("fan_ctrl/fan_target_temperature".to_owned(), "FAN_TARGET_TEMPERATURE".to_owned()),
+            ],
+            OdAttribute {
+                path: "fan_ctrl/fan_minimum_pwm".to_owned(),
+                section: "FAN_MINIMUM_PWM".to_owned(),
+                contents: "15".to_owned(),
+                od_range: [("MINIMUM_PWM".to_owned(), ("15".to_owned(), "100".to_owned()))].into(),
+            }
+        )
+    },
+    validate_tuning_profile_report => {
+        |gpu_handle: &GpuHandle| {
+            let profile = TuningProfile {
+                clocks: Some(ClocksSettings {
+                    max_sclk: Some(6000),
+                    max_mclk: Some(1200),
+                    ..Default::default()
+                }),
+                power_cap: Some(500.0),
+                fan: Some(PmfwSettings {
+                    minimum_pwm: Some(5),
+                    target_temperature: Some(50),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            let report = profile.validate_for(gpu_handle);
+            (
+                report.is_valid(),
+                report
+                    .issues
+                    .into_iter()
+                    .map(|issue| (issue.setting, issue.kind))
+                    .collect::<Vec<_>>(),
+            )
+        },
+        (
+            false,
+            vec![
+                ("max_sclk".to_owned(), IssueKind::Invalid),
+                ("power_cap".to_owned(), IssueKind::Invalid),
+                ("minimum_pwm".to_owned(), IssueKind::Invalid),
+            ]
+        )
+    },
+    validate_tuning_profile_clocks_order => {
+        |gpu_handle: &GpuHandle| {
+            let profile = TuningProfile {
+                clocks: Some(ClocksSettings {
+                    min_sclk: Some(2000),
+                    max_sclk: Some(1500),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            profile
+                .validate_for(gpu_handle)
+                .issues
+                .into_iter()
+                .map(|issue| (issue.setting, issue.kind))
+                .collect::<Vec<_>>()
+        },
+        vec![("clocks".to_owned(), IssueKind::Invalid)]
+    },
+    #[cfg(not(feature = "read-only"))]
+    apply_tuning_profile => {
+        |gpu_handle: &GpuHandle| {
+            let profile = TuningProfile {
+                performance_level: Some(PerformanceLevel::Manual),
+                power_cap: Some(270.0),
+                ..Default::default()
+            };
+            profile.apply(gpu_handle).unwrap();
+            (
+                gpu_handle.get_power_force_performance_level().unwrap(),
+                gpu_handle.hw_monitors[0].get_power_cap().unwrap(),
+            )
+        },
+        (PerformanceLevel::Manual, 270.0)
+    },
+    #[cfg(not(feature = "read-only"))]
+    restore_defaults => {
+        |gpu_handle: &GpuHandle| {
+            let report = gpu_handle.restore_defaults();
+            (
+                report.results.iter().map(|(step, _)| *step).collect::<Vec<_>>(),
+                report.errors().map(|(step, _)| step).collect::<Vec<_>>(),
+                gpu_handle.read_file("gpu_od/fan_ctrl/fan_curve").unwrap(),
+                // The last reset file is used for committing
+                gpu_handle.read_file("gpu_od/fan_ctrl/fan_minimum_pwm").unwrap(),
+                gpu_handle.get_path().join("gpu_od/fan_ctrl/fan_zero_rpm_enable").exists(),
+            )
+        },
+        (
+            vec![RestoreStep::ClocksTable, RestoreStep::FanSettings, RestoreStep::PowerCap],
+            vec![],
+            "r".to_owned(),
+            "c".to_owned(),
+            false,
+        )
+    },
+    #[cfg(not(feature = "read-only"))]
+    cached_power_profile_modes => {
+        |gpu_handle: &GpuHandle| {
+            let heuristic = |name: &str, value| NamedHeuristics {
+                clock_type: Some("GFXCLK".to_owned()),
+                values: [(name.to_owned(), value)].into(),
+            };
+            gpu_handle.set_custom_power_profile_mode_heuristics_by_name(&[heuristic("FPS", 60)]).unwrap();
+            gpu_handle.set_custom_power_profile_mode_heuristics_by_name(&[heuristic("BoosterFreq", 900)]).unwrap();
+            gpu_handle.set_active_power_profile(StandardProfile::Custom).unwrap();
+            let cached_table = gpu_handle.get_cached_power_profile_modes().unwrap();
+
+            gpu_handle.invalidate_power_profile_modes();
+            (
+                cached_table.active_name().map(str::to_owned),
+                cached_table.custom_heuristics().unwrap().components[0].values["BoosterFreq"],
+                cached_table.custom_heuristics().unwrap().components[0].values["FPS"],
+                gpu_handle.get_cached_power_profile_modes().is_err(),
+            )
+        },
+        (Some("CUSTOM".to_owned()), 900, 60, true)
     }
 }
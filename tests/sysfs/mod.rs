@@ -54,9 +54,10 @@ pub fn create_mock_gpu_handle(name: &str) -> (GpuHandle, MockSysFs) {
 
 #[macro_export]
 macro_rules! test_with_handle {
-    ($sysfs_name:expr, $($test_name:ident => {$($code:expr, $expected:expr),* $(,)?}),* $(,)?) => {
+    ($sysfs_name:expr, $($(#[$attr:meta])* $test_name:ident => {$($code:expr, $expected:expr),* $(,)?}),* $(,)?) => {
         $(
             #[test]
+            $(#[$attr])*
             fn $test_name() {
                 let (handle, _mockfs) = $crate::sysfs::create_mock_gpu_handle($sysfs_name);
                 $(
@@ -70,9 +71,10 @@ macro_rules! test_with_handle {
 
 #[macro_export]
 macro_rules! test_with_hw_mon {
-    ($sysfs_name:expr, $($test_name:ident => {$($code:expr, $expected:expr),* $(,)?}),* $(,)?) => {
+    ($sysfs_name:expr, $($(#[$attr:meta])* $test_name:ident => {$($code:expr, $expected:expr),* $(,)?}),* $(,)?) => {
         $(
             #[test]
+            $(#[$attr])*
             fn $test_name() {
                 let (handle, _mockfs) = $crate::sysfs::create_mock_gpu_handle($sysfs_name);
                 let hw_mon = handle.hw_monitors.first().expect("Handle has no hw monitor");